@@ -0,0 +1,74 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Meta, Token, parse_macro_input, punctuated::Punctuated};
+
+/// Derive `openshmem_benchmark::osm_pod::ShmemPod` for a `#[repr(C)]` or
+/// `#[repr(transparent)]` struct whose fields are all `ShmemPod` and which has
+/// no padding.
+#[proc_macro_derive(ShmemPod)]
+pub fn derive_shmem_pod(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ShmemPod can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "ShmemPod cannot be derived for generic structs",
+        ));
+    }
+    if !has_stable_layout(input)? {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ShmemPod requires #[repr(C)] or #[repr(transparent)]",
+        ));
+    }
+
+    let fields: Vec<_> = data.fields.iter().map(|field| &field.ty).collect();
+    let message = format!("{name} has padding and cannot be ShmemPod");
+
+    Ok(quote! {
+        const _: () = {
+            fn assert_shmem_pod<T: ::openshmem_benchmark::osm_pod::ShmemPod>() {}
+            fn assert_fields() {
+                #(assert_shmem_pod::<#fields>();)*
+            }
+
+            assert!(
+                ::std::mem::size_of::<#name>() == 0 #(+ ::std::mem::size_of::<#fields>())*,
+                #message
+            );
+        };
+
+        unsafe impl ::openshmem_benchmark::osm_pod::ShmemPod for #name {}
+    })
+}
+
+fn has_stable_layout(input: &DeriveInput) -> syn::Result<bool> {
+    for attr in &input.attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+
+        let reprs = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for repr in reprs {
+            if repr.path().is_ident("C") || repr.path().is_ident("transparent") {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
@@ -0,0 +1,524 @@
+//! `LD_PRELOAD` interposer that records the SHMEM calls of a C application as
+//! traces `trace-execution` can replay.
+//!
+//! Every `shmem_*` entry point defined here logs the call and forwards it to
+//! the next definition in the lookup order, found with `dlsym(RTLD_NEXT)`,
+//! usually the real library:
+//!
+//! ```text
+//! cargo build --release -p openshmem-benchmark-preload
+//! OSM_RECORD=traces/app oshrun -n 4 -x OSM_RECORD \
+//!     -x LD_PRELOAD=$PWD/target/release/libopenshmem_benchmark_preload.so ./app
+//! ```
+//!
+//! Each PE writes `{OSM_RECORD}_pe_{n}.csv`, `shmem_trace_pe_{n}.csv` if the
+//! variable is unset, in the format of the `record` feature of the main
+//! crate: `op_type,src,dst,size` followed by `timestamp_ns` since
+//! `shmem_init`. Calls before `shmem_init` are not recorded, and calls the
+//! library makes to itself while serving a call are not recorded twice.
+//!
+//! Only the names listed here are intercepted. Calls to anything else, or
+//! calls the application inlines from the SHMEM headers, pass through
+//! unrecorded.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    cell::Cell,
+    ffi::{c_char, c_int, c_long, c_longlong, c_short, c_void},
+    fs::File,
+    io::{BufWriter, Write},
+    mem::size_of,
+    sync::{
+        Mutex, Once, PoisonError,
+        atomic::{AtomicPtr, Ordering},
+    },
+    time::Instant,
+};
+
+type Team = *mut c_void;
+type Ctx = *mut c_void;
+
+/// Operation names, spelled as `trace-execution` parses them.
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Put,
+    PutNonBlocking,
+    Get,
+    GetNonBlocking,
+    Quiet,
+    Fence,
+    Barrier,
+    FetchAdd32,
+    FetchAdd64,
+    CompareAndSwap32,
+    CompareAndSwap64,
+    AtomicFetch,
+    AtomicSet,
+    WaitUntil,
+    Broadcast,
+    AllGather,
+    AllToAll,
+    AllReduce,
+}
+
+impl Op {
+    fn name(self) -> &'static str {
+        match self {
+            Op::Put => "PUT",
+            Op::PutNonBlocking => "PUTNONBLOCKING",
+            Op::Get => "GET",
+            Op::GetNonBlocking => "GETNONBLOCKING",
+            Op::Quiet => "QUIET",
+            Op::Fence => "FENCE",
+            Op::Barrier => "BARRIER",
+            Op::FetchAdd32 => "FETCHADD32",
+            Op::FetchAdd64 => "FETCHADD64",
+            Op::CompareAndSwap32 => "COMPAREANDSWAP32",
+            Op::CompareAndSwap64 => "COMPAREANDSWAP64",
+            Op::AtomicFetch => "ATOMICFETCH",
+            Op::AtomicSet => "ATOMICSET",
+            Op::WaitUntil => "WAITUNTIL",
+            Op::Broadcast => "BROADCAST",
+            Op::AllGather => "ALLGATHER",
+            Op::AllToAll => "ALLTOALL",
+            Op::AllReduce => "ALLREDUCE",
+        }
+    }
+}
+
+/// One call, without the parts the trace fills in.
+struct Event {
+    op: Op,
+    /// Target PE of point-to-point operations.
+    pe: Option<c_int>,
+    /// Bytes moved, per PE for collectives.
+    size: usize,
+}
+
+fn rma(op: Op, pe: c_int, size: usize) -> Event {
+    Event {
+        op,
+        pe: Some(pe),
+        size,
+    }
+}
+
+fn collective(op: Op, size: usize) -> Event {
+    Event { op, pe: None, size }
+}
+
+/// 32 or 64 bit variant of an atomic on `T`.
+fn sized<T>(op32: Op, op64: Op) -> Op {
+    if size_of::<T>() <= 4 { op32 } else { op64 }
+}
+
+fn fail(message: std::fmt::Arguments) -> ! {
+    eprintln!("openshmem-benchmark-preload: {message}");
+    std::process::abort()
+}
+
+/// A symbol resolved, on first use, to its next definition after this
+/// library.
+struct Next {
+    /// Nul-terminated.
+    name: &'static str,
+    ptr: AtomicPtr<c_void>,
+}
+
+impl Next {
+    const fn new(name: &'static str) -> Self {
+        Next {
+            name,
+            ptr: AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+
+    fn get(&self) -> *mut c_void {
+        let ptr = self.ptr.load(Ordering::Relaxed);
+        if !ptr.is_null() {
+            return ptr;
+        }
+        let ptr = unsafe { libc::dlsym(libc::RTLD_NEXT, self.name.as_ptr().cast::<c_char>()) };
+        if ptr.is_null() {
+            fail(format_args!(
+                "{} is not defined by any library loaded after this one",
+                self.name.trim_end_matches('\0')
+            ));
+        }
+        self.ptr.store(ptr, Ordering::Relaxed);
+        ptr
+    }
+}
+
+struct Trace {
+    out: BufWriter<File>,
+    pe: c_int,
+    start: Instant,
+}
+
+static TRACE: Mutex<Option<Trace>> = Mutex::new(None);
+
+thread_local! {
+    /// Set while a recorded call runs, so the library's calls to itself are
+    /// not recorded as well.
+    static INSIDE: Cell<bool> = const { Cell::new(false) };
+}
+
+fn with_trace<R>(f: impl FnOnce(&mut Option<Trace>) -> R) -> R {
+    f(&mut TRACE.lock().unwrap_or_else(PoisonError::into_inner))
+}
+
+fn start() {
+    static MY_PE: Next = Next::new("shmem_my_pe\0");
+    static AT_EXIT: Once = Once::new();
+
+    let my_pe: unsafe extern "C" fn() -> c_int = unsafe { std::mem::transmute(MY_PE.get()) };
+    let pe = unsafe { my_pe() };
+    let mut path = std::env::var_os("OSM_RECORD").unwrap_or_else(|| "shmem_trace".into());
+    path.push(format!("_pe_{pe}.csv"));
+
+    let mut out = File::create(&path)
+        .map(BufWriter::new)
+        .unwrap_or_else(|err| fail(format_args!("cannot create {}: {err}", path.display())));
+    writeln!(out, "op_type,src,dst,size,timestamp_ns")
+        .unwrap_or_else(|err| fail(format_args!("cannot write {}: {err}", path.display())));
+    with_trace(|trace| {
+        *trace = Some(Trace {
+            out,
+            pe,
+            start: Instant::now(),
+        })
+    });
+
+    // applications that exit without finalizing still get their trace
+    AT_EXIT.call_once(|| unsafe {
+        libc::atexit(stop_at_exit);
+    });
+}
+
+fn stop() {
+    if let Some(mut trace) = with_trace(Option::take) {
+        trace.out.flush().unwrap_or_else(|err| {
+            fail(format_args!("cannot write PE {}'s trace: {err}", trace.pe))
+        });
+    }
+}
+
+extern "C" fn stop_at_exit() {
+    stop();
+}
+
+fn record(event: Event) {
+    with_trace(|trace| {
+        let Some(trace) = trace else {
+            return;
+        };
+        let (src, dst) = match event.pe {
+            Some(pe) => (trace.pe, pe),
+            None => (-1, -1),
+        };
+        let timestamp_ns = trace.start.elapsed().as_nanos();
+        writeln!(
+            trace.out,
+            "{},{src},{dst},{},{timestamp_ns}",
+            event.op.name(),
+            event.size
+        )
+        .unwrap_or_else(|err| fail(format_args!("cannot write PE {}'s trace: {err}", trace.pe)));
+    });
+}
+
+/// Record `event` unless the call is nested in a recorded one, then make it.
+fn forward<R>(event: Event, call: impl FnOnce() -> R) -> R {
+    if INSIDE.replace(true) {
+        return call();
+    }
+    record(event);
+    let result = call();
+    INSIDE.set(false);
+    result
+}
+
+/// Define each function to record the event after `=>` and forward the call.
+macro_rules! interpose {
+    ($(fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)? => $event:expr;)*) => {$(
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+            static NEXT: Next = Next::new(concat!(stringify!($name), "\0"));
+            let next: unsafe extern "C" fn($($ty),*) $(-> $ret)? =
+                unsafe { std::mem::transmute(NEXT.get()) };
+            forward($event, || unsafe { next($($arg),*) })
+        }
+    )*};
+}
+
+/// The typed puts and gets of one element type.
+macro_rules! typed_rma {
+    ($($t:ty => $put:ident, $put_nbi:ident, $get:ident, $get_nbi:ident, $p:ident, $g:ident;)*) => {$(
+        interpose! {
+            fn $put(dest: *mut $t, source: *const $t, nelems: usize, pe: c_int)
+                => rma(Op::Put, pe, nelems * size_of::<$t>());
+            fn $put_nbi(dest: *mut $t, source: *const $t, nelems: usize, pe: c_int)
+                => rma(Op::PutNonBlocking, pe, nelems * size_of::<$t>());
+            fn $get(dest: *mut $t, source: *const $t, nelems: usize, pe: c_int)
+                => rma(Op::Get, pe, nelems * size_of::<$t>());
+            fn $get_nbi(dest: *mut $t, source: *const $t, nelems: usize, pe: c_int)
+                => rma(Op::GetNonBlocking, pe, nelems * size_of::<$t>());
+            fn $p(dest: *mut $t, value: $t, pe: c_int) => rma(Op::Put, pe, size_of::<$t>());
+            fn $g(source: *const $t, pe: c_int) -> $t => rma(Op::Get, pe, size_of::<$t>());
+        }
+    )*};
+}
+
+/// The puts and gets of `bits`-bit elements.
+macro_rules! sized_rma {
+    ($($bits:literal => $put:ident, $put_nbi:ident, $get:ident, $get_nbi:ident;)*) => {$(
+        interpose! {
+            fn $put(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+                => rma(Op::Put, pe, nelems * $bits / 8);
+            fn $put_nbi(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+                => rma(Op::PutNonBlocking, pe, nelems * $bits / 8);
+            fn $get(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+                => rma(Op::Get, pe, nelems * $bits / 8);
+            fn $get_nbi(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+                => rma(Op::GetNonBlocking, pe, nelems * $bits / 8);
+        }
+    )*};
+}
+
+/// The atomics and waits on one integer type.
+macro_rules! typed_atomics {
+    ($($t:ty => {
+        $fetch_add:ident, $add:ident, $fetch_inc:ident, $inc:ident, $compare_swap:ident,
+        $swap:ident, $set:ident, $fetch:ident, $fadd:ident, $finc:ident, $cswap:ident,
+        $wait_until:ident
+    })*) => {$(
+        interpose! {
+            fn $fetch_add(dest: *mut $t, value: $t, pe: c_int) -> $t
+                => rma(sized::<$t>(Op::FetchAdd32, Op::FetchAdd64), pe, size_of::<$t>());
+            fn $add(dest: *mut $t, value: $t, pe: c_int)
+                => rma(sized::<$t>(Op::FetchAdd32, Op::FetchAdd64), pe, size_of::<$t>());
+            fn $fetch_inc(dest: *mut $t, pe: c_int) -> $t
+                => rma(sized::<$t>(Op::FetchAdd32, Op::FetchAdd64), pe, size_of::<$t>());
+            fn $inc(dest: *mut $t, pe: c_int)
+                => rma(sized::<$t>(Op::FetchAdd32, Op::FetchAdd64), pe, size_of::<$t>());
+            fn $compare_swap(dest: *mut $t, cond: $t, value: $t, pe: c_int) -> $t
+                => rma(sized::<$t>(Op::CompareAndSwap32, Op::CompareAndSwap64), pe, size_of::<$t>());
+            fn $swap(dest: *mut $t, value: $t, pe: c_int) -> $t
+                => rma(Op::AtomicSet, pe, size_of::<$t>());
+            fn $set(dest: *mut $t, value: $t, pe: c_int) => rma(Op::AtomicSet, pe, size_of::<$t>());
+            fn $fetch(source: *const $t, pe: c_int) -> $t
+                => rma(Op::AtomicFetch, pe, size_of::<$t>());
+            fn $fadd(dest: *mut $t, value: $t, pe: c_int) -> $t
+                => rma(sized::<$t>(Op::FetchAdd32, Op::FetchAdd64), pe, size_of::<$t>());
+            fn $finc(dest: *mut $t, pe: c_int) -> $t
+                => rma(sized::<$t>(Op::FetchAdd32, Op::FetchAdd64), pe, size_of::<$t>());
+            fn $cswap(dest: *mut $t, cond: $t, value: $t, pe: c_int) -> $t
+                => rma(sized::<$t>(Op::CompareAndSwap32, Op::CompareAndSwap64), pe, size_of::<$t>());
+            fn $wait_until(ivar: *mut $t, cmp: c_int, value: $t)
+                => collective(Op::WaitUntil, size_of::<$t>());
+        }
+    )*};
+}
+
+/// The sum reductions over one element type, on teams and active sets.
+macro_rules! typed_sums {
+    ($($t:ty => $reduce:ident, $to_all:ident;)*) => {$(
+        interpose! {
+            fn $reduce(team: Team, dest: *mut $t, source: *const $t, nreduce: usize) -> c_int
+                => collective(Op::AllReduce, nreduce * size_of::<$t>());
+            fn $to_all(
+                dest: *mut $t,
+                source: *const $t,
+                nreduce: c_int,
+                pe_start: c_int,
+                log_pe_stride: c_int,
+                pe_size: c_int,
+                p_wrk: *mut $t,
+                p_sync: *mut c_long,
+            ) => collective(Op::AllReduce, nreduce as usize * size_of::<$t>());
+        }
+    )*};
+}
+
+/// The active-set collectives on `bits`-bit elements.
+macro_rules! sized_collectives {
+    ($($bits:literal => $broadcast:ident, $fcollect:ident, $collect:ident, $alltoall:ident;)*) => {$(
+        interpose! {
+            fn $broadcast(
+                dest: *mut c_void,
+                source: *const c_void,
+                nelems: usize,
+                pe_root: c_int,
+                pe_start: c_int,
+                log_pe_stride: c_int,
+                pe_size: c_int,
+                p_sync: *mut c_long,
+            ) => collective(Op::Broadcast, nelems * $bits / 8);
+            fn $fcollect(
+                dest: *mut c_void,
+                source: *const c_void,
+                nelems: usize,
+                pe_start: c_int,
+                log_pe_stride: c_int,
+                pe_size: c_int,
+                p_sync: *mut c_long,
+            ) => collective(Op::AllGather, nelems * $bits / 8);
+            fn $collect(
+                dest: *mut c_void,
+                source: *const c_void,
+                nelems: usize,
+                pe_start: c_int,
+                log_pe_stride: c_int,
+                pe_size: c_int,
+                p_sync: *mut c_long,
+            ) => collective(Op::AllGather, nelems * $bits / 8);
+            fn $alltoall(
+                dest: *mut c_void,
+                source: *const c_void,
+                nelems: usize,
+                pe_start: c_int,
+                log_pe_stride: c_int,
+                pe_size: c_int,
+                p_sync: *mut c_long,
+            ) => collective(Op::AllToAll, nelems * $bits / 8);
+        }
+    )*};
+}
+
+static INIT: Next = Next::new("shmem_init\0");
+static INIT_THREAD: Next = Next::new("shmem_init_thread\0");
+static FINALIZE: Next = Next::new("shmem_finalize\0");
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn shmem_init() {
+    let next: unsafe extern "C" fn() = unsafe { std::mem::transmute(INIT.get()) };
+    unsafe { next() };
+    start();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn shmem_init_thread(requested: c_int, provided: *mut c_int) -> c_int {
+    let next: unsafe extern "C" fn(c_int, *mut c_int) -> c_int =
+        unsafe { std::mem::transmute(INIT_THREAD.get()) };
+    let result = unsafe { next(requested, provided) };
+    if result == 0 {
+        start();
+    }
+    result
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn shmem_finalize() {
+    stop();
+    let next: unsafe extern "C" fn() = unsafe { std::mem::transmute(FINALIZE.get()) };
+    unsafe { next() };
+}
+
+interpose! {
+    fn shmem_putmem(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+        => rma(Op::Put, pe, nelems);
+    fn shmem_putmem_nbi(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+        => rma(Op::PutNonBlocking, pe, nelems);
+    fn shmem_getmem(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+        => rma(Op::Get, pe, nelems);
+    fn shmem_getmem_nbi(dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+        => rma(Op::GetNonBlocking, pe, nelems);
+
+    fn shmem_ctx_putmem(ctx: Ctx, dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+        => rma(Op::Put, pe, nelems);
+    fn shmem_ctx_putmem_nbi(
+        ctx: Ctx,
+        dest: *mut c_void,
+        source: *const c_void,
+        nelems: usize,
+        pe: c_int,
+    ) => rma(Op::PutNonBlocking, pe, nelems);
+    fn shmem_ctx_getmem(ctx: Ctx, dest: *mut c_void, source: *const c_void, nelems: usize, pe: c_int)
+        => rma(Op::Get, pe, nelems);
+    fn shmem_ctx_getmem_nbi(
+        ctx: Ctx,
+        dest: *mut c_void,
+        source: *const c_void,
+        nelems: usize,
+        pe: c_int,
+    ) => rma(Op::GetNonBlocking, pe, nelems);
+
+    fn shmem_quiet() => collective(Op::Quiet, 0);
+    fn shmem_ctx_quiet(ctx: Ctx) => collective(Op::Quiet, 0);
+    fn shmem_fence() => collective(Op::Fence, 0);
+    fn shmem_ctx_fence(ctx: Ctx) => collective(Op::Fence, 0);
+    fn shmem_barrier_all() => collective(Op::Barrier, 0);
+    fn shmem_barrier(pe_start: c_int, log_pe_stride: c_int, pe_size: c_int, p_sync: *mut c_long)
+        => collective(Op::Barrier, 0);
+    fn shmem_sync_all() => collective(Op::Barrier, 0);
+    fn shmem_team_sync(team: Team) -> c_int => collective(Op::Barrier, 0);
+
+    fn shmem_broadcastmem(
+        team: Team,
+        dest: *mut c_void,
+        source: *const c_void,
+        nelems: usize,
+        pe_root: c_int,
+    ) -> c_int => collective(Op::Broadcast, nelems);
+    fn shmem_fcollectmem(team: Team, dest: *mut c_void, source: *const c_void, nelems: usize) -> c_int
+        => collective(Op::AllGather, nelems);
+    fn shmem_collectmem(team: Team, dest: *mut c_void, source: *const c_void, nelems: usize) -> c_int
+        => collective(Op::AllGather, nelems);
+    fn shmem_alltoallmem(team: Team, dest: *mut c_void, source: *const c_void, nelems: usize) -> c_int
+        => collective(Op::AllToAll, nelems);
+}
+
+typed_rma! {
+    c_char => shmem_char_put, shmem_char_put_nbi, shmem_char_get, shmem_char_get_nbi, shmem_char_p, shmem_char_g;
+    c_short => shmem_short_put, shmem_short_put_nbi, shmem_short_get, shmem_short_get_nbi, shmem_short_p, shmem_short_g;
+    c_int => shmem_int_put, shmem_int_put_nbi, shmem_int_get, shmem_int_get_nbi, shmem_int_p, shmem_int_g;
+    c_long => shmem_long_put, shmem_long_put_nbi, shmem_long_get, shmem_long_get_nbi, shmem_long_p, shmem_long_g;
+    c_longlong => shmem_longlong_put, shmem_longlong_put_nbi, shmem_longlong_get, shmem_longlong_get_nbi, shmem_longlong_p, shmem_longlong_g;
+    f32 => shmem_float_put, shmem_float_put_nbi, shmem_float_get, shmem_float_get_nbi, shmem_float_p, shmem_float_g;
+    f64 => shmem_double_put, shmem_double_put_nbi, shmem_double_get, shmem_double_get_nbi, shmem_double_p, shmem_double_g;
+}
+
+sized_rma! {
+    8 => shmem_put8, shmem_put8_nbi, shmem_get8, shmem_get8_nbi;
+    16 => shmem_put16, shmem_put16_nbi, shmem_get16, shmem_get16_nbi;
+    32 => shmem_put32, shmem_put32_nbi, shmem_get32, shmem_get32_nbi;
+    64 => shmem_put64, shmem_put64_nbi, shmem_get64, shmem_get64_nbi;
+    128 => shmem_put128, shmem_put128_nbi, shmem_get128, shmem_get128_nbi;
+}
+
+typed_atomics! {
+    c_int => {
+        shmem_int_atomic_fetch_add, shmem_int_atomic_add, shmem_int_atomic_fetch_inc,
+        shmem_int_atomic_inc, shmem_int_atomic_compare_swap, shmem_int_atomic_swap,
+        shmem_int_atomic_set, shmem_int_atomic_fetch, shmem_int_fadd, shmem_int_finc,
+        shmem_int_cswap, shmem_int_wait_until
+    }
+    c_long => {
+        shmem_long_atomic_fetch_add, shmem_long_atomic_add, shmem_long_atomic_fetch_inc,
+        shmem_long_atomic_inc, shmem_long_atomic_compare_swap, shmem_long_atomic_swap,
+        shmem_long_atomic_set, shmem_long_atomic_fetch, shmem_long_fadd, shmem_long_finc,
+        shmem_long_cswap, shmem_long_wait_until
+    }
+    c_longlong => {
+        shmem_longlong_atomic_fetch_add, shmem_longlong_atomic_add,
+        shmem_longlong_atomic_fetch_inc, shmem_longlong_atomic_inc,
+        shmem_longlong_atomic_compare_swap, shmem_longlong_atomic_swap,
+        shmem_longlong_atomic_set, shmem_longlong_atomic_fetch, shmem_longlong_fadd,
+        shmem_longlong_finc, shmem_longlong_cswap, shmem_longlong_wait_until
+    }
+}
+
+typed_sums! {
+    c_int => shmem_int_sum_reduce, shmem_int_sum_to_all;
+    c_long => shmem_long_sum_reduce, shmem_long_sum_to_all;
+    c_longlong => shmem_longlong_sum_reduce, shmem_longlong_sum_to_all;
+    f32 => shmem_float_sum_reduce, shmem_float_sum_to_all;
+    f64 => shmem_double_sum_reduce, shmem_double_sum_to_all;
+}
+
+sized_collectives! {
+    32 => shmem_broadcast32, shmem_fcollect32, shmem_collect32, shmem_alltoall32;
+    64 => shmem_broadcast64, shmem_fcollect64, shmem_collect64, shmem_alltoall64;
+}
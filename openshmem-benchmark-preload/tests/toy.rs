@@ -0,0 +1,126 @@
+//! Runs `tests/toy/toy.c`, linked against the stub library next to it, under
+//! the interposer and checks the traces it writes. Needs a C compiler, `cc`
+//! or `$CC`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const NUM_PES: i32 = 2;
+
+fn cc(args: &[&str]) {
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&compiler)
+        .args(args)
+        .status()
+        .unwrap_or_else(|err| panic!("cannot run {compiler}: {err}"));
+    assert!(status.success(), "{compiler} {args:?} failed");
+}
+
+/// The cdylib cargo built with this test, in its `deps` directory, or the
+/// one `cargo build` put above it.
+fn interposer() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    [deps, deps.parent().unwrap()]
+        .iter()
+        .map(|dir| dir.join("libopenshmem_benchmark_preload.so"))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| {
+            panic!(
+                "no libopenshmem_benchmark_preload.so near {}",
+                deps.display()
+            )
+        })
+}
+
+fn build_toy(out: &Path) -> PathBuf {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/toy");
+    let (src, out_str) = (src.to_str().unwrap(), out.to_str().unwrap());
+    let stub = format!("{out_str}/libshmem_stub.so");
+    let toy = format!("{out_str}/toy");
+
+    cc(&[
+        "-shared",
+        "-fPIC",
+        "-o",
+        &stub,
+        &format!("{src}/stub_shmem.c"),
+    ]);
+    cc(&[
+        "-o",
+        &toy,
+        &format!("{src}/toy.c"),
+        &format!("-I{src}"),
+        &format!("-L{out_str}"),
+        "-lshmem_stub",
+        &format!("-Wl,-rpath,{out_str}"),
+    ]);
+    PathBuf::from(toy)
+}
+
+#[test]
+fn toy_program_traces() {
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("toy");
+    fs::create_dir_all(&out).unwrap();
+    let toy = build_toy(&out);
+    let prefix = out.join("trace");
+
+    for pe in 0..NUM_PES {
+        let trace = out.join(format!("trace_pe_{pe}.csv"));
+        let _ = fs::remove_file(&trace);
+
+        let status = Command::new(&toy)
+            .env("LD_PRELOAD", interposer())
+            .env("OSM_RECORD", &prefix)
+            .env("STUB_PE", pe.to_string())
+            .env("STUB_NUM_PES", NUM_PES.to_string())
+            .status()
+            .unwrap();
+        assert!(status.success(), "toy failed on PE {pe}");
+
+        let trace = fs::read_to_string(&trace).unwrap();
+        let mut lines = trace.lines();
+        assert_eq!(lines.next(), Some("op_type,src,dst,size,timestamp_ns"));
+
+        let rows = lines
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let ops = rows
+            .iter()
+            .map(|row| row[..4].join(","))
+            .collect::<Vec<_>>();
+        let next = (pe + 1) % NUM_PES;
+        assert_eq!(
+            ops,
+            [
+                // the barrier's nested quiet is not recorded
+                "BARRIER,-1,-1,0".to_string(),
+                format!("PUT,{pe},{next},64"),
+                format!("PUTNONBLOCKING,{pe},{next},32"),
+                "QUIET,-1,-1,0".to_string(),
+                format!("PUT,{pe},{next},4"),
+                format!("GET,{pe},{next},4"),
+                format!("GET,{pe},{next},32"),
+                "FENCE,-1,-1,0".to_string(),
+                format!("FETCHADD32,{pe},0,4"),
+                format!("COMPAREANDSWAP64,{pe},{next},8"),
+                format!("ATOMICSET,{pe},{next},8"),
+                "WAITUNTIL,-1,-1,8".to_string(),
+                "BROADCAST,-1,-1,64".to_string(),
+                "ALLGATHER,-1,-1,64".to_string(),
+                "ALLTOALL,-1,-1,16".to_string(),
+                "ALLREDUCE,-1,-1,4".to_string(),
+                "BARRIER,-1,-1,0".to_string(),
+            ]
+        );
+
+        let timestamps = rows
+            .iter()
+            .map(|row| row[4].parse::<u64>().unwrap())
+            .collect::<Vec<_>>();
+        assert!(timestamps.is_sorted(), "{timestamps:?}");
+    }
+}
@@ -0,0 +1,43 @@
+/* The part of the OpenSHMEM API the toy program uses, as declared by the
+ * stub library in stub_shmem.c. */
+
+#ifndef STUB_SHMEM_H
+#define STUB_SHMEM_H
+
+#include <stddef.h>
+
+typedef struct stub_team *shmem_team_t;
+
+extern shmem_team_t SHMEM_TEAM_WORLD;
+
+#define SHMEM_CMP_EQ 0
+
+void shmem_init(void);
+void shmem_finalize(void);
+int shmem_my_pe(void);
+int shmem_n_pes(void);
+void *shmem_malloc(size_t size);
+void shmem_free(void *ptr);
+
+void shmem_putmem(void *dest, const void *source, size_t nelems, int pe);
+void shmem_putmem_nbi(void *dest, const void *source, size_t nelems, int pe);
+void shmem_int_p(int *dest, int value, int pe);
+int shmem_int_g(const int *source, int pe);
+void shmem_double_get(double *dest, const double *source, size_t nelems, int pe);
+
+void shmem_quiet(void);
+void shmem_fence(void);
+void shmem_barrier_all(void);
+
+int shmem_int_atomic_fetch_add(int *dest, int value, int pe);
+long shmem_long_atomic_compare_swap(long *dest, long cond, long value, int pe);
+void shmem_long_atomic_set(long *dest, long value, int pe);
+void shmem_long_wait_until(long *ivar, int cmp, long value);
+
+int shmem_broadcastmem(shmem_team_t team, void *dest, const void *source, size_t nelems,
+                       int PE_root);
+int shmem_fcollectmem(shmem_team_t team, void *dest, const void *source, size_t nelems);
+int shmem_alltoallmem(shmem_team_t team, void *dest, const void *source, size_t nelems);
+int shmem_int_sum_reduce(shmem_team_t team, int *dest, const int *source, size_t nreduce);
+
+#endif
@@ -0,0 +1,107 @@
+/* Stand-in for an OpenSHMEM library, enough to run toy.c under the
+ * interposer. Each process is one PE, taken from STUB_PE and STUB_NUM_PES,
+ * and every remote access goes to the local copy of the object. */
+
+#include <stdlib.h>
+#include <string.h>
+
+#include "shmem.h"
+
+shmem_team_t SHMEM_TEAM_WORLD = (shmem_team_t)1;
+
+static int env_int(const char *name, int fallback) {
+    const char *value = getenv(name);
+    return value ? atoi(value) : fallback;
+}
+
+void shmem_init(void) {}
+void shmem_finalize(void) {}
+int shmem_my_pe(void) { return env_int("STUB_PE", 0); }
+int shmem_n_pes(void) { return env_int("STUB_NUM_PES", 1); }
+void *shmem_malloc(size_t size) { return calloc(1, size); }
+void shmem_free(void *ptr) { free(ptr); }
+
+void shmem_putmem(void *dest, const void *source, size_t nelems, int pe) {
+    (void)pe;
+    memmove(dest, source, nelems);
+}
+
+void shmem_putmem_nbi(void *dest, const void *source, size_t nelems, int pe) {
+    shmem_putmem(dest, source, nelems, pe);
+}
+
+void shmem_int_p(int *dest, int value, int pe) {
+    (void)pe;
+    *dest = value;
+}
+
+int shmem_int_g(const int *source, int pe) {
+    (void)pe;
+    return *source;
+}
+
+void shmem_double_get(double *dest, const double *source, size_t nelems, int pe) {
+    (void)pe;
+    memmove(dest, source, nelems * sizeof(double));
+}
+
+void shmem_quiet(void) {}
+void shmem_fence(void) {}
+
+/* Calls back into the API, as real libraries do, which the interposer must
+ * not record as a second operation. */
+void shmem_barrier_all(void) { shmem_quiet(); }
+
+int shmem_int_atomic_fetch_add(int *dest, int value, int pe) {
+    (void)pe;
+    int old = *dest;
+    *dest += value;
+    return old;
+}
+
+long shmem_long_atomic_compare_swap(long *dest, long cond, long value, int pe) {
+    (void)pe;
+    long old = *dest;
+    if (old == cond)
+        *dest = value;
+    return old;
+}
+
+void shmem_long_atomic_set(long *dest, long value, int pe) {
+    (void)pe;
+    *dest = value;
+}
+
+void shmem_long_wait_until(long *ivar, int cmp, long value) {
+    (void)cmp;
+    while (*(volatile long *)ivar != value) {
+    }
+}
+
+int shmem_broadcastmem(shmem_team_t team, void *dest, const void *source, size_t nelems,
+                       int PE_root) {
+    (void)team;
+    (void)PE_root;
+    memmove(dest, source, nelems);
+    return 0;
+}
+
+int shmem_fcollectmem(shmem_team_t team, void *dest, const void *source, size_t nelems) {
+    (void)team;
+    for (int pe = 0; pe < shmem_n_pes(); pe++)
+        memmove((char *)dest + pe * nelems, source, nelems);
+    return 0;
+}
+
+int shmem_alltoallmem(shmem_team_t team, void *dest, const void *source, size_t nelems) {
+    (void)team;
+    memmove(dest, source, nelems * shmem_n_pes());
+    return 0;
+}
+
+int shmem_int_sum_reduce(shmem_team_t team, int *dest, const int *source, size_t nreduce) {
+    (void)team;
+    for (size_t i = 0; i < nreduce; i++)
+        dest[i] = source[i] * shmem_n_pes();
+    return 0;
+}
@@ -0,0 +1,63 @@
+/* Issues one of each kind of operation the interposer records, checking that
+ * every call still reaches the library. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "shmem.h"
+
+#define CHECK(cond)                                                            \
+    do {                                                                       \
+        if (!(cond)) {                                                         \
+            fprintf(stderr, "toy: line %d: %s\n", __LINE__, #cond);           \
+            return 1;                                                          \
+        }                                                                      \
+    } while (0)
+
+int main(void) {
+    shmem_init();
+    int me = shmem_my_pe();
+    int npes = shmem_n_pes();
+    int next = (me + 1) % npes;
+
+    char *src = shmem_malloc(64 * npes);
+    char *dst = shmem_malloc(64 * npes);
+    double *values = shmem_malloc(4 * sizeof(double));
+    int *counter = shmem_malloc(sizeof(int));
+    long *word = shmem_malloc(sizeof(long));
+    memset(src, 'a' + me, 64 * npes);
+    for (int i = 0; i < 4; i++)
+        values[i] = i;
+    shmem_barrier_all();
+
+    shmem_putmem(dst, src, 64, next);
+    CHECK(dst[63] == 'a' + me);
+    shmem_putmem_nbi(dst + 64, src, 32, next);
+    shmem_quiet();
+    shmem_int_p(counter, 7, next);
+    CHECK(shmem_int_g(counter, next) == 7);
+    double copy[4];
+    shmem_double_get(copy, values, 4, next);
+    CHECK(copy[3] == 3.0);
+    shmem_fence();
+
+    CHECK(shmem_int_atomic_fetch_add(counter, 1, 0) == 7);
+    CHECK(shmem_long_atomic_compare_swap(word, 0, 5, next) == 0);
+    shmem_long_atomic_set(word, 9, next);
+    shmem_long_wait_until(word, SHMEM_CMP_EQ, 9);
+
+    CHECK(shmem_broadcastmem(SHMEM_TEAM_WORLD, dst, src, 64, 0) == 0);
+    CHECK(shmem_fcollectmem(SHMEM_TEAM_WORLD, dst, src, 64) == 0);
+    CHECK(shmem_alltoallmem(SHMEM_TEAM_WORLD, dst, src, 16) == 0);
+    CHECK(shmem_int_sum_reduce(SHMEM_TEAM_WORLD, counter, counter, 1) == 0);
+    CHECK(*counter == 8 * npes);
+    shmem_barrier_all();
+
+    shmem_free(word);
+    shmem_free(counter);
+    shmem_free(values);
+    shmem_free(dst);
+    shmem_free(src);
+    shmem_finalize();
+    return 0;
+}
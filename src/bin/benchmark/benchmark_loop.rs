@@ -1,16 +1,17 @@
 use core::panic;
 use std::{
-    arch::x86_64::__rdtscp,
     mem::transmute,
     ops::Deref,
     path::{Path, PathBuf},
-    sync::{Arc, atomic::AtomicBool},
     time::{Duration, Instant},
 };
 
 use bon::builder;
-use openshmem_benchmark::{osm_box::OsmBox, osm_scope, osm_team::OsmTeam, osm_vec::ShVec};
-use openshmem_sys::_SHMEM_SYNC_VALUE;
+use openshmem_benchmark::{
+    osm_barrier::Barrier, osm_cancel::GlobalCancel, osm_clock::Clock, osm_scope, osm_team::OsmTeam,
+    osm_vec::ShVec,
+};
+use openshmem_benchmark::osm_ffi::_SHMEM_SYNC_VALUE;
 
 use crate::{
     RangeBenchmarkData,
@@ -23,8 +24,8 @@ use crate::{
 #[builder]
 pub fn lantency_loop<'a>(
     scope: &osm_scope::OsmScope,
-    local_running: Arc<AtomicBool>,
-    running: &mut OsmBox<'a, AtomicBool>,
+    cancel: &mut GlobalCancel<'a>,
+    barrier: &mut dyn Barrier,
     operation: &Operation,
     epoch_per_iteration: usize,
     data: &mut RangeBenchmarkData<'a>,
@@ -40,18 +41,19 @@ pub fn lantency_loop<'a>(
     const PRIME: usize = 1_000_000_007;
     let mut seed = 0;
     let num_working_set = data.num_working_set();
-    let false_signal = OsmBox::new(AtomicBool::new(false), &scope);
-
-    let mut aux = 0;
+    let clock = Clock::calibrate();
 
     loop {
-        scope.barrier_all();
+        barrier.set_phase("iteration");
+        barrier.wait();
         let mut cycles = Vec::with_capacity(epoch_per_iteration);
 
-        if !running.load(std::sync::atomic::Ordering::SeqCst) {
+        if let Some(cancellation) = cancel.cancelled() {
+            println!("pe {}: stopping, {cancellation}", scope.my_pe());
             break;
         }
 
+        barrier.set_phase("measure");
         let now = Instant::now();
 
         for _ in 0..(epoch_per_iteration) {
@@ -63,7 +65,7 @@ pub fn lantency_loop<'a>(
 
             let dest = &mut data.dst_working_set[i][0];
 
-            let begin_cycle = unsafe { __rdtscp(&mut aux) };
+            let begin_cycle = clock.now();
 
             match operation {
                 Operation::Range(RangeOperation::Get(GetOperation::Get)) => {
@@ -107,7 +109,7 @@ pub fn lantency_loop<'a>(
                 _ => unreachable!("This operation should not be here. {operation:?}"),
             }
 
-            let end_cycle = unsafe { __rdtscp(&mut aux) };
+            let end_cycle = clock.now();
 
             cycles.push(end_cycle - begin_cycle);
         }
@@ -116,9 +118,10 @@ pub fn lantency_loop<'a>(
             Operation::Range(RangeOperation::Get(GetOperation::Get)) => {
                 if my_pe >= num_concurrency {
                     record_latency(
-                        running,
+                        cancel,
                         epoch_per_iteration,
                         &mut final_latency,
+                        &clock,
                         &cycles,
                         now,
                         my_pe,
@@ -128,9 +131,10 @@ pub fn lantency_loop<'a>(
             Operation::Range(RangeOperation::Put(PutOperation::Put)) => {
                 if my_pe < num_concurrency {
                     record_latency(
-                        running,
+                        cancel,
                         epoch_per_iteration,
                         &mut final_latency,
+                        &clock,
                         &cycles,
                         now,
                         my_pe,
@@ -140,9 +144,10 @@ pub fn lantency_loop<'a>(
             Operation::Range(RangeOperation::Broadcast(BroadcastOperation::Broadcast)) => {
                 if my_pe == 0 {
                     record_latency(
-                        running,
+                        cancel,
                         epoch_per_iteration,
                         &mut final_latency,
+                        &clock,
                         &cycles,
                         now,
                         my_pe,
@@ -151,9 +156,10 @@ pub fn lantency_loop<'a>(
             }
             Operation::Atomic { .. } => {
                 record_latency(
-                    running,
+                    cancel,
                     epoch_per_iteration,
                     &mut final_latency,
+                    &clock,
                     &cycles,
                     now,
                     my_pe,
@@ -163,30 +169,26 @@ pub fn lantency_loop<'a>(
             _ => unreachable!("This operation {operation} should not be here."),
         }
 
-        // let only the main pe to stop others
-        if !local_running.load(std::sync::atomic::Ordering::Relaxed) && my_pe == 0 {
-            // set the running flag to false
-            for i in 0..num_pe as i32 {
-                println!("pe {}: stopping pe {}", scope.my_pe(), i);
-                false_signal.put_to_nbi(running, i);
-            }
-        }
+        // deliver a pending Ctrl-C or timeout before the next barrier, so
+        // every PE sees it after that barrier
+        cancel.poll();
     }
 
     final_latency
 }
 
 fn record_latency<'a>(
-    running: &mut OsmBox<'a, AtomicBool>,
+    cancel: &GlobalCancel<'a>,
     epoch_per_iteration: usize,
     final_latency: &mut f64,
+    clock: &Clock,
     latency_cycles: &Vec<u64>,
     now: Instant,
     my_pe: usize,
 ) {
     let latency = now.elapsed();
 
-    if *final_latency == 0.0 || running.load(std::sync::atomic::Ordering::Relaxed) {
+    if *final_latency == 0.0 || cancel.cancelled().is_none() {
         println!(
             "Latency on Machine {my_pe}: {:.2} microseconds",
             latency.as_nanos() as f64 / epoch_per_iteration as f64 / 1000.0
@@ -204,9 +206,17 @@ fn record_latency<'a>(
         let mean_latency = cycles.iter().sum::<u64>() as f64 / cycles.len() as f64;
         let max_latency = cycles[cycles.len() - 1];
 
+        let micros = |cycles: f64| cycles * clock.ticks_to_ns(1) / 1000.0;
         println!(
-            "Latency on Machine {my_pe}: min: {:.2} median: {:.2} mean: {:.2} max: {:.2}",
-            min_latency, median_latency, mean_latency, max_latency
+            "Latency on Machine {my_pe}: min: {:.2} median: {:.2} mean: {:.2} max: {:.2} microseconds",
+            micros(min_latency as f64),
+            micros(median_latency),
+            micros(mean_latency),
+            micros(max_latency as f64)
+        );
+        println!(
+            "Latency on Machine {my_pe}: min: {min_latency} median: {median_latency:.2} mean: {mean_latency:.2} max: {max_latency} cycles at {:.3} GHz",
+            clock.frequency() / 1e9
         );
 
         *final_latency = latency.as_nanos() as f64 / epoch_per_iteration as f64 / 1000.0;
@@ -216,8 +226,8 @@ fn record_latency<'a>(
 #[builder]
 pub fn bandwidth_loop<'a>(
     scope: &osm_scope::OsmScope,
-    local_running: Arc<AtomicBool>,
-    running: &mut OsmBox<'a, AtomicBool>,
+    cancel: &mut GlobalCancel<'a>,
+    barrier: &mut dyn Barrier,
     operation: &Operation,
     epoch_per_iteration: usize,
     data: &mut RangeBenchmarkData<'a>,
@@ -275,18 +285,19 @@ pub fn bandwidth_loop<'a>(
     let mut psync = ShVec::with_capacity(num_pe, scope);
     psync.resize_with(num_pe, || _SHMEM_SYNC_VALUE as i64);
 
-    let false_signal = OsmBox::new(AtomicBool::new(false), &scope);
-
     loop {
-        scope.barrier_all();
+        barrier.set_phase("iteration");
+        barrier.wait();
 
-        if !running.load(std::sync::atomic::Ordering::SeqCst) {
+        if let Some(cancellation) = cancel.cancelled() {
+            println!("pe {}: stopping, {cancellation}", scope.my_pe());
             break;
         }
 
         let now = Instant::now();
 
         for epoch in 0..(epoch_per_iteration) {
+            barrier.set_phase("epoch");
             seed = (1 + seed * 7) % PRIME;
             let i = seed % num_working_set;
 
@@ -429,7 +440,7 @@ pub fn bandwidth_loop<'a>(
             }
 
             // let now = Instant::now();
-            scope.barrier_all();
+            barrier.wait();
             if epoch % 1000 == 0 {
                 // println!("pe {my_pe} {epoch} barrier elapsed time: {}", now.elapsed().as_micros());
             }
@@ -472,18 +483,13 @@ pub fn bandwidth_loop<'a>(
             }
         }
 
-        if final_throughput == 0.0 || running.load(std::sync::atomic::Ordering::Relaxed) {
+        if final_throughput == 0.0 || cancel.cancelled().is_none() {
             final_throughput = throughput;
         }
 
-        // let only the main pe to stop others
-        if !local_running.load(std::sync::atomic::Ordering::Relaxed) && my_pe == 0 {
-            // set the running flag to false
-            for i in 0..num_pe as i32 {
-                println!("pe {}: stopping pe {}", scope.my_pe(), i);
-                false_signal.put_to_nbi(running, i);
-            }
-        }
+        // deliver a pending Ctrl-C or timeout before the next barrier, so
+        // every PE sees it after that barrier
+        cancel.poll();
     }
 
     final_throughput
@@ -3,23 +3,26 @@
 use crate::benchmark_loop::bandwidth_loop;
 use std::iter::repeat_with;
 use std::ops::Deref;
-use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use benchmark_loop::lantency_loop;
 use bon::builder;
 use clap::Parser;
-use libc::gethostname;
+use openshmem_benchmark::osm_barrier::{
+    Barrier, BarrierAll, CentralCounterBarrier, DiagnosticBarrier, DisseminationBarrier, SyncAll,
+    TournamentBarrier,
+};
 use openshmem_benchmark::osm_box::OsmBox;
+use openshmem_benchmark::osm_cancel::GlobalCancel;
 use openshmem_benchmark::osm_scope;
-use openshmem_benchmark::osm_scope::OsmScope;
+use openshmem_benchmark::osm_scope::{OsmScope, RuntimeInfo};
 use openshmem_benchmark::osm_vec::ShVec;
 
 use layout::RangeBenchmarkData;
-use openshmem_sys::num_pes;
+use openshmem_benchmark::osm_ffi::num_pes;
 use ops::{
-    AtomicOperation, BroadcastOperation, GetOperation, Operation, PutOperation, RangeOperation,
+    AtomicOperation, BarrierKind, BroadcastOperation, GetOperation, Operation, PutOperation,
+    RangeOperation,
 };
 
 mod benchmark_loop;
@@ -45,6 +48,12 @@ struct Config {
     /// Measure Latency instead of Throughput
     /// Only valid for Blocking operations
     latency: bool,
+    #[arg(global = true, long, value_enum, default_value_t = BarrierKind::default())]
+    /// Barrier used between epochs and iterations
+    barrier: BarrierKind,
+    #[arg(global = true, long, default_value_t = 60)]
+    /// Seconds before the diagnostic barrier reports missing PEs
+    barrier_timeout: u64,
 }
 
 fn main() {
@@ -52,40 +61,39 @@ fn main() {
     benchmark(&config);
 }
 
-fn setup_exit_signal(timeout: Option<u64>, scope: &OsmScope) -> Arc<AtomicBool> {
-    let local_running = Arc::new(std::sync::atomic::AtomicBool::new(true));
-    let lr1 = local_running.clone();
-
-    ctrlc::set_handler(move || {
-        lr1.store(false, std::sync::atomic::Ordering::Relaxed);
-    })
-    .expect("Error setting Ctrl-C handler");
+fn setup_cancel<'a>(timeout: Option<u64>, scope: &'a OsmScope) -> GlobalCancel<'a> {
+    let cancel = GlobalCancel::new(scope);
+    cancel
+        .cancel_on_signal()
+        .expect("Error setting Ctrl-C handler");
 
     if let Some(timeout) = timeout {
         if scope.my_pe() == 0 {
-            let lr2 = local_running.clone();
-            std::thread::spawn(move || {
-                std::thread::sleep(std::time::Duration::from_secs(timeout));
-                lr2.store(false, std::sync::atomic::Ordering::Relaxed);
-            });
+            cancel.cancel_after(Duration::from_secs(timeout));
         }
     }
 
-    return local_running;
+    cancel
 }
 
-fn print_config(config: &Config, scope: &OsmScope) {
-    let pe = scope.my_pe();
-    let num_pe = scope.num_pes();
+fn make_barrier<'a>(config: &Config, scope: &'a OsmScope) -> Box<dyn Barrier + 'a> {
+    match config.barrier {
+        BarrierKind::BarrierAll => Box::new(BarrierAll::new(scope)),
+        BarrierKind::SyncAll => Box::new(SyncAll::new(scope)),
+        BarrierKind::Dissemination => Box::new(DisseminationBarrier::new(scope)),
+        BarrierKind::Tournament => Box::new(TournamentBarrier::new(scope)),
+        BarrierKind::CentralCounter => Box::new(CentralCounterBarrier::new(scope)),
+        BarrierKind::Diagnostic => Box::new(DiagnosticBarrier::new(
+            Duration::from_secs(config.barrier_timeout),
+            scope,
+        )),
+    }
+}
 
-    let hostname = unsafe {
-        let mut name = [0; 256];
-        let len = name.len();
-        gethostname(name.as_mut_ptr() as *mut _, len);
-        String::from_utf8_lossy(&name)
-            .trim_end_matches('\0')
-            .to_string()
-    };
+fn print_config(config: &Config, info: &RuntimeInfo) {
+    let pe = info.my_pe;
+    let num_pe = info.num_pes;
+    let hostname = &info.hostnames[pe as usize];
 
     // print config in format
     println!("Configuration on {}:{pe}:", hostname);
@@ -96,16 +104,23 @@ fn print_config(config: &Config, scope: &OsmScope) {
     println!("  Duration: {:?}", config.duration);
     println!("  Operation: {}", config.operation);
     println!("  Number of Working Set: {}", config.num_working_set);
+    println!("  Barrier: {}", config.barrier);
+    println!(
+        "  Runtime: {} {}.{}",
+        info.library_name, info.library_version.0, info.library_version.1
+    );
+    println!("  Node Rank: {}/{}", info.node_rank, info.node_size);
 }
 
 fn benchmark(cli: &Config) {
     let scope = osm_scope::OsmScope::init();
 
-    print_config(cli, &scope);
+    let runtime_info = scope.runtime_info();
+    print_config(cli, &runtime_info);
 
-    let local_running = setup_exit_signal(cli.duration, &scope);
+    let mut cancel = setup_cancel(cli.duration, &scope);
 
-    let mut running = OsmBox::new(AtomicBool::new(true), &scope);
+    let mut barrier = make_barrier(cli, &scope);
 
     let operation = &cli.operation;
     let epoch_size = cli.epoch_size;
@@ -150,8 +165,8 @@ fn benchmark(cli: &Config) {
     let final_result = if cli.latency {
         lantency_loop()
             .scope(&scope)
-            .local_running(local_running.clone())
-            .running(&mut running)
+            .cancel(&mut cancel)
+            .barrier(barrier.as_mut())
             .operation(operation)
             .epoch_per_iteration(cli.epoch_per_iteration)
             .data(&mut datas[data_id])
@@ -159,18 +174,24 @@ fn benchmark(cli: &Config) {
     } else {
         bandwidth_loop()
             .scope(&scope)
-            .local_running(local_running.clone())
-            .running(&mut running)
+            .cancel(&mut cancel)
+            .barrier(barrier.as_mut())
             .operation(operation)
             .epoch_per_iteration(cli.epoch_per_iteration)
             .data(&mut datas[data_id])
             .call()
     };
 
-    output(&scope, num_concurrency, final_result, &cli);
+    output(&scope, num_concurrency, final_result, &cli, &runtime_info);
 }
 
-fn output(scope: &OsmScope, num_concurrency: usize, final_result: f64, config: &Config) {
+fn output(
+    scope: &OsmScope,
+    num_concurrency: usize,
+    final_result: f64,
+    config: &Config,
+    runtime_info: &RuntimeInfo,
+) {
     // eprintln!("Final throughput: {:.2} messages/second", final_throughput);
     let my_pe = scope.my_pe() as usize;
     let op = &config.operation;
@@ -208,6 +229,10 @@ fn output(scope: &OsmScope, num_concurrency: usize, final_result: f64, config: &
     // sync all the pe
     scope.barrier_all();
     if scope.my_pe() == 0 {
+        println!(
+            "Runtime: {}",
+            serde_json::to_string(runtime_info).expect("Failed to serialize runtime info")
+        );
         println!("Throughput on all PEs:");
         for i in 0..(num_concurrency * 2) {
             if *results[i] == 0.0 {
@@ -54,6 +54,19 @@ pub enum OpenShmemOp {
     Fence,
 }
 
+/// Barrier used between epochs and iterations.
+#[derive(ValueEnum, Debug, Clone, Copy, Display, PartialEq, Default)]
+pub enum BarrierKind {
+    #[default]
+    BarrierAll,
+    SyncAll,
+    Dissemination,
+    Tournament,
+    CentralCounter,
+    /// Central counter that reports missing PEs after `--barrier-timeout`
+    Diagnostic,
+}
+
 #[derive(Subcommand, Debug, Clone, Copy, Display, PartialEq)]
 pub enum BroadcastOperation {
     Broadcast,
@@ -1,20 +1,22 @@
-use std::sync::{Arc, atomic::AtomicBool};
-
 use openshmem_benchmark::{
     osm_alloc::OsmMalloc,
-    osm_box::OsmBox,
+    osm_barrier::Barrier,
+    osm_cancel::GlobalCancel,
     osm_scope::{self, OsmScope},
+    osm_team::OsmTeam,
     osm_vec::ShVec,
 };
-use openshmem_sys::{_SHMEM_REDUCE_MIN_WRKDATA_SIZE, _SHMEM_REDUCE_SYNC_SIZE, _SHMEM_SYNC_VALUE};
+use openshmem_benchmark::osm_ffi::{_SHMEM_REDUCE_MIN_WRKDATA_SIZE, _SHMEM_REDUCE_SYNC_SIZE, _SHMEM_SYNC_VALUE};
 use quanta::Instant;
 
 use crate::operations::{Operation, OperationType};
 
-pub fn run(operations: &Vec<Operation>, scope: &OsmScope) -> (usize, f64) {
-    let mut false_signal = OsmBox::new(AtomicBool::new(false), &scope);
-    let mut running = OsmBox::new(AtomicBool::new(true), &scope);
-
+pub fn run(
+    operations: &Vec<Operation>,
+    scope: &OsmScope,
+    barrier: &mut dyn Barrier,
+    cancel: &mut GlobalCancel,
+) -> (usize, f64) {
     let max_data_size = operations.iter().map(|e| e.size).max().unwrap();
 
     let max_reduce_size = operations
@@ -22,7 +24,7 @@ pub fn run(operations: &Vec<Operation>, scope: &OsmScope) -> (usize, f64) {
         .filter(|e| e.op_type == OperationType::AllReduce)
         .map(|e| e.size)
         .max()
-        .unwrap();
+        .unwrap_or(0);
 
     let mut src = ShVec::<u8>::new(&scope);
     let mut dst = ShVec::<u8>::new(&scope);
@@ -31,6 +33,9 @@ pub fn run(operations: &Vec<Operation>, scope: &OsmScope) -> (usize, f64) {
     let num_pes = scope.num_pes() / 2;
 
     src.resize_with(max_data_size, || 0);
+    // atomics target a word of their own, `src` may be too short or unaligned
+    let mut word = ShVec::<i64>::with_capacity(1, &scope);
+    word.resize_with(1, || 0);
     dst.resize_with(max_data_size * (num_pes as usize) * 2, || 0);
 
     scope.barrier_all();
@@ -64,11 +69,17 @@ pub fn run(operations: &Vec<Operation>, scope: &OsmScope) -> (usize, f64) {
     let mut num_ops = 0;
 
     if scope.my_pe() >= num_pes {
+        barrier.set_phase("collectives");
         let mut counter = 0;
         for operation in operations.iter() {
             let cnt = std::cmp::min(operation.size, max_data_size);
             match operation.op_type {
                 // OperationType::Barrier => scope.barrier_all(),
+                OperationType::Broadcast => {
+                    // byte-sized and team-based, so no pSync and no rounding to words
+                    OsmTeam::world().broadcast(&src[..cnt], &mut dst[..cnt], 0);
+                    num_ops += 1;
+                }
                 OperationType::AllGather => {
                     num_ops += src[..cnt].all_gather(&mut dst, scope, &mut psync);
                 }
@@ -89,6 +100,7 @@ pub fn run(operations: &Vec<Operation>, scope: &OsmScope) -> (usize, f64) {
             }
         }
     } else {
+        barrier.set_phase("rma");
         for operation in operations.iter() {
             let cnt = std::cmp::min(operation.size, max_data_size);
             // periodically print the number of operations
@@ -126,17 +138,30 @@ pub fn run(operations: &Vec<Operation>, scope: &OsmScope) -> (usize, f64) {
                     // scope.barrier_all();
                 }
                 OperationType::Fence => scope.fence(),
+                OperationType::Quiet => scope.quiet(),
                 OperationType::FetchAdd32 => {
-                    src.fetch_add_i32(1, my_pe + num_pes as i32);
+                    word.cast_slice_mut::<i32>()[..1].fetch_add_i32(1, my_pe + num_pes as i32);
                 }
                 OperationType::FetchAdd64 => {
-                    src.fetch_add_i64(1, my_pe + num_pes as i32);
+                    word.fetch_add_i64(1, my_pe + num_pes as i32);
                 }
                 OperationType::CompareAndSwap32 => {
-                    src.compare_and_swap_i32(1, 1, my_pe + num_pes as i32);
+                    word.cast_slice_mut::<i32>()[..1].compare_and_swap_i32(1, 1, my_pe + num_pes as i32);
                 }
                 OperationType::CompareAndSwap64 => {
-                    src.compare_and_swap_i64(1, 1, my_pe + num_pes as i32);
+                    word.compare_and_swap_i64(1, 1, my_pe + num_pes as i32);
+                }
+                OperationType::AtomicFetch => {
+                    word[0].atomic_fetch(my_pe + num_pes as i32);
+                }
+                OperationType::AtomicSet => {
+                    word[0].atomic_set(1, my_pe + num_pes as i32);
+                }
+                // released by a put in another PE's trace
+                OperationType::WaitUntil => {}
+                OperationType::Broadcast => {
+                    OsmTeam::world().broadcast(&src[..cnt], &mut dst[..cnt], 0);
+                    num_ops += 1;
                 }
                 OperationType::AllGather => {
                     num_ops += src[..cnt].all_gather(&mut dst, scope, &mut psync);
@@ -160,12 +185,11 @@ pub fn run(operations: &Vec<Operation>, scope: &OsmScope) -> (usize, f64) {
         }
     }
 
-    scope.barrier_all();
+    barrier.wait();
     let end = Instant::now();
 
-    false_signal.store(false, std::sync::atomic::Ordering::SeqCst);
-    scope.barrier_all();
-    false_signal.put_to_nbi(&mut running, 1);
+    // a Ctrl-C during the trial ends the replay on every PE after it
+    cancel.poll();
     scope.barrier_all();
 
     return (num_ops, end.duration_since(start).as_secs_f64());
@@ -1,9 +1,13 @@
 pub mod operations;
 
-use std::{fs::File, io::BufReader};
+use std::{fs::File, io::BufReader, time::Duration};
 
 use clap::Parser;
-use openshmem_benchmark::osm_scope;
+use openshmem_benchmark::{
+    osm_barrier::{Barrier, BarrierAll, DiagnosticBarrier},
+    osm_cancel::GlobalCancel,
+    osm_scope::OsmScope,
+};
 
 use crate::operations::Operation;
 
@@ -13,6 +17,13 @@ struct Args {
     trace_file: String,
     #[arg(short, long)]
     small_message: bool,
+    /// Report PEs missing from the end-of-trace barrier after this many seconds
+    #[arg(long)]
+    barrier_timeout: Option<u64>,
+    /// Replay on this many simulated PEs and report data races between them
+    #[cfg(feature = "sim")]
+    #[arg(long)]
+    simulate: Option<usize>,
 }
 
 pub mod execution;
@@ -21,19 +32,50 @@ fn main() {
     let args = Args::parse();
     println!("Trace file: {}", args.trace_file);
 
-    let trace_file = File::open(args.trace_file).unwrap();
+    let trace_file = File::open(&args.trace_file).unwrap();
     let reader = BufReader::new(trace_file);
     let operations = csv::Reader::from_reader(reader)
         .deserialize::<Operation>()
         .map(|e| e.unwrap())
         .collect::<Vec<_>>();
-    let scope = osm_scope::OsmScope::init();
+
+    #[cfg(feature = "sim")]
+    if let Some(num_pes) = args.simulate {
+        use openshmem_benchmark::osm_sim::{self, SimConfig};
+
+        let config = SimConfig {
+            num_pes,
+            check_races: true,
+            ..Default::default()
+        };
+        let report = osm_sim::run(config, |scope| replay(&args, &operations, scope));
+        eprintln!("Data races: {}", report.races.len());
+        if !report.races.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    replay(&args, &operations, OsmScope::init());
+}
+
+fn replay(args: &Args, operations: &Vec<Operation>, scope: OsmScope) {
+    let mut barrier: Box<dyn Barrier> = match args.barrier_timeout {
+        Some(timeout) => Box::new(DiagnosticBarrier::new(Duration::from_secs(timeout), &scope)),
+        None => Box::new(BarrierAll::new(&scope)),
+    };
+
+    let mut cancel = GlobalCancel::new(&scope);
+    cancel
+        .cancel_on_signal()
+        .expect("Error setting Ctrl-C handler");
 
     let min_sec = 10.0;
     let mut num_ops = 0;
     let mut times = Vec::new();
     loop {
-        let (each_num_ops, time) = execution::run(&operations, &scope);
+        let (each_num_ops, time) =
+            execution::run(operations, &scope, barrier.as_mut(), &mut cancel);
         println!("Trial {}: {}", times.len(), time);
         println!("current Op/s (in {:0.2}s): {:0.2}", time, each_num_ops as f64 / time);
         println!("Num ops: {}", each_num_ops);
@@ -43,6 +85,10 @@ fn main() {
         if times.iter().sum::<f64>() >= min_sec {
             break;
         }
+        if let Some(cancellation) = cancel.cancelled() {
+            eprintln!("Stopping after {} trials, {cancellation}", times.len());
+            break;
+        }
     }
 
     let throughput = num_ops as f64 / (times.iter().sum::<f64>());
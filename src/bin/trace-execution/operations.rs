@@ -10,10 +10,15 @@ pub enum OperationType {
     GetNonBlocking,
     Barrier,
     Fence,
+    Quiet,
     FetchAdd32,
     FetchAdd64,
     CompareAndSwap32,
     CompareAndSwap64,
+    AtomicFetch,
+    AtomicSet,
+    WaitUntil,
+    Broadcast,
     AllToAll,
     AllReduce,
     AllGather,
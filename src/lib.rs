@@ -1,10 +1,31 @@
 #![feature(allocator_api)]
 
+pub mod osm_active_message;
 pub mod osm_alloc;
 pub mod osm_arc;
+pub mod osm_array;
+pub mod osm_barrier;
 pub mod osm_box;
+pub mod osm_cancel;
+pub mod osm_channel;
+pub mod osm_clock;
+pub mod osm_collective;
+pub mod osm_context;
+pub mod osm_error;
+pub mod osm_ffi;
+pub mod osm_future;
+pub mod osm_hashmap;
+pub mod osm_object;
+pub mod osm_pod;
+pub mod osm_queue;
+pub mod osm_reduce;
+#[cfg(feature = "record")]
+pub mod osm_record;
 pub mod osm_scope;
+#[cfg(feature = "sim")]
+pub mod osm_sim;
 pub mod osm_slice;
+pub mod osm_static;
 pub mod osm_vec;
 pub mod osm_team;
 pub mod osm_wrapper;
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use ref_cast::RefCast;
+
+use crate::{osm_ffi::shmem_fence, osm_scope::OsmScope, osm_slice::OsmSlice, osm_vec::ShVec};
+
+const HEADER_SIZE: usize = 8;
+
+pub type HandlerId = u32;
+
+type Handler<'a> = Box<dyn FnMut(i32, &[u8]) + 'a>;
+
+/// Active-message endpoint with per-source inbound rings on every PE.
+///
+/// Each PE hosts `capacity` fixed-size records per source PE. A record is a
+/// `(handler_id, len)` header followed by up to `max_payload` bytes. Senders
+/// stage the record locally, `put_to_nbi` it into the target's ring, fence and
+/// then bump the target's tail counter for that source. The target runs the
+/// registered handlers for all published records when it calls
+/// [`ActiveMessages::progress`] and writes the consumed count back to the
+/// sender for flow control.
+///
+/// Handlers receive the source PE and the payload. They cannot send messages
+/// themselves; push follow-up work to local state and send it after
+/// `progress` returns. Construction is collective.
+pub struct ActiveMessages<'a> {
+    inbox: ShVec<'a, u8>,
+    tails: ShVec<'a, i64>,
+    heads: ShVec<'a, i64>,
+    staging: Vec<u8>,
+    handlers: HashMap<HandlerId, Handler<'a>>,
+    sent: Vec<i64>,
+    acked: Vec<i64>,
+    processed: Vec<i64>,
+    my_pe: i32,
+    num_pes: usize,
+    capacity: usize,
+    max_payload: usize,
+    record_size: usize,
+}
+
+impl<'a> ActiveMessages<'a> {
+    pub fn new(capacity: usize, max_payload: usize, scope: &'a OsmScope) -> Self {
+        assert!(capacity > 0, "ActiveMessages requires a non-zero capacity");
+
+        let num_pes = scope.num_pes() as usize;
+        let record_size = (HEADER_SIZE + max_payload).next_multiple_of(8);
+        let ring_bytes = num_pes * capacity * record_size;
+
+        let mut inbox = ShVec::with_capacity(ring_bytes, scope);
+        inbox.resize_with(ring_bytes, || 0);
+        let mut tails = ShVec::with_capacity(num_pes, scope);
+        tails.resize_with(num_pes, || 0);
+        let mut heads = ShVec::with_capacity(num_pes, scope);
+        heads.resize_with(num_pes, || 0);
+
+        scope.barrier_all();
+
+        ActiveMessages {
+            inbox,
+            tails,
+            heads,
+            staging: vec![0; ring_bytes],
+            handlers: HashMap::new(),
+            sent: vec![0; num_pes],
+            acked: vec![0; num_pes],
+            processed: vec![0; num_pes],
+            my_pe: scope.my_pe(),
+            num_pes,
+            capacity,
+            max_payload,
+            record_size,
+        }
+    }
+
+    pub fn max_payload(&self) -> usize {
+        self.max_payload
+    }
+
+    /// Register `handler` under `id`, replacing any previous handler.
+    ///
+    /// Every PE that may receive messages for `id` has to register it.
+    pub fn register(&mut self, id: HandlerId, handler: impl FnMut(i32, &[u8]) + 'a) {
+        self.handlers.insert(id, Box::new(handler));
+    }
+
+    /// Send `payload` to be handled by `handler_id` on `target_pe`.
+    ///
+    /// If the target's ring for this PE is full, this keeps running local
+    /// handlers until the target has consumed a record.
+    pub fn send(&mut self, target_pe: i32, handler_id: HandlerId, payload: &[u8]) {
+        assert!(
+            payload.len() <= self.max_payload,
+            "active message payload of {} bytes exceeds the maximum of {}",
+            payload.len(),
+            self.max_payload
+        );
+
+        let target = target_pe as usize;
+        let capacity = self.capacity as i64;
+        while self.sent[target] - self.acked[target] >= capacity {
+            self.progress();
+            self.acked[target] = self.heads[target].atomic_fetch(self.my_pe);
+        }
+
+        // staging is laid out by target, the target's inbox by source
+        let staged = self.record_offset(target, self.sent[target]);
+        let remote = self.record_offset(self.my_pe as usize, self.sent[target]);
+        let record = &mut self.staging[staged..staged + self.record_size];
+        record[..4].copy_from_slice(&handler_id.to_ne_bytes());
+        record[4..HEADER_SIZE].copy_from_slice(&(payload.len() as u32).to_ne_bytes());
+        record[HEADER_SIZE..HEADER_SIZE + payload.len()].copy_from_slice(payload);
+
+        let len = HEADER_SIZE + payload.len();
+        OsmSlice::ref_cast(&self.staging[staged..staged + len])
+            .put_to_nbi(&mut self.inbox[remote..remote + len], target_pe);
+        self.sent[target] += 1;
+
+        // the record must be delivered before the target sees the new tail
+        unsafe { shmem_fence() };
+        self.tails[self.my_pe as usize].atomic_set(self.sent[target], target_pe);
+    }
+
+    /// Run the handlers for every record published to this PE so far.
+    ///
+    /// Returns the number of handled messages.
+    pub fn progress(&mut self) -> usize {
+        let mut handled = 0;
+
+        for source in 0..self.num_pes {
+            let available = self.tails[source].atomic_fetch(self.my_pe);
+            if available == self.processed[source] {
+                continue;
+            }
+
+            while self.processed[source] < available {
+                let offset = self.record_offset(source, self.processed[source]);
+                let record: &[u8] = &self.inbox[offset..offset + self.record_size];
+                let handler_id = HandlerId::from_ne_bytes(record[..4].try_into().unwrap());
+                let len = u32::from_ne_bytes(record[4..HEADER_SIZE].try_into().unwrap()) as usize;
+
+                let handler = self.handlers.get_mut(&handler_id).unwrap_or_else(|| {
+                    panic!("no active message handler registered for id {handler_id}")
+                });
+                handler(source as i32, &record[HEADER_SIZE..HEADER_SIZE + len]);
+
+                self.processed[source] += 1;
+                handled += 1;
+            }
+
+            self.heads[self.my_pe as usize].atomic_set(self.processed[source], source as i32);
+        }
+
+        handled
+    }
+
+    /// Collectively wait until every message sent so far has been handled.
+    ///
+    /// All `send` calls have to return on every PE before any PE enters this.
+    pub fn quiesce(&mut self, scope: &OsmScope) {
+        scope.quiet();
+        scope.barrier_all();
+        self.progress();
+        scope.barrier_all();
+    }
+
+    fn record_offset(&self, source: usize, sequence: i64) -> usize {
+        let slot = (sequence % self.capacity as i64) as usize;
+        (source * self.capacity + slot) * self.record_size
+    }
+}
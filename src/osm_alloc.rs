@@ -2,9 +2,7 @@ use std::{
     alloc::Allocator, ptr::NonNull
 };
 
-use openshmem_sys::{shfree, shmemalign, shrealloc};
-
-use crate::osm_scope::OsmScope;
+use crate::{osm_ffi::{shfree, shmemalign, shrealloc}, osm_scope::OsmScope};
 
 #[derive(Clone)]
 pub struct OsmMalloc<'a> {
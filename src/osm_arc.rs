@@ -1,6 +1,13 @@
-use std::{mem::transmute, ops::Deref, sync::Arc};
+use std::{ops::Deref, sync::Arc};
 
-use crate::{osm_alloc::OsmMalloc, osm_scope::OsmScope, osm_wrapper::OsmWrapper};
+use ref_cast::RefCast;
+
+use crate::{
+    osm_alloc::OsmMalloc,
+    osm_error::{ShmemError, alloc_error},
+    osm_scope::OsmScope,
+    osm_wrapper::OsmWrapper,
+};
 
 pub struct OsmArc<'a, T> {
     data: Arc<T, OsmMalloc<'a>>,
@@ -11,14 +18,23 @@ impl<'a, T> OsmArc<'a, T> {
         let data = Arc::new_in(data, OsmMalloc::new(scope));
         OsmArc { data }
     }
+
+    pub fn try_new(data: T, scope: &'a OsmScope) -> Result<Self, ShmemError> {
+        let data = Arc::try_new_in(data, OsmMalloc::new(scope)).map_err(|_| {
+            alloc_error(
+                "shmemalign",
+                std::mem::size_of::<T>(),
+                std::mem::align_of::<T>(),
+            )
+        })?;
+        Ok(OsmArc { data })
+    }
 }
 
 impl<T> Deref for OsmArc<'_, T> {
     type Target = OsmWrapper<T>;
 
     fn deref(&self) -> &Self::Target {
-        unsafe {
-            transmute(&self.data)
-        }
+        OsmWrapper::ref_cast(self.data.deref())
     }
 }
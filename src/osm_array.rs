@@ -0,0 +1,558 @@
+//! Multi-dimensional symmetric arrays, for stencil and halo-exchange codes.
+//!
+//! [`SymArray2`] and [`SymArray3`] store their elements row-major in one
+//! symmetric allocation, so the last index is contiguous. Views select a box
+//! of the array, a row, a column or a face, and transfer it to the same view
+//! of the array on another PE. Axes whose elements are contiguous on both
+//! sides are merged into runs that move with one `shmem_putmem` or
+//! `shmem_getmem`; the rest move with strided `shmem_iput`/`shmem_iget`, one
+//! call per run of the innermost axis.
+//!
+//! A halo exchange sends an interior row into the ghost row of the same
+//! array on a neighbour. Borrow the two rows together with
+//! [`SymArray::split_mut`]:
+//!
+//! ```ignore
+//! let mut grid = SymArray2::new([rows + 2, cols], 0.0f64, &scope);
+//! let (interior, mut ghost) = grid.split_mut([rows..rows + 1, 0..cols], [0..1, 0..cols]);
+//! interior.put_to(&mut ghost, next_pe);
+//! ```
+
+use std::{
+    marker::PhantomData,
+    ops::{Index, IndexMut, Range},
+};
+
+use ref_cast::RefCast;
+
+use crate::{
+    osm_error::ShmemError,
+    osm_ffi::{
+        ptrdiff_t, shmem_getmem, shmem_iget8, shmem_iget16, shmem_iget32, shmem_iget64,
+        shmem_iget128, shmem_iput8, shmem_iput16, shmem_iput32, shmem_iput64, shmem_iput128,
+        shmem_putmem,
+    },
+    osm_pod::ShmemPod,
+    osm_scope::OsmScope,
+    osm_slice::OsmSlice,
+    osm_vec::ShVec,
+    osm_wrapper::OsmWrapper,
+};
+
+/// A symmetric array of rank `N`, row-major.
+pub struct SymArray<'a, T, const N: usize> {
+    data: ShVec<'a, T>,
+    shape: [usize; N],
+}
+
+pub type SymArray2<'a, T> = SymArray<'a, T, 2>;
+pub type SymArray3<'a, T> = SymArray<'a, T, 3>;
+
+impl<'a, T: Clone, const N: usize> SymArray<'a, T, N> {
+    /// Allocate an array of `shape` with every element set to `value`.
+    /// Collective: every PE has to allocate the same shape.
+    pub fn new(shape: [usize; N], value: T, scope: &'a OsmScope) -> Self {
+        let len = shape.iter().product();
+        let mut data = ShVec::with_capacity(len, scope);
+        data.resize_with(len, || value.clone());
+        SymArray { data, shape }
+    }
+
+    pub fn try_new(shape: [usize; N], value: T, scope: &'a OsmScope) -> Result<Self, ShmemError> {
+        let len = shape.iter().product();
+        let mut data = ShVec::try_with_capacity(len, scope)?;
+        data.try_resize_with(len, || value.clone())?;
+        Ok(SymArray { data, shape })
+    }
+}
+
+impl<'a, T, const N: usize> SymArray<'a, T, N> {
+    pub fn shape(&self) -> [usize; N] {
+        self.shape
+    }
+
+    /// The elements in row-major order.
+    pub fn as_slice(&self) -> &OsmSlice<T> {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut OsmSlice<T> {
+        &mut self.data
+    }
+
+    /// The box spanned by `ranges`, one per axis.
+    pub fn view(&self, ranges: [Range<usize>; N]) -> SymView<'_, T, N> {
+        let (offset, shape) = self.select(&ranges);
+        SymView {
+            ptr: unsafe { self.data.as_ptr().add(offset) },
+            shape,
+            strides: self.strides(),
+            _array: PhantomData,
+        }
+    }
+
+    pub fn view_mut(&mut self, ranges: [Range<usize>; N]) -> SymViewMut<'_, T, N> {
+        let (offset, shape) = self.select(&ranges);
+        SymViewMut {
+            ptr: unsafe { self.data.as_mut_ptr().add(offset) },
+            shape,
+            strides: self.strides(),
+            _array: PhantomData,
+        }
+    }
+
+    /// Borrow the box `read` and the box `write` at once, to transfer between
+    /// two parts of the array on different PEs. Panics if the boxes overlap.
+    pub fn split_mut(
+        &mut self,
+        read: [Range<usize>; N],
+        write: [Range<usize>; N],
+    ) -> (SymView<'_, T, N>, SymViewMut<'_, T, N>) {
+        let disjoint = read.iter().zip(&write).any(|(read, write)| {
+            read.is_empty()
+                || write.is_empty()
+                || read.end <= write.start
+                || write.end <= read.start
+        });
+        assert!(disjoint, "{read:?} overlaps {write:?}");
+
+        let (read, strides) = (self.select(&read), self.strides());
+        let (write_offset, write_shape) = self.select(&write);
+        let ptr = self.data.as_mut_ptr();
+        unsafe {
+            (
+                SymView {
+                    ptr: ptr.add(read.0),
+                    shape: read.1,
+                    strides,
+                    _array: PhantomData,
+                },
+                SymViewMut {
+                    ptr: ptr.add(write_offset),
+                    shape: write_shape,
+                    strides,
+                    _array: PhantomData,
+                },
+            )
+        }
+    }
+
+    fn strides(&self) -> [usize; N] {
+        let mut strides = [1; N];
+        for axis in (0..N.saturating_sub(1)).rev() {
+            strides[axis] = strides[axis + 1] * self.shape[axis + 1];
+        }
+        strides
+    }
+
+    fn select(&self, ranges: &[Range<usize>; N]) -> (usize, [usize; N]) {
+        let strides = self.strides();
+        let mut offset = 0;
+        let mut shape = [0; N];
+        for axis in 0..N {
+            let range = &ranges[axis];
+            assert!(
+                range.start <= range.end && range.end <= self.shape[axis],
+                "range {range:?} out of bounds for axis {axis} of length {}",
+                self.shape[axis]
+            );
+            shape[axis] = range.len();
+            offset += range.start * strides[axis];
+        }
+        (offset, shape)
+    }
+
+    fn offset(&self, index: [usize; N]) -> usize {
+        offset(index, self.shape, self.strides())
+    }
+}
+
+impl<'a, T> SymArray<'a, T, 2> {
+    pub fn row(&self, row: usize) -> SymView<'_, T, 1> {
+        let [_, cols] = self.shape;
+        self.view([row..row + 1, 0..cols]).squeeze(0)
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> SymViewMut<'_, T, 1> {
+        let [_, cols] = self.shape;
+        self.view_mut([row..row + 1, 0..cols]).squeeze(0)
+    }
+
+    pub fn column(&self, column: usize) -> SymView<'_, T, 1> {
+        let [rows, _] = self.shape;
+        self.view([0..rows, column..column + 1]).squeeze(1)
+    }
+
+    pub fn column_mut(&mut self, column: usize) -> SymViewMut<'_, T, 1> {
+        let [rows, _] = self.shape;
+        self.view_mut([0..rows, column..column + 1]).squeeze(1)
+    }
+}
+
+impl<'a, T> SymArray<'a, T, 3> {
+    /// The plane at `index` along `axis`, with the other two axes in order.
+    pub fn face(&self, axis: usize, index: usize) -> SymView<'_, T, 2> {
+        self.view(self.face_ranges(axis, index)).squeeze(axis)
+    }
+
+    pub fn face_mut(&mut self, axis: usize, index: usize) -> SymViewMut<'_, T, 2> {
+        let ranges = self.face_ranges(axis, index);
+        self.view_mut(ranges).squeeze(axis)
+    }
+
+    fn face_ranges(&self, axis: usize, index: usize) -> [Range<usize>; 3] {
+        assert!(
+            axis < 3,
+            "axis {axis} out of bounds for a 3-dimensional array"
+        );
+        let mut ranges = self.shape.map(|len| 0..len);
+        ranges[axis] = index..index + 1;
+        ranges
+    }
+}
+
+impl<'a, T, const N: usize> Index<[usize; N]> for SymArray<'a, T, N> {
+    type Output = OsmWrapper<T>;
+
+    fn index(&self, index: [usize; N]) -> &Self::Output {
+        &self.data[self.offset(index)]
+    }
+}
+
+impl<'a, T, const N: usize> IndexMut<[usize; N]> for SymArray<'a, T, N> {
+    fn index_mut(&mut self, index: [usize; N]) -> &mut Self::Output {
+        let offset = self.offset(index);
+        &mut self.data[offset]
+    }
+}
+
+/// A box of a [`SymArray`].
+pub struct SymView<'v, T, const N: usize> {
+    ptr: *const T,
+    shape: [usize; N],
+    strides: [usize; N],
+    _array: PhantomData<&'v [T]>,
+}
+
+/// A mutable box of a [`SymArray`], the target of transfers.
+pub struct SymViewMut<'v, T, const N: usize> {
+    ptr: *mut T,
+    shape: [usize; N],
+    strides: [usize; N],
+    _array: PhantomData<&'v mut [T]>,
+}
+
+impl<'v, T, const N: usize> SymView<'v, T, N> {
+    pub fn shape(&self) -> [usize; N] {
+        self.shape
+    }
+
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The elements in row-major order.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        offsets(self.shape, self.strides)
+            .map(|offset| unsafe { (*self.ptr.add(offset)).clone() })
+            .collect()
+    }
+
+    fn squeeze<const M: usize>(self, axis: usize) -> SymView<'v, T, M> {
+        let (shape, strides) = squeeze(self.shape, self.strides, axis);
+        SymView {
+            ptr: self.ptr,
+            shape,
+            strides,
+            _array: PhantomData,
+        }
+    }
+}
+
+impl<'v, T, const N: usize> SymViewMut<'v, T, N> {
+    pub fn shape(&self) -> [usize; N] {
+        self.shape
+    }
+
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_view(&self) -> SymView<'_, T, N> {
+        SymView {
+            ptr: self.ptr,
+            shape: self.shape,
+            strides: self.strides,
+            _array: PhantomData,
+        }
+    }
+
+    /// Overwrite the elements with `values`, in row-major order.
+    ///
+    /// Panics if the number of values differs from [`SymViewMut::len`].
+    pub fn copy_from(&mut self, values: impl IntoIterator<Item = T>) {
+        let mut values = values.into_iter();
+        for offset in offsets(self.shape, self.strides) {
+            let value = values.next().expect("fewer values than elements");
+            unsafe { *self.ptr.add(offset) = value };
+        }
+        assert!(values.next().is_none(), "more values than elements");
+    }
+
+    fn squeeze<const M: usize>(self, axis: usize) -> SymViewMut<'v, T, M> {
+        let (shape, strides) = squeeze(self.shape, self.strides, axis);
+        SymViewMut {
+            ptr: self.ptr,
+            shape,
+            strides,
+            _array: PhantomData,
+        }
+    }
+}
+
+impl<T: ShmemPod, const N: usize> SymView<'_, T, N> {
+    /// Copy this view into `target`, the same shape of the array on `pe`.
+    #[track_caller]
+    pub fn put_to(&self, target: &mut SymViewMut<'_, T, N>, pe: i32) {
+        assert_eq!(self.shape, target.shape, "views differ in shape");
+        unsafe {
+            transfer(
+                target.ptr,
+                target.strides,
+                self.ptr,
+                self.strides,
+                self.shape,
+                pe,
+                Direction::Put,
+            );
+        }
+    }
+}
+
+impl<T: ShmemPod, const N: usize> SymViewMut<'_, T, N> {
+    /// Fill this view from `source`, the same shape of the array on `pe`.
+    #[track_caller]
+    pub fn get_from(&mut self, source: &SymView<'_, T, N>, pe: i32) {
+        assert_eq!(self.shape, source.shape, "views differ in shape");
+        unsafe {
+            transfer(
+                self.ptr,
+                self.strides,
+                source.ptr,
+                source.strides,
+                self.shape,
+                pe,
+                Direction::Get,
+            );
+        }
+    }
+
+    #[track_caller]
+    pub fn put_to(&self, target: &mut SymViewMut<'_, T, N>, pe: i32) {
+        self.as_view().put_to(target, pe);
+    }
+}
+
+impl<'v, T, const N: usize> Index<[usize; N]> for SymView<'v, T, N> {
+    type Output = OsmWrapper<T>;
+
+    fn index(&self, index: [usize; N]) -> &Self::Output {
+        let offset = offset(index, self.shape, self.strides);
+        OsmWrapper::ref_cast(unsafe { &*self.ptr.add(offset) })
+    }
+}
+
+impl<'v, T, const N: usize> Index<[usize; N]> for SymViewMut<'v, T, N> {
+    type Output = OsmWrapper<T>;
+
+    fn index(&self, index: [usize; N]) -> &Self::Output {
+        let offset = offset(index, self.shape, self.strides);
+        OsmWrapper::ref_cast(unsafe { &*self.ptr.add(offset) })
+    }
+}
+
+impl<'v, T, const N: usize> IndexMut<[usize; N]> for SymViewMut<'v, T, N> {
+    fn index_mut(&mut self, index: [usize; N]) -> &mut Self::Output {
+        let offset = offset(index, self.shape, self.strides);
+        OsmWrapper::ref_cast_mut(unsafe { &mut *self.ptr.add(offset) })
+    }
+}
+
+fn offset<const N: usize>(index: [usize; N], shape: [usize; N], strides: [usize; N]) -> usize {
+    for axis in 0..N {
+        assert!(
+            index[axis] < shape[axis],
+            "index {index:?} out of bounds for shape {shape:?}"
+        );
+    }
+    index
+        .iter()
+        .zip(strides)
+        .map(|(i, stride)| i * stride)
+        .sum()
+}
+
+fn squeeze<const N: usize, const M: usize>(
+    shape: [usize; N],
+    strides: [usize; N],
+    axis: usize,
+) -> ([usize; M], [usize; M]) {
+    assert_eq!(M + 1, N);
+    let mut kept = (0..N).filter(|&i| i != axis);
+    let kept: [usize; M] = std::array::from_fn(|_| kept.next().unwrap());
+    (kept.map(|i| shape[i]), kept.map(|i| strides[i]))
+}
+
+/// Element offsets of a view in row-major order.
+fn offsets<const N: usize>(shape: [usize; N], strides: [usize; N]) -> impl Iterator<Item = usize> {
+    let len: usize = shape.iter().product();
+    (0..len).map(move |mut flat| {
+        let mut offset = 0;
+        for axis in (0..N).rev() {
+            offset += flat % shape[axis] * strides[axis];
+            flat /= shape[axis];
+        }
+        offset
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Put,
+    Get,
+}
+
+/// Move the elements of `shape` between `source` and `dest`, one of which is
+/// remote. Strides are in elements.
+#[track_caller]
+unsafe fn transfer<T, const N: usize>(
+    dest: *mut T,
+    dest_strides: [usize; N],
+    source: *const T,
+    source_strides: [usize; N],
+    shape: [usize; N],
+    pe: i32,
+    direction: Direction,
+) {
+    if shape.contains(&0) {
+        return;
+    }
+
+    // (length, dest stride, source stride) of each axis, outermost first,
+    // merging an axis into the one outside it where both sides are dense
+    let mut axes: Vec<(usize, usize, usize)> = Vec::with_capacity(N);
+    for axis in 0..N {
+        let (len, dst, sst) = (shape[axis], dest_strides[axis], source_strides[axis]);
+        if len == 1 {
+            continue;
+        }
+        match axes.last_mut() {
+            Some(outer) if outer.1 == len * dst && outer.2 == len * sst => {
+                *outer = (outer.0 * len, dst, sst);
+            }
+            _ => axes.push((len, dst, sst)),
+        }
+    }
+
+    let (len, dst, sst) = axes.pop().unwrap_or((1, 1, 1));
+    let mut index = vec![0; axes.len()];
+    loop {
+        let (dest_offset, source_offset) = axes
+            .iter()
+            .zip(&index)
+            .fold((0, 0), |(d, s), (&(_, dst, sst), &i)| {
+                (d + i * dst, s + i * sst)
+            });
+        unsafe {
+            transfer_run(
+                dest.add(dest_offset),
+                source.add(source_offset),
+                dst,
+                sst,
+                len,
+                pe,
+                direction,
+            );
+        }
+
+        // advance the outer axes like an odometer
+        let mut axis = axes.len();
+        loop {
+            if axis == 0 {
+                return;
+            }
+            axis -= 1;
+            index[axis] += 1;
+            if index[axis] < axes[axis].0 {
+                break;
+            }
+            index[axis] = 0;
+        }
+    }
+}
+
+/// One run of `len` elements, `dest_stride` and `source_stride` elements
+/// apart.
+#[track_caller]
+unsafe fn transfer_run<T>(
+    dest: *mut T,
+    source: *const T,
+    dest_stride: usize,
+    source_stride: usize,
+    len: usize,
+    pe: i32,
+    direction: Direction,
+) {
+    let size = std::mem::size_of::<T>();
+    if len == 1 || (dest_stride == 1 && source_stride == 1) {
+        unsafe {
+            match direction {
+                Direction::Put => shmem_putmem(dest.cast(), source.cast(), len * size, pe),
+                Direction::Get => shmem_getmem(dest.cast(), source.cast(), len * size, pe),
+            }
+        }
+        return;
+    }
+
+    let (d, s, n) = (dest.cast(), source.cast(), len);
+    let (tst, sst) = (dest_stride as ptrdiff_t, source_stride as ptrdiff_t);
+    unsafe {
+        match (direction, size) {
+            (Direction::Put, 1) => shmem_iput8(d, s, tst, sst, n, pe),
+            (Direction::Put, 2) => shmem_iput16(d, s, tst, sst, n, pe),
+            (Direction::Put, 4) => shmem_iput32(d, s, tst, sst, n, pe),
+            (Direction::Put, 8) => shmem_iput64(d, s, tst, sst, n, pe),
+            (Direction::Put, 16) => shmem_iput128(d, s, tst, sst, n, pe),
+            (Direction::Get, 1) => shmem_iget8(d, s, tst, sst, n, pe),
+            (Direction::Get, 2) => shmem_iget16(d, s, tst, sst, n, pe),
+            (Direction::Get, 4) => shmem_iget32(d, s, tst, sst, n, pe),
+            (Direction::Get, 8) => shmem_iget64(d, s, tst, sst, n, pe),
+            (Direction::Get, 16) => shmem_iget128(d, s, tst, sst, n, pe),
+            // no strided call for this element size, move one element at a time
+            _ => {
+                for i in 0..len {
+                    transfer_run(
+                        dest.add(i * dest_stride),
+                        source.add(i * source_stride),
+                        1,
+                        1,
+                        1,
+                        pe,
+                        direction,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use std::time::{Duration, Instant};
+
+use ref_cast::RefCast;
+
+use crate::{
+    osm_box::OsmBox, osm_ffi::shmem_getmem, osm_scope::OsmScope, osm_slice::OsmSlice,
+    osm_team::OsmTeam, osm_vec::ShVec, osm_wrapper::ShmemCmp,
+};
+
+const MAX_ROUNDS: usize = 64;
+const LABEL_LEN: usize = 32;
+
+/// A barrier over all PEs that can be waited on repeatedly.
+///
+/// The user-space implementations quiet before arriving, so like
+/// [`OsmScope::barrier_all`] they complete outstanding RMA.
+pub trait Barrier {
+    fn wait(&mut self);
+
+    /// Label what this PE is doing until its next arrival, for barriers that
+    /// report on PEs that never show up.
+    fn set_phase(&mut self, _label: &str) {}
+}
+
+/// `shmem_barrier_all`: completes outstanding RMA, then synchronizes.
+pub struct BarrierAll<'a> {
+    scope: &'a OsmScope,
+}
+
+impl<'a> BarrierAll<'a> {
+    pub fn new(scope: &'a OsmScope) -> Self {
+        BarrierAll { scope }
+    }
+}
+
+impl Barrier for BarrierAll<'_> {
+    fn wait(&mut self) {
+        self.scope.barrier_all();
+    }
+}
+
+/// `shmem_sync_all`: synchronizes without completing outstanding RMA.
+pub struct SyncAll<'a> {
+    scope: &'a OsmScope,
+}
+
+impl<'a> SyncAll<'a> {
+    pub fn new(scope: &'a OsmScope) -> Self {
+        SyncAll { scope }
+    }
+}
+
+impl Barrier for SyncAll<'_> {
+    fn wait(&mut self) {
+        self.scope.sync_all();
+    }
+}
+
+impl Barrier for OsmTeam {
+    fn wait(&mut self) {
+        self.sync();
+    }
+}
+
+/// Every PE increments a counter on PE 0; the last one to arrive releases
+/// everybody else by writing the epoch into their release flag.
+pub struct CentralCounterBarrier<'a> {
+    scope: &'a OsmScope,
+    counter: OsmBox<'a, i64>,
+    release: OsmBox<'a, i64>,
+    epoch: i64,
+}
+
+impl<'a> CentralCounterBarrier<'a> {
+    pub fn new(scope: &'a OsmScope) -> Self {
+        let counter = OsmBox::new(0, scope);
+        let release = OsmBox::new(0, scope);
+        scope.barrier_all();
+
+        CentralCounterBarrier {
+            scope,
+            counter,
+            release,
+            epoch: 0,
+        }
+    }
+}
+
+impl Barrier for CentralCounterBarrier<'_> {
+    fn wait(&mut self) {
+        let num_pes = self.scope.num_pes();
+        self.epoch += 1;
+        self.scope.quiet();
+
+        // the counter is never reset, epoch e is complete at e * num_pes arrivals
+        let arrived = self.counter.fetch_add(1, 0) + 1;
+        if arrived == self.epoch * num_pes as i64 {
+            for pe in 0..num_pes {
+                self.release.atomic_set(self.epoch, pe);
+            }
+        }
+
+        self.release.wait_until(ShmemCmp::Ge, self.epoch);
+    }
+}
+
+/// In round `k` every PE signals the PE `2^k` ahead and waits for the one
+/// `2^k` behind, finishing in `ceil(log2(n))` rounds without a root.
+pub struct DisseminationBarrier<'a> {
+    scope: &'a OsmScope,
+    flags: ShVec<'a, i64>,
+    epoch: i64,
+}
+
+impl<'a> DisseminationBarrier<'a> {
+    pub fn new(scope: &'a OsmScope) -> Self {
+        let mut flags = ShVec::with_capacity(MAX_ROUNDS, scope);
+        flags.resize_with(MAX_ROUNDS, || 0);
+        scope.barrier_all();
+
+        DisseminationBarrier {
+            scope,
+            flags,
+            epoch: 0,
+        }
+    }
+}
+
+impl Barrier for DisseminationBarrier<'_> {
+    fn wait(&mut self) {
+        let num_pes = self.scope.num_pes() as usize;
+        let my_pe = self.scope.my_pe() as usize;
+        self.epoch += 1;
+        self.scope.quiet();
+
+        // a peer may already be in the next epoch, hence Ge instead of Eq
+        let mut distance = 1;
+        let mut round = 0;
+        while distance < num_pes {
+            let pe = (my_pe + distance) % num_pes;
+            self.flags[round].atomic_set(self.epoch, pe as i32);
+            self.flags[round].wait_until(ShmemCmp::Ge, self.epoch);
+
+            distance *= 2;
+            round += 1;
+        }
+    }
+}
+
+/// Static tournament: in round `k` the PE with bit `k` set reports to the
+/// one `2^k` below and drops out. PE 0 wins and the release travels back
+/// down the same tree.
+pub struct TournamentBarrier<'a> {
+    scope: &'a OsmScope,
+    arrivals: ShVec<'a, i64>,
+    release: OsmBox<'a, i64>,
+    epoch: i64,
+}
+
+impl<'a> TournamentBarrier<'a> {
+    pub fn new(scope: &'a OsmScope) -> Self {
+        let mut arrivals = ShVec::with_capacity(MAX_ROUNDS, scope);
+        arrivals.resize_with(MAX_ROUNDS, || 0);
+        let release = OsmBox::new(0, scope);
+        scope.barrier_all();
+
+        TournamentBarrier {
+            scope,
+            arrivals,
+            release,
+            epoch: 0,
+        }
+    }
+}
+
+impl Barrier for TournamentBarrier<'_> {
+    fn wait(&mut self) {
+        let num_pes = self.scope.num_pes() as usize;
+        let my_pe = self.scope.my_pe() as usize;
+        self.epoch += 1;
+        self.scope.quiet();
+
+        let mut round = 0;
+        while 1 << round < num_pes {
+            let distance = 1 << round;
+            if my_pe & distance != 0 {
+                self.arrivals[round].atomic_set(self.epoch, (my_pe - distance) as i32);
+                break;
+            }
+            if my_pe + distance < num_pes {
+                self.arrivals[round].wait_until(ShmemCmp::Ge, self.epoch);
+            }
+            round += 1;
+        }
+
+        if my_pe != 0 {
+            self.release.wait_until(ShmemCmp::Ge, self.epoch);
+        }
+
+        // wake the PEs that lost to us, latest round first
+        for round in (0..round).rev() {
+            let loser = my_pe + (1 << round);
+            if loser < num_pes {
+                self.release.atomic_set(self.epoch, loser as i32);
+            }
+        }
+    }
+}
+
+/// Central counter barrier that gives up after `timeout` and reports the PEs
+/// that did not arrive.
+///
+/// Arrival epochs and phase labels are kept on PE 0. A waiter that times out
+/// prints every missing PE with the epoch it last arrived at and its current
+/// label, then panics, which takes the whole job down.
+pub struct DiagnosticBarrier<'a> {
+    scope: &'a OsmScope,
+    timeout: Duration,
+    counter: OsmBox<'a, i64>,
+    release: OsmBox<'a, i64>,
+    arrivals: ShVec<'a, i64>,
+    labels: ShVec<'a, u8>,
+    label: [u8; LABEL_LEN],
+    epoch: i64,
+}
+
+impl<'a> DiagnosticBarrier<'a> {
+    pub fn new(timeout: Duration, scope: &'a OsmScope) -> Self {
+        let num_pes = scope.num_pes() as usize;
+
+        let counter = OsmBox::new(0, scope);
+        let release = OsmBox::new(0, scope);
+        let mut arrivals = ShVec::with_capacity(num_pes, scope);
+        arrivals.resize_with(num_pes, || 0);
+        let mut labels = ShVec::with_capacity(num_pes * LABEL_LEN, scope);
+        labels.resize_with(num_pes * LABEL_LEN, || 0);
+        scope.barrier_all();
+
+        DiagnosticBarrier {
+            scope,
+            timeout,
+            counter,
+            release,
+            arrivals,
+            labels,
+            label: [0; LABEL_LEN],
+            epoch: 0,
+        }
+    }
+
+    fn report(&self) {
+        let num_pes = self.scope.num_pes() as usize;
+        let mut labels = vec![0u8; num_pes * LABEL_LEN];
+        unsafe {
+            shmem_getmem(
+                labels.as_mut_ptr().cast(),
+                self.labels.as_ptr().cast(),
+                labels.len(),
+                0,
+            )
+        };
+
+        eprintln!(
+            "PE {} timed out after {:?} in barrier epoch {}, missing PEs:",
+            self.scope.my_pe(),
+            self.timeout,
+            self.epoch
+        );
+        for pe in 0..num_pes {
+            let arrived = self.arrivals[pe].atomic_fetch(0);
+            if arrived < self.epoch {
+                let label = &labels[pe * LABEL_LEN..(pe + 1) * LABEL_LEN];
+                let len = label.iter().position(|&b| b == 0).unwrap_or(LABEL_LEN);
+                eprintln!(
+                    "  PE {pe}: last arrived at epoch {arrived}, phase \"{}\"",
+                    String::from_utf8_lossy(&label[..len])
+                );
+            }
+        }
+    }
+}
+
+impl Barrier for DiagnosticBarrier<'_> {
+    fn wait(&mut self) {
+        let num_pes = self.scope.num_pes();
+        let my_pe = self.scope.my_pe();
+        self.epoch += 1;
+        self.scope.quiet();
+
+        self.arrivals[my_pe as usize].atomic_set(self.epoch, 0);
+        let arrived = self.counter.fetch_add(1, 0) + 1;
+        if arrived == self.epoch * num_pes as i64 {
+            for pe in 0..num_pes {
+                self.release.atomic_set(self.epoch, pe);
+            }
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        while self.release.atomic_fetch(my_pe) < self.epoch {
+            if Instant::now() > deadline {
+                self.report();
+                panic!("barrier epoch {} timed out", self.epoch);
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    fn set_phase(&mut self, label: &str) {
+        let bytes = &label.as_bytes()[..label.len().min(LABEL_LEN)];
+        let mut padded = [0; LABEL_LEN];
+        padded[..bytes.len()].copy_from_slice(bytes);
+        if padded == self.label {
+            return;
+        }
+
+        // pushed eagerly, a PE that hangs never gets to report it later
+        self.label = padded;
+        let slot = self.scope.my_pe() as usize * LABEL_LEN;
+        OsmSlice::ref_cast(&self.label[..]).put_to(&mut self.labels[slot..slot + LABEL_LEN], 0);
+    }
+}
@@ -1,6 +1,11 @@
-use std::mem::transmute;
+use ref_cast::RefCast;
 
-use crate::{osm_alloc::OsmMalloc, osm_scope::OsmScope, osm_wrapper::OsmWrapper};
+use crate::{
+    osm_alloc::OsmMalloc,
+    osm_error::{ShmemError, alloc_error},
+    osm_scope::OsmScope,
+    osm_wrapper::OsmWrapper,
+};
 
 pub struct OsmBox<'a, T> {
     data: Box<T, OsmMalloc<'a>>,
@@ -12,22 +17,29 @@ impl<'a, T> OsmBox<'a, T> {
         let data = Box::new_in(data, allocator);
         OsmBox { data }
     }
+
+    pub fn try_new(data: T, scope: &'a OsmScope) -> Result<Self, ShmemError> {
+        let data = Box::try_new_in(data, OsmMalloc::new(scope)).map_err(|_| {
+            alloc_error(
+                "shmemalign",
+                std::mem::size_of::<T>(),
+                std::mem::align_of::<T>(),
+            )
+        })?;
+        Ok(OsmBox { data })
+    }
 }
 
 impl<'a, T> std::ops::Deref for OsmBox<'a, T> {
     type Target = OsmWrapper<T>;
 
     fn deref(&self) -> &Self::Target {
-        unsafe {
-            transmute(self.data.deref())
-        }
+        OsmWrapper::ref_cast(self.data.deref())
     }
 }
 
 impl<'a, T> std::ops::DerefMut for OsmBox<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe {
-            transmute(self.data.deref_mut())
-        }
+        OsmWrapper::ref_cast_mut(self.data.deref_mut())
     }
 }
\ No newline at end of file
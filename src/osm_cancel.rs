@@ -0,0 +1,232 @@
+//! Job-wide cancellation that any PE can trigger.
+//!
+//! Each PE holds a symmetric word that is zero until the job is cancelled,
+//! then names the PE that cancelled and why. Competing cancellations are
+//! settled on PE 0 with a compare-and-swap, and the winner writes its word
+//! into every PE, so all PEs report the same cause.
+//!
+//! Signal handlers and timer threads must not issue SHMEM calls, so
+//! [`CancelTrigger`]s only mark the cancellation pending on their PE; the
+//! next [`GlobalCancel::poll`] on that PE propagates it.
+//!
+//! To stop a loop on all PEs in the same iteration, poll at the end of each
+//! iteration and check [`GlobalCancel::cancelled`] after the barrier that
+//! starts the next one. A PE that polled a trigger has delivered the
+//! cancellation before it arrives at the barrier.
+
+use std::{
+    fmt::Display,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicI64, AtomicU8, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::{osm_box::OsmBox, osm_ffi::shmem_quiet, osm_scope::OsmScope};
+
+/// Why the job was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelReason {
+    /// Ctrl-C, see [`GlobalCancel::cancel_on_signal`].
+    Signal,
+    /// See [`GlobalCancel::cancel_after`].
+    Timeout,
+    /// [`GlobalCancel::cancel`] or [`CancelTrigger::cancel`].
+    Requested,
+}
+
+impl CancelReason {
+    fn code(self) -> u8 {
+        match self {
+            CancelReason::Signal => 1,
+            CancelReason::Timeout => 2,
+            CancelReason::Requested => 3,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(CancelReason::Signal),
+            2 => Some(CancelReason::Timeout),
+            3 => Some(CancelReason::Requested),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CancelReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CancelReason::Signal => "signal",
+            CancelReason::Timeout => "timeout",
+            CancelReason::Requested => "requested",
+        })
+    }
+}
+
+/// Who cancelled the job and why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancellation {
+    pub pe: i32,
+    pub reason: CancelReason,
+}
+
+impl Cancellation {
+    // PE + 1 in the low half so that a cancelled word is never zero
+    fn encode(self) -> i64 {
+        (self.reason.code() as i64) << 32 | (self.pe as i64 + 1)
+    }
+
+    fn decode(word: i64) -> Option<Self> {
+        Some(Cancellation {
+            pe: (word & 0xffff_ffff) as i32 - 1,
+            reason: CancelReason::from_code((word >> 32) as u8)?,
+        })
+    }
+}
+
+impl Display for Cancellation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cancelled by PE {} ({})", self.pe, self.reason)
+    }
+}
+
+/// Marks a cancellation pending on its PE. Can be sent to other threads,
+/// including signal handlers.
+#[derive(Debug, Clone)]
+pub struct CancelTrigger {
+    pending: Arc<AtomicU8>,
+}
+
+impl CancelTrigger {
+    /// Only the first reason given on a PE is kept.
+    pub fn cancel(&self, reason: CancelReason) {
+        let _ =
+            self.pending
+                .compare_exchange(0, reason.code(), Ordering::Relaxed, Ordering::Relaxed);
+    }
+}
+
+/// Triggers fired by the process-wide Ctrl-C handler, `None` until it is
+/// installed. Simulated PEs share the process, so there can be several.
+static SIGNAL_TRIGGERS: Mutex<Option<Vec<CancelTrigger>>> = Mutex::new(None);
+
+/// A cancellation flag shared by all PEs.
+pub struct GlobalCancel<'a> {
+    scope: &'a OsmScope,
+    state: OsmBox<'a, AtomicI64>,
+    trigger: CancelTrigger,
+}
+
+impl<'a> GlobalCancel<'a> {
+    /// Collective: every PE has to create it.
+    pub fn new(scope: &'a OsmScope) -> Self {
+        let state = OsmBox::new(AtomicI64::new(0), scope);
+        scope.barrier_all();
+
+        GlobalCancel {
+            scope,
+            state,
+            trigger: CancelTrigger {
+                pending: Arc::new(AtomicU8::new(0)),
+            },
+        }
+    }
+
+    /// A handle that cancels from another thread, at this PE's next poll.
+    pub fn trigger(&self) -> CancelTrigger {
+        self.trigger.clone()
+    }
+
+    /// Cancel on Ctrl-C. Fails if another Ctrl-C handler is installed.
+    pub fn cancel_on_signal(&self) -> Result<(), ctrlc::Error> {
+        let mut triggers = SIGNAL_TRIGGERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if triggers.is_none() {
+            ctrlc::set_handler(|| {
+                let triggers = SIGNAL_TRIGGERS
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                for trigger in triggers.iter().flatten() {
+                    trigger.cancel(CancelReason::Signal);
+                }
+            })?;
+        }
+        triggers.get_or_insert_default().push(self.trigger());
+        Ok(())
+    }
+
+    /// Cancel once `timeout` has passed.
+    pub fn cancel_after(&self, timeout: Duration) {
+        let trigger = self.trigger();
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            trigger.cancel(CancelReason::Timeout);
+        });
+    }
+
+    /// Cancel the job now, unless another PE already did.
+    #[track_caller]
+    pub fn cancel(&mut self, reason: CancelReason) {
+        if self.cancelled().is_some() {
+            return;
+        }
+
+        let word = Cancellation {
+            pe: self.scope.my_pe(),
+            reason,
+        }
+        .encode();
+        if self.state.compare_and_swap(0, word, 0) != 0 {
+            // lost to another PE, which delivers its own word
+            return;
+        }
+        for pe in 1..self.scope.num_pes() {
+            self.state.atomic_set(word, pe);
+        }
+        // barriers that do not complete RMA must not overtake the word
+        unsafe { shmem_quiet() };
+    }
+
+    /// Deliver a cancellation triggered on this PE, then report whether the
+    /// job is cancelled.
+    #[track_caller]
+    pub fn poll(&mut self) -> Option<Cancellation> {
+        let pending = self.trigger.pending.load(Ordering::Relaxed);
+        if let Some(reason) = CancelReason::from_code(pending) {
+            self.cancel(reason);
+        }
+        self.cancelled()
+    }
+
+    /// The cancellation this PE has received, without delivering pending
+    /// triggers. Costs one local load.
+    pub fn cancelled(&self) -> Option<Cancellation> {
+        Cancellation::decode(self.state.load(Ordering::Acquire))
+    }
+
+    /// Block until the job is cancelled, by this or any other PE.
+    #[track_caller]
+    pub fn wait(&mut self) -> Cancellation {
+        loop {
+            if let Some(cancellation) = self.poll() {
+                return cancellation;
+            }
+            std::thread::yield_now();
+        }
+    }
+}
+
+impl Drop for GlobalCancel<'_> {
+    fn drop(&mut self) {
+        // the Ctrl-C handler stays installed, but must not keep this trigger
+        let mut triggers = SIGNAL_TRIGGERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(triggers) = triggers.as_mut() {
+            triggers.retain(|trigger| !Arc::ptr_eq(&trigger.pending, &self.trigger.pending));
+        }
+    }
+}
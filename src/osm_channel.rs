@@ -0,0 +1,135 @@
+use ref_cast::RefCast;
+
+use crate::{
+    osm_box::OsmBox, osm_ffi::shmem_fence, osm_pod::ShmemPod, osm_scope::OsmScope,
+    osm_slice::OsmSlice, osm_vec::ShVec, osm_wrapper::ShmemCmp,
+};
+
+/// Single-producer single-consumer channel from `sender` to `receiver`.
+///
+/// The ring buffer lives on the receiver. The sender copies each message into
+/// a local staging slot, issues `put_to_nbi` into the matching ring slot,
+/// fences and then bumps the receiver's `tail`. The receiver waits on its local
+/// `tail` and periodically writes the number of consumed messages back into
+/// the sender's `head`, which is what the sender waits on when the ring is
+/// full. A staging slot is only reused after the receiver acknowledged it, so
+/// the outstanding nbi put from it has completed by then.
+///
+/// Construction is collective; `send` may only be called on the sender and
+/// `recv` only on the receiver.
+pub struct ShmemChannel<'a, T> {
+    buffer: ShVec<'a, T>,
+    head: OsmBox<'a, i64>,
+    tail: OsmBox<'a, i64>,
+    staging: Vec<T>,
+    sender: i32,
+    receiver: i32,
+    my_pe: i32,
+    capacity: usize,
+    sent: i64,
+    acked: i64,
+    received: i64,
+    available: i64,
+}
+
+impl<'a, T> ShmemChannel<'a, T>
+where
+    T: ShmemPod + Copy + Default,
+{
+    pub fn new(sender: i32, receiver: i32, capacity: usize, scope: &'a OsmScope) -> Self {
+        assert!(capacity > 0, "ShmemChannel requires a non-zero capacity");
+
+        let mut buffer = ShVec::with_capacity(capacity, scope);
+        buffer.resize_with(capacity, T::default);
+        let head = OsmBox::new(0, scope);
+        let tail = OsmBox::new(0, scope);
+
+        scope.barrier_all();
+
+        ShmemChannel {
+            buffer,
+            head,
+            tail,
+            staging: vec![T::default(); capacity],
+            sender,
+            receiver,
+            my_pe: scope.my_pe(),
+            capacity,
+            sent: 0,
+            acked: 0,
+            received: 0,
+            available: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Send `value` to the receiver, waiting for space if the ring is full.
+    pub fn send(&mut self, value: T) {
+        assert_eq!(self.my_pe, self.sender, "send called on a non-sender PE");
+
+        let capacity = self.capacity as i64;
+        if self.sent - self.acked >= capacity {
+            self.head.wait_until(ShmemCmp::Gt, self.sent - capacity);
+            self.acked = self.head.atomic_fetch(self.my_pe);
+        }
+
+        let slot = (self.sent % capacity) as usize;
+        self.staging[slot] = value;
+        OsmSlice::ref_cast(&self.staging[slot..slot + 1])
+            .put_to_nbi(&mut self.buffer[slot..slot + 1], self.receiver);
+        self.sent += 1;
+
+        // the payload must be delivered before the receiver sees the new tail
+        unsafe { shmem_fence() };
+        self.tail.atomic_set(self.sent, self.receiver);
+    }
+
+    /// Receive the next message, waiting until one arrives.
+    pub fn recv(&mut self) -> T {
+        assert_eq!(
+            self.my_pe, self.receiver,
+            "recv called on a non-receiver PE"
+        );
+
+        if self.received == self.available {
+            self.tail.wait_until(ShmemCmp::Gt, self.received);
+            self.available = self.tail.atomic_fetch(self.my_pe);
+        }
+
+        self.take()
+    }
+
+    pub fn try_recv(&mut self) -> Option<T> {
+        assert_eq!(
+            self.my_pe, self.receiver,
+            "try_recv called on a non-receiver PE"
+        );
+
+        if self.received == self.available {
+            self.available = self.tail.atomic_fetch(self.my_pe);
+            if self.received == self.available {
+                return None;
+            }
+        }
+
+        Some(self.take())
+    }
+
+    fn take(&mut self) -> T {
+        let capacity = self.capacity as i64;
+        let slot = (self.received % capacity) as usize;
+        let value = *self.buffer[slot];
+        self.received += 1;
+
+        // acknowledge in batches of half the ring to keep flow-control traffic low
+        if self.received - self.acked >= (capacity / 2).max(1) {
+            self.acked = self.received;
+            self.head.atomic_set(self.received, self.sender);
+        }
+
+        value
+    }
+}
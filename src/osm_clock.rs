@@ -0,0 +1,218 @@
+//! Cheap timestamps that convert to nanoseconds, and the offsets between the
+//! clocks of different PEs.
+//!
+//! On x86-64 with an invariant TSC, [`Clock`] reads the timestamp counter
+//! with `rdtscp` and converts ticks with a frequency calibrated against the
+//! monotonic clock. Everywhere else a tick is a nanosecond of
+//! [`std::time::Instant`].
+//!
+//! [`ClockOffset::estimate`] measures how far each PE's [`Clock::now_ns`] is
+//! from PE 0's, so timestamps taken on different PEs can be put on one
+//! timeline, for one-way latencies or merged traces.
+
+use std::time::{Duration, Instant};
+
+use crate::{osm_scope::OsmScope, osm_vec::ShVec, osm_wrapper::ShmemCmp};
+
+const DEFAULT_CALIBRATION: Duration = Duration::from_millis(20);
+const DEFAULT_ROUNDS: usize = 100;
+
+/// A calibrated tick counter.
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    tsc: bool,
+    ns_per_tick: f64,
+    /// Ticks at calibration, where [`Clock::now_ns`] starts.
+    origin: u64,
+    origin_instant: Instant,
+}
+
+impl Clock {
+    /// Calibrate against the monotonic clock for 20 ms.
+    pub fn calibrate() -> Self {
+        Self::calibrate_for(DEFAULT_CALIBRATION)
+    }
+
+    /// Calibrate against the monotonic clock for `duration`. Longer runs give
+    /// a more precise frequency.
+    pub fn calibrate_for(duration: Duration) -> Self {
+        let origin_instant = Instant::now();
+        if !tsc_available() {
+            return Clock {
+                tsc: false,
+                ns_per_tick: 1.0,
+                origin: 0,
+                origin_instant,
+            };
+        }
+
+        let (begin_ticks, begin) = paired_read();
+        while begin.elapsed() < duration {
+            std::hint::spin_loop();
+        }
+        let (end_ticks, end) = paired_read();
+
+        Clock {
+            tsc: true,
+            ns_per_tick: (end - begin).as_nanos() as f64 / (end_ticks - begin_ticks) as f64,
+            origin: begin_ticks,
+            origin_instant: begin,
+        }
+    }
+
+    /// Whether ticks come from the timestamp counter rather than `Instant`.
+    pub fn is_tsc(&self) -> bool {
+        self.tsc
+    }
+
+    /// Ticks per second.
+    pub fn frequency(&self) -> f64 {
+        1e9 / self.ns_per_tick
+    }
+
+    /// The current tick count. Only differences between ticks of the same
+    /// clock are meaningful.
+    #[inline]
+    pub fn now(&self) -> u64 {
+        if self.tsc {
+            read_tsc()
+        } else {
+            self.origin_instant.elapsed().as_nanos() as u64
+        }
+    }
+
+    /// Nanoseconds since this clock was calibrated.
+    #[inline]
+    pub fn now_ns(&self) -> i64 {
+        self.ticks_to_ns(self.now().wrapping_sub(self.origin)) as i64
+    }
+
+    /// Convert a tick difference to nanoseconds.
+    #[inline]
+    pub fn ticks_to_ns(&self, ticks: u64) -> f64 {
+        ticks as f64 * self.ns_per_tick
+    }
+
+    pub fn ticks_to_duration(&self, ticks: u64) -> Duration {
+        Duration::from_nanos(self.ticks_to_ns(ticks) as u64)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn tsc_available() -> bool {
+    use std::arch::x86_64::__cpuid;
+
+    // invariant TSC: CPUID.80000007H:EDX[8], it ticks at a constant rate
+    // across frequency changes and sleep states
+    let max_extended = __cpuid(0x8000_0000).eax;
+    max_extended >= 0x8000_0007 && __cpuid(0x8000_0007).edx & (1 << 8) != 0
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn tsc_available() -> bool {
+    false
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn read_tsc() -> u64 {
+    let mut aux = 0;
+    unsafe { std::arch::x86_64::__rdtscp(&mut aux) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn read_tsc() -> u64 {
+    unreachable!("no timestamp counter on this architecture")
+}
+
+/// An `Instant` and the ticks taken around it, keeping the tightest of a
+/// few attempts so a preemption in between does not skew the calibration.
+fn paired_read() -> (u64, Instant) {
+    (0..16)
+        .map(|_| {
+            let before = read_tsc();
+            let instant = Instant::now();
+            let after = read_tsc();
+            (after - before, before + (after - before) / 2, instant)
+        })
+        .min_by_key(|&(spread, _, _)| spread)
+        .map(|(_, ticks, instant)| (ticks, instant))
+        .unwrap()
+}
+
+/// How far this PE's [`Clock::now_ns`] is from PE 0's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockOffset {
+    /// Add to a local [`Clock::now_ns`] timestamp to get PE 0's time.
+    pub offset_ns: i64,
+    /// Round trip of the sample the offset was taken from. The offset is
+    /// off by at most half of it.
+    pub round_trip_ns: i64,
+}
+
+impl ClockOffset {
+    /// Estimate the offset with 100 ping-pongs per PE. Collective: every PE
+    /// has to call it.
+    pub fn estimate(clock: &Clock, scope: &OsmScope) -> Self {
+        Self::estimate_with(clock, DEFAULT_ROUNDS, scope)
+    }
+
+    /// Estimate the offset with `rounds` ping-pongs per PE.
+    ///
+    /// PE 0 serves the other PEs one after another. A PE stamps a ping, PE 0
+    /// answers with its own time, and the PE stamps the reply; assuming both
+    /// directions take as long, PE 0's stamp was taken halfway. The round
+    /// with the shortest round trip wins, as it left the least room for
+    /// asymmetry. Clocks drift apart, so re-estimate on long runs.
+    pub fn estimate_with(clock: &Clock, rounds: usize, scope: &OsmScope) -> Self {
+        let num_pes = scope.num_pes() as usize;
+        let my_pe = scope.my_pe() as usize;
+
+        let mut pings = ShVec::with_capacity(num_pes, scope);
+        pings.resize_with(num_pes, || 0i64);
+        let mut reply = ShVec::with_capacity(1, scope);
+        reply.resize_with(1, || -1i64);
+        scope.barrier_all();
+
+        let mut best = ClockOffset {
+            offset_ns: 0,
+            round_trip_ns: 0,
+        };
+
+        if my_pe == 0 {
+            for pe in 1..num_pes {
+                for round in 1..=rounds as i64 {
+                    pings[pe].wait_until(ShmemCmp::Ge, round);
+                    reply[0].atomic_set(clock.now_ns(), pe as i32);
+                }
+            }
+        } else {
+            best.round_trip_ns = i64::MAX;
+            for round in 1..=rounds as i64 {
+                // the previous reply has arrived, so nothing races this reset
+                reply[0].atomic_set(-1, my_pe as i32);
+                let sent = clock.now_ns();
+                pings[my_pe].atomic_set(round, 0);
+                reply[0].wait_until(ShmemCmp::Ge, 0);
+                let received = clock.now_ns();
+                let remote = reply[0].atomic_fetch(my_pe as i32);
+
+                let round_trip_ns = received - sent;
+                if round_trip_ns < best.round_trip_ns {
+                    best = ClockOffset {
+                        offset_ns: remote - (sent + round_trip_ns / 2),
+                        round_trip_ns,
+                    };
+                }
+            }
+        }
+
+        scope.barrier_all();
+        best
+    }
+
+    /// Convert a local [`Clock::now_ns`] timestamp to PE 0's time.
+    pub fn to_reference(&self, local_ns: i64) -> i64 {
+        local_ns + self.offset_ns
+    }
+}
@@ -0,0 +1,567 @@
+use ref_cast::RefCast;
+
+use crate::{
+    osm_error::{ShmemError, check, check_size, check_size_exact},
+    osm_ffi::{shmem_alltoallmem, shmem_collectmem, shmem_fcollectmem},
+    osm_pod::ShmemPod,
+    osm_scope::OsmScope,
+    osm_slice::OsmSlice,
+    osm_team::OsmTeam,
+    osm_vec::ShVec,
+    osm_wrapper::OsmWrapper,
+};
+
+const MAX_ROUNDS: usize = 64;
+
+/// Symmetric scratch space for collectives that exchange per-PE counts.
+///
+/// Sized for the world team, so one scratch can be used with any team. It is
+/// allocated collectively over all PEs and must not be shared by collectives
+/// running concurrently on overlapping teams. Collectives that forward data
+/// through intermediate PEs additionally need a byte staging area, see
+/// [`CollectiveScratch::with_staging`].
+///
+/// The `try_` collectives taking a scratch check their arguments on every
+/// member and agree on the outcome before moving data, so a short buffer on
+/// one member makes all of them return the same [`ShmemError`].
+pub struct CollectiveScratch<'a> {
+    pub(crate) scope: &'a OsmScope,
+    send: ShVec<'a, i64>,
+    recv: ShVec<'a, i64>,
+    pub(crate) staging: ShVec<'a, u8>,
+    /// One arrival flag per communication round.
+    pub(crate) signals: ShVec<'a, i64>,
+}
+
+impl<'a> CollectiveScratch<'a> {
+    pub fn new(scope: &'a OsmScope) -> Self {
+        Self::with_staging(0, scope)
+    }
+
+    /// Every PE has to pass the same `staging_bytes`.
+    pub fn with_staging(staging_bytes: usize, scope: &'a OsmScope) -> Self {
+        let num_pes = scope.num_pes() as usize;
+
+        // room for the two words per member of agree
+        let mut send = ShVec::with_capacity(num_pes.max(2), scope);
+        send.resize_with(num_pes.max(2), || 0);
+        let mut recv = ShVec::with_capacity(2 * num_pes, scope);
+        recv.resize_with(2 * num_pes, || 0);
+        let mut staging = ShVec::with_capacity(staging_bytes, scope);
+        staging.resize_with(staging_bytes, || 0);
+        let mut signals = ShVec::with_capacity(MAX_ROUNDS, scope);
+        signals.resize_with(MAX_ROUNDS, || 0);
+
+        scope.barrier_all();
+
+        CollectiveScratch {
+            scope,
+            send,
+            recv,
+            staging,
+            signals,
+        }
+    }
+
+    /// Exchange one count per PE so that every member learns all of them.
+    #[track_caller]
+    fn all_counts(&mut self, team: OsmTeam, count: usize) -> Result<Vec<usize>, ShmemError> {
+        let num_pes = team.num_pes() as usize;
+        // a fast member must not overwrite recv, or the dst of the collective
+        // using these counts, while a slow one still reads the previous result
+        team.try_sync()?;
+        *self.send[0] = count as i64;
+        team.try_fcollect(&self.send[..1], &mut self.recv[..num_pes])?;
+        Ok(self.recv[..num_pes].iter().map(|&c| c as usize).collect())
+    }
+
+    /// Send `values[i]` to member `i` and return the value received from each member.
+    #[track_caller]
+    fn exchange_counts(
+        &mut self,
+        team: OsmTeam,
+        values: &[usize],
+    ) -> Result<Vec<usize>, ShmemError> {
+        let num_pes = team.num_pes() as usize;
+        team.try_sync()?;
+        for (slot, &value) in self.send[..num_pes].iter_mut().zip(values) {
+            *slot = value as i64;
+        }
+
+        let result = unsafe {
+            shmem_alltoallmem(
+                team.inner,
+                self.recv.as_mut_ptr().cast(),
+                self.send.as_ptr().cast(),
+                std::mem::size_of::<i64>(),
+            )
+        };
+        check("shmem_alltoallmem", result)?;
+
+        Ok(self.recv[..num_pes].iter().map(|&c| c as usize).collect())
+    }
+
+    /// Agree on the size checks of every member: if any of them failed, all
+    /// members return the error of the first one in team order, so none is
+    /// left waiting in the next step of the collective.
+    ///
+    /// `local` must be `Ok` or a [`ShmemError::Size`] for `operation`.
+    #[track_caller]
+    pub(crate) fn agree(
+        &mut self,
+        team: OsmTeam,
+        operation: &'static str,
+        local: Result<(), ShmemError>,
+    ) -> Result<(), ShmemError> {
+        let (expected, actual) = match local {
+            Ok(()) => (0, 0),
+            Err(ShmemError::Size {
+                expected, actual, ..
+            }) => (expected, actual),
+            Err(err) => unreachable!("only size checks are agreed on, got {err}"),
+        };
+
+        let num_pes = team.num_pes() as usize;
+        team.try_sync()?;
+        *self.send[0] = expected as i64;
+        *self.send[1] = actual as i64;
+        team.try_fcollect(&self.send[..2], &mut self.recv[..2 * num_pes])?;
+
+        // a failed check always needs more than zero bytes
+        let recv: &[i64] = &self.recv[..2 * num_pes];
+        match recv.chunks(2).position(|pair| pair[0] != 0) {
+            None => Ok(()),
+            Some(rank) => Err(ShmemError::Size {
+                operation,
+                pe: team.translate_pe(rank as i32, OsmTeam::world()),
+                expected: recv[2 * rank] as usize,
+                actual: recv[2 * rank + 1] as usize,
+            }),
+        }
+    }
+}
+
+/// Order in which [`OsmTeam::alltoallv`] moves the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlltoallvSchedule {
+    /// Every member puts to members `0..n` in order.
+    #[default]
+    Linear,
+    /// In step `s` every member puts to `rank + s`, spreading the load evenly.
+    Pairwise,
+    /// `log2(n)` rounds forwarding blocks through intermediate members; fewer
+    /// messages for small payloads. Needs a scratch with a staging area.
+    Bruck,
+}
+
+impl OsmTeam {
+    /// Concatenate equally sized contributions of all members into `dst` on every member.
+    #[track_caller]
+    pub fn fcollect<T: ShmemPod>(self, src: &OsmSlice<T>, dst: &mut OsmSlice<T>) {
+        self.try_fcollect(src, dst)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    #[track_caller]
+    pub fn try_fcollect<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+    ) -> Result<(), ShmemError> {
+        check_size(
+            "shmem_fcollectmem",
+            std::mem::size_of_val::<[T]>(src) * self.num_pes() as usize,
+            std::mem::size_of_val::<[T]>(dst),
+        )?;
+
+        let result = unsafe {
+            shmem_fcollectmem(
+                self.inner,
+                dst.as_mut_ptr().cast(),
+                src.as_ptr().cast(),
+                std::mem::size_of_val::<[T]>(src),
+            )
+        };
+        check("shmem_fcollectmem", result)
+    }
+
+    /// Concatenate variable-size contributions of all members into `dst` on
+    /// every member, in team order.
+    ///
+    /// Returns the number of elements each member contributed.
+    #[track_caller]
+    pub fn collect<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        scratch: &mut CollectiveScratch,
+    ) -> Vec<usize> {
+        self.try_collect(src, dst, scratch)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[track_caller]
+    pub fn try_collect<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<Vec<usize>, ShmemError> {
+        let counts = scratch.all_counts(self, src.len())?;
+        let dst_fits = check_size(
+            "shmem_collectmem",
+            counts.iter().sum::<usize>() * std::mem::size_of::<T>(),
+            std::mem::size_of_val::<[T]>(dst),
+        );
+        scratch.agree(self, "shmem_collectmem", dst_fits)?;
+
+        let result = unsafe {
+            shmem_collectmem(
+                self.inner,
+                dst.as_mut_ptr().cast(),
+                src.as_ptr().cast(),
+                std::mem::size_of_val::<[T]>(src),
+            )
+        };
+        check("shmem_collectmem", result)?;
+
+        Ok(counts)
+    }
+
+    /// Concatenate variable-size contributions of all members into `dst` on `root`.
+    ///
+    /// Every member puts its data directly at its offset on the root. Returns
+    /// the number of elements each member contributed.
+    #[track_caller]
+    pub fn gather<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        root: i32,
+        scratch: &mut CollectiveScratch,
+    ) -> Vec<usize> {
+        self.try_gather(src, dst, root, scratch)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[track_caller]
+    pub fn try_gather<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        root: i32,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<Vec<usize>, ShmemError> {
+        self.check_root("gather", root)?;
+        let counts = scratch.all_counts(self, src.len())?;
+        let offset: usize = counts[..self.my_pe() as usize].iter().sum();
+        // every member addresses the root's buffer through its own `dst`
+        let needed = if self.my_pe() == root {
+            counts.iter().sum::<usize>()
+        } else {
+            offset + src.len()
+        };
+        let dst_fits = check_size(
+            "gather",
+            needed * std::mem::size_of::<T>(),
+            std::mem::size_of_val::<[T]>(dst),
+        );
+        scratch.agree(self, "gather", dst_fits)?;
+
+        let root_pe = self.translate_pe(root, OsmTeam::world());
+        src.put_to_nbi(&mut dst[offset..offset + src.len()], root_pe);
+        scratch.scope.quiet();
+        self.try_sync()?;
+
+        Ok(counts)
+    }
+
+    /// Distribute `counts[i]` consecutive elements of `src` on `root` to member `i`.
+    ///
+    /// `src` and `counts` are only read on the root. Every member receives its
+    /// part at the start of `dst` and gets back the number of elements.
+    #[track_caller]
+    pub fn scatter<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        counts: &[usize],
+        root: i32,
+        scratch: &mut CollectiveScratch,
+    ) -> usize {
+        self.try_scatter(src, dst, counts, root, scratch)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[track_caller]
+    pub fn try_scatter<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        counts: &[usize],
+        root: i32,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<usize, ShmemError> {
+        self.check_root("scatter", root)?;
+        let args = if self.my_pe() == root {
+            check_scatter_args(src, dst, counts, self.num_pes() as usize)
+        } else {
+            Ok(())
+        };
+        scratch.agree(self, "scatter", args)?;
+        // the counts land in scratch.recv, which agree has just read
+        self.try_sync()?;
+
+        if self.my_pe() == root {
+            let mut offset = 0;
+            for (rank, &count) in counts.iter().enumerate() {
+                let pe = self.translate_pe(rank as i32, OsmTeam::world());
+                src[offset..offset + count].put_to_nbi(&mut dst[..count], pe);
+                OsmWrapper::ref_cast(&(count as i64)).put_to(&mut scratch.recv[0], pe);
+                offset += count;
+            }
+            scratch.scope.quiet();
+        }
+
+        self.try_sync()?;
+
+        Ok(*scratch.recv[0] as usize)
+    }
+
+    /// Exchange variable-size blocks between all members.
+    ///
+    /// Member `i` receives `send_counts[i]` elements starting at
+    /// `send_displs[i]` of `src`. Counts are exchanged first, so the received
+    /// blocks are packed into `dst` in team order. Returns the number of
+    /// elements received from each member.
+    #[track_caller]
+    pub fn alltoallv<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        send_counts: &[usize],
+        send_displs: &[usize],
+        dst: &mut OsmSlice<T>,
+        schedule: AlltoallvSchedule,
+        scratch: &mut CollectiveScratch,
+    ) -> Vec<usize> {
+        self.try_alltoallv(src, send_counts, send_displs, dst, schedule, scratch)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[track_caller]
+    pub fn try_alltoallv<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        send_counts: &[usize],
+        send_displs: &[usize],
+        dst: &mut OsmSlice<T>,
+        schedule: AlltoallvSchedule,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<Vec<usize>, ShmemError> {
+        let num_pes = self.num_pes() as usize;
+        let args = check_alltoallv_args(src, send_counts, send_displs, num_pes);
+        scratch.agree(self, "alltoallv", args)?;
+
+        let recv_counts = scratch.exchange_counts(self, send_counts)?;
+        let recv_displs = prefix_sums(&recv_counts);
+        let dst_fits = check_size(
+            "alltoallv",
+            recv_counts.iter().sum::<usize>() * std::mem::size_of::<T>(),
+            std::mem::size_of_val::<[T]>(dst),
+        );
+        scratch.agree(self, "alltoallv", dst_fits)?;
+
+        if schedule == AlltoallvSchedule::Bruck {
+            self.alltoallv_bruck(src, send_counts, send_displs, dst, &recv_displs, scratch)?;
+            return Ok(recv_counts);
+        }
+
+        // where our block starts in every member's dst
+        let remote_displs = scratch.exchange_counts(self, &recv_displs)?;
+
+        let my_rank = self.my_pe() as usize;
+        for step in 0..num_pes {
+            let rank = match schedule {
+                AlltoallvSchedule::Pairwise => (my_rank + step) % num_pes,
+                _ => step,
+            };
+            let (start, count) = (send_displs[rank], send_counts[rank]);
+            let offset = remote_displs[rank];
+            let pe = self.translate_pe(rank as i32, OsmTeam::world());
+            src[start..start + count].put_to_nbi(&mut dst[offset..offset + count], pe);
+        }
+
+        scratch.scope.quiet();
+        self.try_sync()?;
+
+        Ok(recv_counts)
+    }
+
+    #[track_caller]
+    fn alltoallv_bruck<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        send_counts: &[usize],
+        send_displs: &[usize],
+        dst: &mut OsmSlice<T>,
+        recv_displs: &[usize],
+        scratch: &mut CollectiveScratch,
+    ) -> Result<(), ShmemError> {
+        let num_pes = self.num_pes() as usize;
+        let my_rank = self.my_pe() as usize;
+        let elem = std::mem::size_of::<T>();
+
+        // block r holds the data travelling r ranks forward
+        let mut blocks: Vec<Vec<u8>> = (0..num_pes)
+            .map(|r| {
+                let rank = (my_rank + r) % num_pes;
+                let start = send_displs[rank];
+                as_bytes(&src[start..start + send_counts[rank]]).to_vec()
+            })
+            .collect();
+
+        let mut distance = 1;
+        while distance < num_pes {
+            let moving: Vec<usize> = (0..num_pes).filter(|r| r & distance != 0).collect();
+
+            // header of block lengths, then the blocks padded to 8 bytes
+            let mut packed: Vec<u8> = Vec::new();
+            for &r in &moving {
+                packed.extend_from_slice(&(blocks[r].len() as u64).to_ne_bytes());
+            }
+            for &r in &moving {
+                packed.extend_from_slice(&blocks[r]);
+                packed.resize(packed.len().next_multiple_of(8), 0);
+            }
+
+            // every member learns whether all packets of this round fit, and
+            // the target has to be done unpacking the previous round
+            let lengths = scratch.all_counts(self, packed.len())?;
+            let (sender, &needed) = lengths
+                .iter()
+                .enumerate()
+                .max_by_key(|&(_, len)| len)
+                .unwrap();
+            if needed > scratch.staging.len() {
+                let receiver = (sender + distance) % num_pes;
+                return Err(ShmemError::Size {
+                    operation: "alltoallv",
+                    pe: self.translate_pe(receiver as i32, OsmTeam::world()),
+                    expected: needed,
+                    actual: scratch.staging.len(),
+                });
+            }
+
+            let target = (my_rank + distance) % num_pes;
+            let pe = self.translate_pe(target as i32, OsmTeam::world());
+
+            OsmSlice::ref_cast(&packed[..]).put_to_nbi(&mut scratch.staging[..packed.len()], pe);
+            scratch.scope.quiet();
+            self.try_sync()?;
+
+            let staging: &[u8] = &scratch.staging;
+            let mut offset = moving.len() * 8;
+            for (i, &r) in moving.iter().enumerate() {
+                let len = u64::from_ne_bytes(staging[i * 8..i * 8 + 8].try_into().unwrap());
+                let len = len as usize;
+                blocks[r] = staging[offset..offset + len].to_vec();
+                offset += len.next_multiple_of(8);
+            }
+
+            distance *= 2;
+        }
+
+        // block r now holds what the member r ranks behind sent to us
+        for (r, block) in blocks.iter().enumerate() {
+            let source = (my_rank + num_pes - r) % num_pes;
+            let start = recv_displs[source];
+            let count = block.len() / elem;
+            as_bytes_mut(&mut dst[start..start + count]).copy_from_slice(block);
+        }
+        Ok(())
+    }
+
+    /// `root` has to be a member. Roots are the same on every member, so
+    /// this fails everywhere or nowhere.
+    fn check_root(self, operation: &'static str, root: i32) -> Result<(), ShmemError> {
+        if !(0..self.num_pes()).contains(&root) {
+            return Err(ShmemError::InvalidPe {
+                operation,
+                pe: root,
+                num_pes: self.num_pes(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// One count and displacement per member, each selecting a block inside `src`.
+fn check_alltoallv_args<T>(
+    src: &OsmSlice<T>,
+    send_counts: &[usize],
+    send_displs: &[usize],
+    num_pes: usize,
+) -> Result<(), ShmemError> {
+    let words = |len: usize| len * std::mem::size_of::<usize>();
+    check_size_exact("alltoallv", words(num_pes), words(send_counts.len()))?;
+    check_size_exact("alltoallv", words(num_pes), words(send_displs.len()))?;
+
+    let end = send_counts
+        .iter()
+        .zip(send_displs)
+        .map(|(&count, &displ)| displ.saturating_add(count))
+        .max()
+        .unwrap_or(0);
+    check_size(
+        "alltoallv",
+        end.saturating_mul(std::mem::size_of::<T>()),
+        std::mem::size_of_val::<[T]>(src),
+    )
+}
+
+/// One count per member, all of them inside `src`, and each part fitting the
+/// `dst` of its member, which has the size of the root's.
+fn check_scatter_args<T>(
+    src: &OsmSlice<T>,
+    dst: &OsmSlice<T>,
+    counts: &[usize],
+    num_pes: usize,
+) -> Result<(), ShmemError> {
+    let words = |len: usize| len * std::mem::size_of::<usize>();
+    check_size_exact("scatter", words(num_pes), words(counts.len()))?;
+
+    let elem = std::mem::size_of::<T>();
+    let total = counts
+        .iter()
+        .fold(0usize, |sum, &count| sum.saturating_add(count));
+    check_size(
+        "scatter",
+        total.saturating_mul(elem),
+        std::mem::size_of_val::<[T]>(src),
+    )?;
+    let largest = counts.iter().copied().max().unwrap_or(0);
+    check_size(
+        "scatter",
+        largest.saturating_mul(elem),
+        std::mem::size_of_val::<[T]>(dst),
+    )
+}
+
+fn prefix_sums(counts: &[usize]) -> Vec<usize> {
+    counts
+        .iter()
+        .scan(0, |sum, &count| {
+            let start = *sum;
+            *sum += count;
+            Some(start)
+        })
+        .collect()
+}
+
+pub(crate) fn as_bytes<T: ShmemPod>(slice: &[T]) -> &[u8] {
+    OsmSlice::ref_cast(slice).cast_slice::<u8>()
+}
+
+pub(crate) fn as_bytes_mut<T: ShmemPod>(slice: &mut [T]) -> &mut [u8] {
+    OsmSlice::ref_cast_mut(slice).cast_slice_mut::<u8>()
+}
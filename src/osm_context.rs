@@ -0,0 +1,254 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    mem::MaybeUninit,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    osm_error::{ShmemError, check},
+    osm_ffi::{
+        SHMEM_CTX_NOSTORE, SHMEM_CTX_PRIVATE, SHMEM_CTX_SERIALIZED, shmem_ctx_create,
+        shmem_ctx_destroy, shmem_ctx_fence, shmem_ctx_getmem, shmem_ctx_getmem_nbi,
+        shmem_ctx_putmem, shmem_ctx_putmem_nbi, shmem_ctx_quiet, shmem_ctx_t,
+    },
+    osm_pod::ShmemPod,
+    osm_scope::OsmScope,
+    osm_slice::OsmSlice,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContextOptions {
+    /// Only used by the creating thread.
+    pub private: bool,
+    /// Never used by more than one thread at a time.
+    pub serialized: bool,
+    /// `quiet` and `fence` are not needed for stores on this context.
+    pub nostore: bool,
+}
+
+impl ContextOptions {
+    fn bits(self) -> i64 {
+        let mut bits = 0;
+        if self.private {
+            bits |= SHMEM_CTX_PRIVATE as i64;
+        }
+        if self.serialized {
+            bits |= SHMEM_CTX_SERIALIZED as i64;
+        }
+        if self.nostore {
+            bits |= SHMEM_CTX_NOSTORE as i64;
+        }
+        bits
+    }
+}
+
+/// A communication context: RMA issued on it is ordered and completed
+/// independently of the default context and of other contexts.
+pub struct OsmContext<'a> {
+    inner: shmem_ctx_t,
+    /// Number of quiets issued on this context.
+    quiets: Cell<u64>,
+    _scope: &'a OsmScope,
+}
+
+impl<'a> OsmContext<'a> {
+    pub fn new(options: ContextOptions, scope: &'a OsmScope) -> Self {
+        Self::try_new(options, scope).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(options: ContextOptions, scope: &'a OsmScope) -> Result<Self, ShmemError> {
+        let mut ctx = MaybeUninit::uninit();
+        let result = unsafe { shmem_ctx_create(options.bits(), ctx.as_mut_ptr()) };
+        check("shmem_ctx_create", result)?;
+
+        Ok(OsmContext {
+            inner: unsafe { ctx.assume_init() },
+            quiets: Cell::new(0),
+            _scope: scope,
+        })
+    }
+
+    #[track_caller]
+    pub fn put_to<T: ShmemPod>(&self, src: &OsmSlice<T>, dst: &mut OsmSlice<T>, pe: i32) {
+        unsafe {
+            shmem_ctx_putmem(
+                self.inner,
+                dst.as_mut_ptr().cast(),
+                src.as_ptr().cast(),
+                std::mem::size_of_val::<[T]>(src),
+                pe,
+            );
+        }
+    }
+
+    /// Start a put on this context, completed by [`OsmContext::quiet`] only.
+    #[track_caller]
+    pub fn put_to_nbi<T: ShmemPod>(
+        &self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        pe: i32,
+    ) -> ContextNbiHandle<'_, 'a> {
+        unsafe {
+            shmem_ctx_putmem_nbi(
+                self.inner,
+                dst.as_mut_ptr().cast(),
+                src.as_ptr().cast(),
+                std::mem::size_of_val::<[T]>(src),
+                pe,
+            );
+        }
+        self.issue()
+    }
+
+    #[track_caller]
+    pub fn get_from<T: ShmemPod>(&self, dst: &mut OsmSlice<T>, src: &OsmSlice<T>, pe: i32) {
+        unsafe {
+            shmem_ctx_getmem(
+                self.inner,
+                dst.as_mut_ptr().cast(),
+                src.as_ptr().cast(),
+                std::mem::size_of_val::<[T]>(dst),
+                pe,
+            );
+        }
+    }
+
+    /// Start a get on this context, completed by [`OsmContext::quiet`] only.
+    #[track_caller]
+    pub fn get_from_nbi<T: ShmemPod>(
+        &self,
+        dst: &mut OsmSlice<T>,
+        src: &OsmSlice<T>,
+        pe: i32,
+    ) -> ContextNbiHandle<'_, 'a> {
+        unsafe {
+            shmem_ctx_getmem_nbi(
+                self.inner,
+                dst.as_mut_ptr().cast(),
+                src.as_ptr().cast(),
+                std::mem::size_of_val::<[T]>(dst),
+                pe,
+            );
+        }
+        self.issue()
+    }
+
+    /// [`OsmContext::put_to`] after checking the target PE, that `dst` is
+    /// remotely accessible and large enough.
+    #[track_caller]
+    pub fn try_put_to<T: ShmemPod>(
+        &self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        pe: i32,
+    ) -> Result<(), ShmemError> {
+        src.check_transfer("shmem_ctx_putmem", dst, pe)?;
+        self.put_to(src, dst, pe);
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn try_put_to_nbi<T: ShmemPod>(
+        &self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        pe: i32,
+    ) -> Result<ContextNbiHandle<'_, 'a>, ShmemError> {
+        src.check_transfer("shmem_ctx_putmem_nbi", dst, pe)?;
+        Ok(self.put_to_nbi(src, dst, pe))
+    }
+
+    #[track_caller]
+    pub fn try_get_from<T: ShmemPod>(
+        &self,
+        dst: &mut OsmSlice<T>,
+        src: &OsmSlice<T>,
+        pe: i32,
+    ) -> Result<(), ShmemError> {
+        dst.check_transfer("shmem_ctx_getmem", src, pe)?;
+        self.get_from(dst, src, pe);
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn try_get_from_nbi<T: ShmemPod>(
+        &self,
+        dst: &mut OsmSlice<T>,
+        src: &OsmSlice<T>,
+        pe: i32,
+    ) -> Result<ContextNbiHandle<'_, 'a>, ShmemError> {
+        dst.check_transfer("shmem_ctx_getmem_nbi", src, pe)?;
+        Ok(self.get_from_nbi(dst, src, pe))
+    }
+
+    /// Complete the RMA issued on this context only.
+    #[track_caller]
+    pub fn quiet(&self) {
+        unsafe { shmem_ctx_quiet(self.inner) }
+        self.quiets.set(self.quiets.get() + 1);
+    }
+
+    fn issue(&self) -> ContextNbiHandle<'_, 'a> {
+        ContextNbiHandle {
+            ctx: self,
+            epoch: self.quiets.get(),
+        }
+    }
+
+    #[track_caller]
+    pub fn fence(&self) {
+        unsafe { shmem_ctx_fence(self.inner) }
+    }
+}
+
+impl Drop for OsmContext<'_> {
+    fn drop(&mut self) {
+        unsafe { shmem_ctx_destroy(self.inner) }
+    }
+}
+
+/// Completion handle of a non-blocking operation issued on an [`OsmContext`].
+///
+/// Unlike [`NbiHandle`](crate::osm_future::NbiHandle), it is completed by a
+/// quiet on its context; [`OsmScope::quiet`] and barriers do not complete it.
+/// Awaiting the handle or calling [`ContextNbiHandle::wait`] quiets the
+/// context if needed.
+#[derive(Clone, Copy)]
+pub struct ContextNbiHandle<'c, 'a> {
+    ctx: &'c OsmContext<'a>,
+    epoch: u64,
+}
+
+impl ContextNbiHandle<'_, '_> {
+    pub fn is_complete(&self) -> bool {
+        self.ctx.quiets.get() > self.epoch
+    }
+
+    #[track_caller]
+    pub fn wait(self) {
+        if !self.is_complete() {
+            self.ctx.quiet();
+        }
+    }
+}
+
+impl std::fmt::Debug for ContextNbiHandle<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextNbiHandle")
+            .field("epoch", &self.epoch)
+            .field("complete", &self.is_complete())
+            .finish()
+    }
+}
+
+impl Future for ContextNbiHandle<'_, '_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        self.wait();
+        Poll::Ready(())
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    ffi::c_void,
+    fmt::{Display, Formatter},
+};
+
+use crate::osm_ffi::{shmem_addr_accessible, shmem_my_pe, shmem_n_pes};
+
+/// Failure of a SHMEM operation, with enough context to report it.
+///
+/// The `try_` collectives that take a
+/// [`CollectiveScratch`](crate::osm_collective::CollectiveScratch) agree on
+/// their argument checks, so when one member's buffers are wrong every member
+/// returns the same error instead of waiting for it. The others, like
+/// [`OsmTeam::try_fcollect`](crate::osm_team::OsmTeam::try_fcollect), check locally and
+/// rely on all members passing consistent arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShmemError {
+    /// The symmetric heap of `pe` could not satisfy an allocation.
+    Alloc {
+        operation: &'static str,
+        pe: i32,
+        size: usize,
+        align: usize,
+    },
+    /// The runtime returned a non-zero status code on `pe`.
+    Runtime {
+        operation: &'static str,
+        pe: i32,
+        code: i32,
+    },
+    /// The target PE does not exist.
+    InvalidPe {
+        operation: &'static str,
+        pe: i32,
+        num_pes: i32,
+    },
+    /// The remote address is not accessible from this PE, usually because the
+    /// buffer is not symmetric.
+    Inaccessible { operation: &'static str, pe: i32 },
+    /// A buffer on `pe` is smaller than the operation needs, or has the wrong
+    /// size for it.
+    Size {
+        operation: &'static str,
+        pe: i32,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl Display for ShmemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShmemError::Alloc {
+                operation,
+                pe,
+                size,
+                align,
+            } => write!(
+                f,
+                "{operation}: failed to allocate {size} bytes aligned to {align} on the symmetric heap of PE {pe}"
+            ),
+            ShmemError::Runtime {
+                operation,
+                pe,
+                code,
+            } => write!(f, "{operation}: runtime returned {code} on PE {pe}"),
+            ShmemError::InvalidPe {
+                operation,
+                pe,
+                num_pes,
+            } => write!(f, "{operation}: PE {pe} is out of range for {num_pes} PEs"),
+            ShmemError::Inaccessible { operation, pe } => {
+                write!(f, "{operation}: address is not accessible on PE {pe}")
+            }
+            ShmemError::Size {
+                operation,
+                pe,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{operation}: needs {expected} bytes, got {actual} on PE {pe}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShmemError {}
+
+/// Map a C status code to `Ok` for zero and a [`ShmemError::Runtime`] otherwise.
+pub(crate) fn check(operation: &'static str, code: i32) -> Result<(), ShmemError> {
+    if code == 0 {
+        Ok(())
+    } else {
+        Err(ShmemError::Runtime {
+            operation,
+            pe: unsafe { shmem_my_pe() },
+            code,
+        })
+    }
+}
+
+/// A failed allocation of `size` bytes on the calling PE.
+pub(crate) fn alloc_error(operation: &'static str, size: usize, align: usize) -> ShmemError {
+    ShmemError::Alloc {
+        operation,
+        pe: unsafe { shmem_my_pe() },
+        size,
+        align,
+    }
+}
+
+/// Check that `addr` can be the remote side of an RMA or atomic on `pe`.
+pub(crate) fn check_remote(
+    operation: &'static str,
+    addr: *const c_void,
+    pe: i32,
+) -> Result<(), ShmemError> {
+    let num_pes = unsafe { shmem_n_pes() };
+    if !(0..num_pes).contains(&pe) {
+        return Err(ShmemError::InvalidPe {
+            operation,
+            pe,
+            num_pes,
+        });
+    }
+    if unsafe { shmem_addr_accessible(addr, pe) } != 1 {
+        return Err(ShmemError::Inaccessible { operation, pe });
+    }
+    Ok(())
+}
+
+/// Check that a buffer of `actual` bytes can hold the `expected` bytes.
+pub(crate) fn check_size(
+    operation: &'static str,
+    expected: usize,
+    actual: usize,
+) -> Result<(), ShmemError> {
+    if actual < expected {
+        return Err(ShmemError::Size {
+            operation,
+            pe: unsafe { shmem_my_pe() },
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Check that a buffer of `actual` bytes has exactly the `expected` size.
+pub(crate) fn check_size_exact(
+    operation: &'static str,
+    expected: usize,
+    actual: usize,
+) -> Result<(), ShmemError> {
+    if actual != expected {
+        return Err(ShmemError::Size {
+            operation,
+            pe: unsafe { shmem_my_pe() },
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! The OpenSHMEM C API the rest of the crate calls into: `openshmem-sys`, or
+//! the threads-as-PEs simulator with the `sim` feature. With `record`, the
+//! operations are shadowed by the logging versions in `osm_record::ffi`.
+
+#[cfg(not(feature = "sim"))]
+pub use openshmem_sys::*;
+
+#[cfg(feature = "sim")]
+pub use crate::osm_sim::ffi::*;
+
+#[cfg(feature = "record")]
+pub use crate::osm_record::ffi::{
+    shmem_alltoall64, shmem_alltoallmem, shmem_barrier_all, shmem_broadcast64, shmem_broadcastmem,
+    shmem_collectmem, shmem_ctx_fence, shmem_ctx_getmem, shmem_ctx_getmem_nbi, shmem_ctx_putmem,
+    shmem_ctx_putmem_nbi, shmem_ctx_quiet, shmem_fcollect64, shmem_fcollectmem, shmem_fence,
+    shmem_getmem, shmem_getmem_nbi, shmem_iget8, shmem_iget16, shmem_iget32, shmem_iget64,
+    shmem_iget128, shmem_int_atomic_fetch_add, shmem_int_cswap, shmem_int_sum_reduce,
+    shmem_int_sum_to_all, shmem_iput8, shmem_iput16, shmem_iput32, shmem_iput64, shmem_iput128,
+    shmem_long_atomic_compare_swap, shmem_long_atomic_fetch, shmem_long_atomic_fetch_add,
+    shmem_long_atomic_set, shmem_long_cswap, shmem_long_wait_until, shmem_putmem, shmem_putmem_nbi,
+    shmem_quiet, shmem_sync_all, shmem_team_sync,
+};
@@ -0,0 +1,208 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+use crate::{osm_ffi::shmem_quiet, osm_scope::OsmScope};
+
+/// Number of quiets (or barriers, which imply one) completed on this PE.
+#[cfg(not(feature = "sim"))]
+static QUIET_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+// simulated PEs share the process, so each thread counts its own quiets
+#[cfg(feature = "sim")]
+thread_local! {
+    static QUIET_EPOCH: AtomicU64 = const { AtomicU64::new(0) };
+}
+
+fn with_quiet_epoch<R>(f: impl FnOnce(&AtomicU64) -> R) -> R {
+    #[cfg(feature = "sim")]
+    return QUIET_EPOCH.with(f);
+    #[cfg(not(feature = "sim"))]
+    f(&QUIET_EPOCH)
+}
+
+thread_local! {
+    static QUIET_WAITERS: RefCell<Vec<Waker>> = const { RefCell::new(Vec::new()) };
+    static EXECUTOR_ACTIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Record that every non-blocking operation issued so far has completed.
+///
+/// Called by [`OsmScope::quiet`] and [`OsmScope::barrier_all`].
+pub(crate) fn complete_epoch() {
+    with_quiet_epoch(|epoch| epoch.fetch_add(1, Ordering::Release));
+    QUIET_WAITERS.with_borrow_mut(|waiters| waiters.drain(..).for_each(Waker::wake));
+}
+
+/// Completion handle of a non-blocking operation.
+///
+/// The operation is complete once a quiet has been issued after it. Awaiting
+/// the handle inside a [`LocalExecutor`] parks the task until the executor
+/// runs out of ready work and quiets; awaiting it anywhere else, or calling
+/// [`NbiHandle::wait`], quiets immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NbiHandle {
+    epoch: u64,
+}
+
+impl NbiHandle {
+    pub(crate) fn issue() -> Self {
+        NbiHandle {
+            epoch: with_quiet_epoch(|epoch| epoch.load(Ordering::Acquire)),
+        }
+    }
+
+    /// Combine several handles into one that completes when all of them have.
+    pub fn join(handles: impl IntoIterator<Item = NbiHandle>) -> NbiHandle {
+        let epoch = handles
+            .into_iter()
+            .map(|handle| handle.epoch)
+            .max()
+            .unwrap_or(0);
+        NbiHandle { epoch }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        with_quiet_epoch(|epoch| epoch.load(Ordering::Acquire)) > self.epoch
+    }
+
+    #[track_caller]
+    pub fn wait(self, scope: &OsmScope) {
+        if !self.is_complete() {
+            scope.quiet();
+        }
+    }
+}
+
+impl Future for NbiHandle {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.is_complete() {
+            return Poll::Ready(());
+        }
+
+        if EXECUTOR_ACTIVE.get() {
+            QUIET_WAITERS.with_borrow_mut(|waiters| waiters.push(cx.waker().clone()));
+            Poll::Pending
+        } else {
+            // nobody will quiet on our behalf
+            unsafe { shmem_quiet() };
+            complete_epoch();
+            Poll::Ready(())
+        }
+    }
+}
+
+/// Future that lets other tasks run once before resolving.
+pub fn yield_now() -> impl Future<Output = ()> {
+    let mut yielded = false;
+    std::future::poll_fn(move |cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+}
+
+struct TaskWaker {
+    id: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.ready.lock().unwrap().push_back(self.id);
+    }
+}
+
+type Task<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// Single-threaded executor that quiets whenever every task is blocked.
+///
+/// Tasks overlap computation with outstanding non-blocking operations: a
+/// task awaiting an [`NbiHandle`] is resumed after the next quiet, which the
+/// executor only issues once no other task can make progress.
+pub struct LocalExecutor<'a> {
+    scope: &'a OsmScope,
+    tasks: Vec<Option<Task<'a>>>,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl<'a> LocalExecutor<'a> {
+    pub fn new(scope: &'a OsmScope) -> Self {
+        LocalExecutor {
+            scope,
+            tasks: Vec::new(),
+            ready: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn spawn(&mut self, future: impl Future<Output = ()> + 'a) {
+        self.ready.lock().unwrap().push_back(self.tasks.len());
+        self.tasks.push(Some(Box::pin(future)));
+    }
+
+    /// Run until every spawned task has finished.
+    pub fn run(&mut self) {
+        let was_active = EXECUTOR_ACTIVE.replace(true);
+
+        while self.tasks.iter().any(Option::is_some) {
+            let next = self.ready.lock().unwrap().pop_front();
+            match next {
+                Some(id) => self.poll_task(id),
+                None if QUIET_WAITERS.with_borrow(|waiters| !waiters.is_empty()) => {
+                    self.scope.quiet();
+                }
+                None => std::thread::yield_now(),
+            }
+        }
+
+        EXECUTOR_ACTIVE.set(was_active);
+    }
+
+    /// Run `future` together with the spawned tasks and return its output.
+    pub fn block_on<T: 'a>(&mut self, future: impl Future<Output = T> + 'a) -> T {
+        let output = Rc::new(RefCell::new(None));
+        let slot = output.clone();
+        self.spawn(async move {
+            let value = future.await;
+            *slot.borrow_mut() = Some(value);
+        });
+        self.run();
+
+        output.take().expect("block_on future did not complete")
+    }
+
+    fn poll_task(&mut self, id: usize) {
+        let Some(task) = self.tasks[id].as_mut() else {
+            return;
+        };
+
+        let waker = Waker::from(Arc::new(TaskWaker {
+            id,
+            ready: self.ready.clone(),
+        }));
+        let mut cx = Context::from_waker(&waker);
+
+        if task.as_mut().poll(&mut cx).is_ready() {
+            self.tasks[id] = None;
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use ref_cast::RefCast;
+
+use crate::{
+    osm_ffi::shmem_fence, osm_pod::ShmemPod, osm_scope::OsmScope, osm_vec::ShVec,
+    osm_wrapper::OsmWrapper,
+};
+
+const EMPTY: i64 = 0;
+const BUSY: i64 = 1;
+const FULL: i64 = 2;
+
+/// Fixed-capacity hash map spread over all PEs.
+///
+/// Every PE hosts `capacity` open-addressed buckets in symmetric memory. A key
+/// lives on the PE selected by its hash and is probed linearly from there.
+/// Slots are claimed with a remote compare-and-swap on their state word; key
+/// and value are then written with puts and published by setting the state to
+/// `FULL` after a fence.
+///
+/// Construction is collective. Values are not updated atomically, so a `get`
+/// racing an `insert` of the same key may observe a torn value.
+pub struct DistHashMap<'a, K, V> {
+    states: ShVec<'a, i64>,
+    keys: ShVec<'a, K>,
+    values: ShVec<'a, V>,
+    capacity: usize,
+    num_pes: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistHashMapError {
+    Full,
+}
+
+impl<'a, K, V> DistHashMap<'a, K, V>
+where
+    K: Hash + Eq + ShmemPod + Copy + Default,
+    V: ShmemPod + Copy + Default,
+{
+    pub fn new(capacity: usize, scope: &'a OsmScope) -> Self {
+        assert!(capacity > 0, "DistHashMap requires a non-zero capacity");
+
+        let mut states = ShVec::with_capacity(capacity, scope);
+        states.resize_with(capacity, || EMPTY);
+        let mut keys = ShVec::with_capacity(capacity, scope);
+        keys.resize_with(capacity, K::default);
+        let mut values = ShVec::with_capacity(capacity, scope);
+        values.resize_with(capacity, V::default);
+
+        // make sure every PE has initialized its buckets before anyone probes them
+        scope.barrier_all();
+
+        DistHashMap {
+            states,
+            keys,
+            values,
+            capacity,
+            num_pes: scope.num_pes() as usize,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn owner_of(&self, key: &K) -> i32 {
+        self.locate(key).0
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Result<(), DistHashMapError> {
+        let (pe, start) = self.locate(&key);
+
+        for probe in 0..self.capacity {
+            let slot = (start + probe) % self.capacity;
+
+            match self.states[slot].compare_and_swap(EMPTY, BUSY, pe) {
+                EMPTY => {
+                    OsmWrapper::ref_cast(&key).put_to(&mut self.keys[slot], pe);
+                    OsmWrapper::ref_cast(&value).put_to(&mut self.values[slot], pe);
+                    // key and value must land before the slot becomes visible
+                    unsafe { shmem_fence() };
+                    self.states[slot].atomic_set(FULL, pe);
+                    return Ok(());
+                }
+                _ => {
+                    self.wait_published(slot, pe);
+                    if self.keys[slot].get_value(pe) == key {
+                        OsmWrapper::ref_cast(&value).put_to(&mut self.values[slot], pe);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        Err(DistHashMapError::Full)
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let (pe, start) = self.locate(key);
+
+        for probe in 0..self.capacity {
+            let slot = (start + probe) % self.capacity;
+
+            if self.wait_published(slot, pe) == EMPTY {
+                return None;
+            }
+
+            if self.keys[slot].get_value(pe) == *key {
+                return Some(self.values[slot].get_value(pe));
+            }
+        }
+
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn hash(key: &K) -> u64 {
+        // DefaultHasher::new uses fixed keys, so every PE agrees on the owner
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn locate(&self, key: &K) -> (i32, usize) {
+        let hash = Self::hash(key);
+        let pe = (hash % self.num_pes as u64) as i32;
+        let start = ((hash / self.num_pes as u64) % self.capacity as u64) as usize;
+        (pe, start)
+    }
+
+    /// Spin while another PE is still writing the slot, returning its final state.
+    fn wait_published(&self, slot: usize, pe: i32) -> i64 {
+        loop {
+            let state = self.states[slot].atomic_fetch(pe);
+            if state != BUSY {
+                return state;
+            }
+            std::hint::spin_loop();
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use ref_cast::RefCast;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    osm_box::OsmBox,
+    osm_ffi::shmem_fence,
+    osm_scope::OsmScope,
+    osm_team::OsmTeam,
+    osm_vec::ShVec,
+    osm_wrapper::{OsmWrapper, ShmemCmp},
+};
+
+/// Symmetric staging area for shipping serde-serializable values between PEs.
+///
+/// Values are encoded as JSON into a local staging buffer and put, together
+/// with a length header, into the target's inbox. A sender first claims the
+/// target's inbox with a remote compare-and-swap on its `lock`, then signals
+/// the message by incrementing the target's `sequence` after a fence. The
+/// receiver waits on `sequence`, decodes the value and releases the lock, so
+/// several PEs may send to the same target; each inbox holds one message.
+///
+/// Construction and [`ObjectBuffer::broadcast_value`] are collective. A
+/// broadcast must not overlap point-to-point messages on the same buffer.
+pub struct ObjectBuffer<'a> {
+    staging: ShVec<'a, u8>,
+    inbox: ShVec<'a, u8>,
+    staging_len: ShVec<'a, i64>,
+    inbox_len: ShVec<'a, i64>,
+    lock: OsmBox<'a, i64>,
+    sequence: OsmBox<'a, i64>,
+    received: i64,
+    capacity: usize,
+    my_pe: i32,
+}
+
+impl<'a> ObjectBuffer<'a> {
+    pub fn new(capacity: usize, scope: &'a OsmScope) -> Self {
+        let mut staging = ShVec::with_capacity(capacity, scope);
+        staging.resize_with(capacity, || 0);
+        let mut inbox = ShVec::with_capacity(capacity, scope);
+        inbox.resize_with(capacity, || 0);
+        let mut staging_len = ShVec::with_capacity(1, scope);
+        staging_len.resize_with(1, || 0);
+        let mut inbox_len = ShVec::with_capacity(1, scope);
+        inbox_len.resize_with(1, || 0);
+        let lock = OsmBox::new(0, scope);
+        let sequence = OsmBox::new(0, scope);
+
+        scope.barrier_all();
+
+        ObjectBuffer {
+            staging,
+            inbox,
+            staging_len,
+            inbox_len,
+            lock,
+            sequence,
+            received: 0,
+            capacity,
+            my_pe: scope.my_pe(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Serialize `value` and deliver it to `target_pe`, waiting until the
+    /// target's inbox is free.
+    pub fn put_value<T: Serialize>(&mut self, value: &T, target_pe: i32) {
+        let len = self.stage(value);
+
+        while self.lock.compare_and_swap(0, 1, target_pe) != 0 {
+            std::hint::spin_loop();
+        }
+
+        self.staging[..len].put_to(&mut self.inbox[..len], target_pe);
+        OsmWrapper::ref_cast(&(len as i64)).put_to(&mut self.inbox_len[0], target_pe);
+        // payload and header must land before the target sees the signal
+        unsafe { shmem_fence() };
+        self.sequence.fetch_add(1, target_pe);
+    }
+
+    /// Wait for the next value sent to this PE and deserialize it.
+    pub fn get_value<T: DeserializeOwned>(&mut self) -> T {
+        self.sequence.wait_until(ShmemCmp::Gt, self.received);
+        self.take()
+    }
+
+    pub fn try_get_value<T: DeserializeOwned>(&mut self) -> Option<T> {
+        if self.sequence.atomic_fetch(self.my_pe) > self.received {
+            Some(self.take())
+        } else {
+            None
+        }
+    }
+
+    /// Broadcast `value` from `root` to every PE. Collective.
+    ///
+    /// Only the root's `value` is read; other PEs may pass `None`.
+    pub fn broadcast_value<T>(&mut self, value: Option<&T>, root: i32, scope: &OsmScope) -> T
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        if scope.my_pe() == root {
+            let value = value.expect("the broadcast root has to provide a value");
+            let len = self.stage(value);
+            *self.staging_len[0] = len as i64;
+        }
+
+        let team = OsmTeam::world();
+        team.broadcast(&self.staging_len, &mut self.inbox_len, root);
+
+        let len = if scope.my_pe() == root {
+            *self.staging_len[0] as usize
+        } else {
+            *self.inbox_len[0] as usize
+        };
+        team.broadcast(&self.staging[..len], &mut self.inbox[..len], root);
+
+        let bytes: &[u8] = if scope.my_pe() == root {
+            &self.staging[..len]
+        } else {
+            &self.inbox[..len]
+        };
+        serde_json::from_slice(bytes).expect("Failed to deserialize broadcast value")
+    }
+
+    fn stage<T: Serialize>(&mut self, value: &T) -> usize {
+        let bytes = serde_json::to_vec(value).expect("Failed to serialize value");
+        assert!(
+            bytes.len() <= self.capacity,
+            "serialized value of {} bytes exceeds the object buffer capacity of {}",
+            bytes.len(),
+            self.capacity
+        );
+
+        self.staging[..bytes.len()].copy_from_slice(&bytes);
+        bytes.len()
+    }
+
+    fn take<T: DeserializeOwned>(&mut self) -> T {
+        let len = *self.inbox_len[0] as usize;
+        let value = serde_json::from_slice(&self.inbox[..len])
+            .expect("Failed to deserialize received value");
+        self.received += 1;
+
+        // hand the inbox to the next sender
+        self.lock.atomic_set(0, self.my_pe);
+
+        value
+    }
+}
@@ -0,0 +1,37 @@
+pub use openshmem_benchmark_derive::ShmemPod;
+
+/// Plain data that keeps its meaning when its bytes are copied to another PE.
+///
+/// Required by every RMA, atomic and collective entry point, so pointer
+/// bearing types like `Vec` or `Box` cannot be transferred. Derive it for
+/// `#[repr(C)]` structs of `ShmemPod` fields:
+///
+/// ```ignore
+/// #[derive(Clone, Copy, ShmemPod)]
+/// #[repr(C)]
+/// struct Sample {
+///     value: f64,
+///     index: u64,
+/// }
+/// ```
+///
+/// # Safety
+///
+/// The type must not contain pointers, references or padding, and every bit
+/// pattern of its size must be a valid value. It must not have interior
+/// mutability either, as [`OsmSlice::cast_slice`](crate::osm_slice::OsmSlice::cast_slice)
+/// shares its bytes as another `ShmemPod` type; symmetric atomics go through
+/// `OsmWrapper<AtomicI64>` instead.
+pub unsafe trait ShmemPod: Sized {}
+
+macro_rules! impl_shmem_pod {
+    ($($t:ty),*) => {
+        $(unsafe impl ShmemPod for $t {})*
+    };
+}
+
+impl_shmem_pod!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+unsafe impl<T: ShmemPod, const N: usize> ShmemPod for [T; N] {}
@@ -0,0 +1,140 @@
+use ref_cast::RefCast;
+
+use crate::{
+    osm_box::OsmBox, osm_ffi::shmem_fence, osm_pod::ShmemPod, osm_scope::OsmScope, osm_vec::ShVec,
+    osm_wrapper::OsmWrapper,
+};
+
+/// Fixed-capacity MPMC ring buffer in symmetric memory.
+///
+/// Every PE hosts one ring, so the queue can live on a single PE (always pass
+/// the same `pe`) or be sharded by picking the target per call. Producers and
+/// consumers claim tickets on the host with remote fetch-add (or
+/// compare-and-swap for the `try_` variants). Ticket `t` maps to slot
+/// `t % capacity` in turn `t / capacity`; each slot has a sequence word that is
+/// `2 * turn` while empty and `2 * turn + 1` once the value has been put and
+/// published after a fence.
+///
+/// Construction is collective.
+pub struct DistQueue<'a, T> {
+    head: OsmBox<'a, i64>,
+    tail: OsmBox<'a, i64>,
+    sequences: ShVec<'a, i64>,
+    slots: ShVec<'a, T>,
+    capacity: usize,
+}
+
+impl<'a, T> DistQueue<'a, T>
+where
+    T: ShmemPod + Copy + Default,
+{
+    pub fn new(capacity: usize, scope: &'a OsmScope) -> Self {
+        assert!(capacity > 0, "DistQueue requires a non-zero capacity");
+
+        let head = OsmBox::new(0, scope);
+        let tail = OsmBox::new(0, scope);
+        let mut sequences = ShVec::with_capacity(capacity, scope);
+        sequences.resize_with(capacity, || 0);
+        let mut slots = ShVec::with_capacity(capacity, scope);
+        slots.resize_with(capacity, T::default);
+
+        // every ring has to be initialized before the first remote ticket
+        scope.barrier_all();
+
+        DistQueue {
+            head,
+            tail,
+            sequences,
+            slots,
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of elements in the ring on `pe`, including ones still in flight.
+    pub fn len(&self, pe: i32) -> usize {
+        let head = self.head.atomic_fetch(pe);
+        let tail = self.tail.atomic_fetch(pe);
+        (tail - head).clamp(0, self.capacity as i64) as usize
+    }
+
+    pub fn is_empty(&self, pe: i32) -> bool {
+        self.len(pe) == 0
+    }
+
+    /// Enqueue onto the ring on `pe`, waiting for space if it is full.
+    pub fn enqueue(&mut self, value: T, pe: i32) {
+        let ticket = self.tail.fetch_add(1, pe);
+        self.publish(ticket, value, pe);
+    }
+
+    /// Dequeue from the ring on `pe`, waiting until an element is published.
+    ///
+    /// The ticket is claimed unconditionally, so this blocks forever if no
+    /// producer ever enqueues; use [`DistQueue::try_dequeue`] to drain.
+    pub fn dequeue(&mut self, pe: i32) -> T {
+        let ticket = self.head.fetch_add(1, pe);
+        self.consume(ticket, pe)
+    }
+
+    pub fn try_enqueue(&mut self, value: T, pe: i32) -> Result<(), T> {
+        loop {
+            let tail = self.tail.atomic_fetch(pe);
+            let head = self.head.atomic_fetch(pe);
+            if tail - head >= self.capacity as i64 {
+                return Err(value);
+            }
+            if self.tail.compare_and_swap(tail, tail + 1, pe) == tail {
+                self.publish(tail, value, pe);
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn try_dequeue(&mut self, pe: i32) -> Option<T> {
+        loop {
+            let head = self.head.atomic_fetch(pe);
+            let tail = self.tail.atomic_fetch(pe);
+            if head >= tail {
+                return None;
+            }
+            if self.head.compare_and_swap(head, head + 1, pe) == head {
+                return Some(self.consume(head, pe));
+            }
+        }
+    }
+
+    fn publish(&mut self, ticket: i64, value: T, pe: i32) {
+        let (slot, turn) = self.slot_of(ticket);
+
+        self.wait_sequence(slot, 2 * turn, pe);
+        OsmWrapper::ref_cast(&value).put_to(&mut self.slots[slot], pe);
+        // the value must land before consumers can see the slot as full
+        unsafe { shmem_fence() };
+        self.sequences[slot].atomic_set(2 * turn + 1, pe);
+    }
+
+    fn consume(&mut self, ticket: i64, pe: i32) -> T {
+        let (slot, turn) = self.slot_of(ticket);
+
+        self.wait_sequence(slot, 2 * turn + 1, pe);
+        let value = self.slots[slot].get_value(pe);
+        self.sequences[slot].atomic_set(2 * (turn + 1), pe);
+
+        value
+    }
+
+    fn slot_of(&self, ticket: i64) -> (usize, i64) {
+        let capacity = self.capacity as i64;
+        ((ticket % capacity) as usize, ticket / capacity)
+    }
+
+    fn wait_sequence(&self, slot: usize, expected: i64, pe: i32) {
+        while self.sequences[slot].atomic_fetch(pe) != expected {
+            std::hint::spin_loop();
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Recording of the operations a PE issues, as traces `trace-execution` can
+//! replay.
+//!
+//! With the `record` feature the RMA, atomic, synchronization and collective
+//! calls of [`crate::osm_ffi`] resolve to [`ffi`], which logs every call made
+//! while the calling PE is recording. Each PE writes its own CSV file with the
+//! `op_type,src,dst,size` columns `trace-execution` reads, followed by
+//! `timestamp_ns`, the time since the recording started, and `call_site`.
+//! The public operations are `#[track_caller]`, so the call site is the
+//! application code that issued them rather than this crate.
+//!
+//! Record part of a run with [`Recording::start`], or set `OSM_RECORD` to a
+//! path prefix to record everything from [`OsmScope::init`] until the scope
+//! is dropped.
+//!
+//! `size` is in bytes. Point-to-point operations have the issuing PE as `src`
+//! and the target as `dst`; barriers, collectives and waits have `-1` for
+//! both. Collectives this crate builds from puts and fences, such as
+//! [`crate::osm_team::OsmTeam::all_reduce_with`], appear as those puts and
+//! fences.
+
+pub(crate) mod ffi;
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter},
+    marker::PhantomData,
+    panic::Location,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use serde::{Serialize, Serializer};
+
+use crate::osm_scope::OsmScope;
+
+/// Operation names, spelled as `trace-execution` parses them.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub(crate) enum Op {
+    Put,
+    PutNonBlocking,
+    Get,
+    GetNonBlocking,
+    Quiet,
+    Fence,
+    Barrier,
+    FetchAdd32,
+    FetchAdd64,
+    CompareAndSwap32,
+    CompareAndSwap64,
+    AtomicFetch,
+    AtomicSet,
+    WaitUntil,
+    Broadcast,
+    AllGather,
+    AllToAll,
+    AllReduce,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    op_type: Op,
+    src: i32,
+    dst: i32,
+    size: usize,
+    timestamp_ns: u64,
+    #[serde(serialize_with = "display")]
+    call_site: &'static Location<'static>,
+}
+
+fn display<S: Serializer>(value: &impl Display, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+struct Recorder {
+    writer: csv::Writer<BufWriter<File>>,
+    path: PathBuf,
+    pe: i32,
+    start: Instant,
+}
+
+#[cfg(not(feature = "sim"))]
+static RECORDER: std::sync::Mutex<Option<Recorder>> = std::sync::Mutex::new(None);
+
+// simulated PEs share the process, so each thread records to its own file
+#[cfg(feature = "sim")]
+thread_local! {
+    static RECORDER: std::cell::RefCell<Option<Recorder>> = const { std::cell::RefCell::new(None) };
+}
+
+fn with_recorder<R>(f: impl FnOnce(&mut Option<Recorder>) -> R) -> R {
+    #[cfg(feature = "sim")]
+    return RECORDER.with_borrow_mut(f);
+    #[cfg(not(feature = "sim"))]
+    f(&mut RECORDER
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner))
+}
+
+/// Records the operations this PE issues until it is dropped.
+///
+/// A PE records to one file at a time, so this fails while another
+/// recording, including one requested through `OSM_RECORD`, is active.
+pub struct Recording<'a> {
+    path: PathBuf,
+    _scope: PhantomData<&'a OsmScope>,
+}
+
+impl<'a> Recording<'a> {
+    /// Record to `{prefix}_pe_{n}.csv`, where `n` is this PE.
+    pub fn start(prefix: impl AsRef<Path>, scope: &'a OsmScope) -> io::Result<Self> {
+        let path = start(prefix.as_ref(), scope.my_pe())?;
+        Ok(Recording {
+            path,
+            _scope: PhantomData,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop recording and report whether the trace was written completely,
+    /// which dropping the recording cannot.
+    pub fn finish(self) -> io::Result<()> {
+        stop()
+    }
+}
+
+impl Drop for Recording<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = stop() {
+            eprintln!("failed to write {}: {err}", self.path.display());
+        }
+    }
+}
+
+fn start(prefix: &Path, pe: i32) -> io::Result<PathBuf> {
+    let mut name = prefix.as_os_str().to_owned();
+    name.push(format!("_pe_{pe}.csv"));
+    let path = PathBuf::from(name);
+
+    with_recorder(|recorder| {
+        if let Some(active) = recorder {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("PE {pe} is already recording to {}", active.path.display()),
+            ));
+        }
+        *recorder = Some(Recorder {
+            writer: csv::Writer::from_writer(BufWriter::new(File::create(&path)?)),
+            path: path.clone(),
+            pe,
+            start: Instant::now(),
+        });
+        Ok(path)
+    })
+}
+
+fn stop() -> io::Result<()> {
+    match with_recorder(Option::take) {
+        Some(mut recorder) => recorder.writer.flush(),
+        None => Ok(()),
+    }
+}
+
+/// Start the recording requested through `OSM_RECORD`, if any.
+pub(crate) fn start_from_env(pe: i32) {
+    if let Some(prefix) = std::env::var_os("OSM_RECORD") {
+        start(Path::new(&prefix), pe)
+            .unwrap_or_else(|err| panic!("OSM_RECORD: cannot record PE {pe}: {err}"));
+    }
+}
+
+/// Stop the recording started by [`start_from_env`], if it is still active.
+pub(crate) fn stop_from_env() {
+    if let Err(err) = stop() {
+        eprintln!("OSM_RECORD: failed to write the trace: {err}");
+    }
+}
+
+/// Log an operation issued by this PE that targets `pe`.
+#[track_caller]
+pub(crate) fn record_rma(op: Op, pe: i32, size: usize) {
+    log(op, Some(pe), size, Location::caller());
+}
+
+/// Log a barrier, collective or wait, which has no single target.
+#[track_caller]
+pub(crate) fn record_collective(op: Op, size: usize) {
+    log(op, None, size, Location::caller());
+}
+
+fn log(op: Op, pe: Option<i32>, size: usize, call_site: &'static Location<'static>) {
+    with_recorder(|recorder| {
+        let Some(recorder) = recorder else {
+            return;
+        };
+        let entry = Entry {
+            op_type: op,
+            src: pe.map_or(-1, |_| recorder.pe),
+            dst: pe.unwrap_or(-1),
+            size,
+            timestamp_ns: recorder.start.elapsed().as_nanos() as u64,
+            call_site,
+        };
+        if let Err(err) = recorder.writer.serialize(entry) {
+            panic!("failed to write {}: {err}", recorder.path.display());
+        }
+    });
+}
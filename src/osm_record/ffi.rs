@@ -0,0 +1,397 @@
+//! Recording versions of the operations in [`crate::osm_ffi`]. Each logs the
+//! call and forwards it to the runtime, or the simulator with `sim`.
+
+#![allow(non_snake_case, clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use std::ffi::{c_int, c_long, c_void};
+
+#[cfg(not(feature = "sim"))]
+use openshmem_sys as sys;
+
+#[cfg(feature = "sim")]
+use crate::osm_sim::ffi as sys;
+
+use super::{Op, record_collective, record_rma};
+use sys::{ptrdiff_t, shmem_ctx_t, shmem_team_t, size_t};
+
+const INT: usize = std::mem::size_of::<c_int>();
+const LONG: usize = std::mem::size_of::<c_long>();
+
+#[track_caller]
+pub unsafe fn shmem_putmem(dest: *mut c_void, source: *const c_void, nelems: size_t, pe: c_int) {
+    record_rma(Op::Put, pe, nelems);
+    unsafe { sys::shmem_putmem(dest, source, nelems, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_putmem_nbi(
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    record_rma(Op::PutNonBlocking, pe, nelems);
+    unsafe { sys::shmem_putmem_nbi(dest, source, nelems, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_getmem(dest: *mut c_void, source: *const c_void, nelems: size_t, pe: c_int) {
+    record_rma(Op::Get, pe, nelems);
+    unsafe { sys::shmem_getmem(dest, source, nelems, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_getmem_nbi(
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    record_rma(Op::GetNonBlocking, pe, nelems);
+    unsafe { sys::shmem_getmem_nbi(dest, source, nelems, pe) }
+}
+
+// strided, logged as one transfer of all the elements
+macro_rules! strided_rma {
+    ($($put:ident, $get:ident, $size:expr;)*) => {
+        $(
+            #[track_caller]
+            pub unsafe fn $put(
+                dest: *mut c_void,
+                source: *const c_void,
+                dst: ptrdiff_t,
+                sst: ptrdiff_t,
+                nelems: size_t,
+                pe: c_int,
+            ) {
+                record_rma(Op::Put, pe, nelems * $size);
+                unsafe { sys::$put(dest, source, dst, sst, nelems, pe) }
+            }
+
+            #[track_caller]
+            pub unsafe fn $get(
+                dest: *mut c_void,
+                source: *const c_void,
+                dst: ptrdiff_t,
+                sst: ptrdiff_t,
+                nelems: size_t,
+                pe: c_int,
+            ) {
+                record_rma(Op::Get, pe, nelems * $size);
+                unsafe { sys::$get(dest, source, dst, sst, nelems, pe) }
+            }
+        )*
+    };
+}
+
+strided_rma! {
+    shmem_iput8, shmem_iget8, 1;
+    shmem_iput16, shmem_iget16, 2;
+    shmem_iput32, shmem_iget32, 4;
+    shmem_iput64, shmem_iget64, 8;
+    shmem_iput128, shmem_iget128, 16;
+}
+
+#[track_caller]
+pub unsafe fn shmem_ctx_putmem(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    record_rma(Op::Put, pe, nelems);
+    unsafe { sys::shmem_ctx_putmem(ctx, dest, source, nelems, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_ctx_putmem_nbi(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    record_rma(Op::PutNonBlocking, pe, nelems);
+    unsafe { sys::shmem_ctx_putmem_nbi(ctx, dest, source, nelems, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_ctx_getmem(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    record_rma(Op::Get, pe, nelems);
+    unsafe { sys::shmem_ctx_getmem(ctx, dest, source, nelems, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_ctx_getmem_nbi(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    record_rma(Op::GetNonBlocking, pe, nelems);
+    unsafe { sys::shmem_ctx_getmem_nbi(ctx, dest, source, nelems, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_quiet() {
+    record_collective(Op::Quiet, 0);
+    unsafe { sys::shmem_quiet() }
+}
+
+#[track_caller]
+pub unsafe fn shmem_ctx_quiet(ctx: shmem_ctx_t) {
+    record_collective(Op::Quiet, 0);
+    unsafe { sys::shmem_ctx_quiet(ctx) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_fence() {
+    record_collective(Op::Fence, 0);
+    unsafe { sys::shmem_fence() }
+}
+
+#[track_caller]
+pub unsafe fn shmem_ctx_fence(ctx: shmem_ctx_t) {
+    record_collective(Op::Fence, 0);
+    unsafe { sys::shmem_ctx_fence(ctx) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_barrier_all() {
+    record_collective(Op::Barrier, 0);
+    unsafe { sys::shmem_barrier_all() }
+}
+
+#[track_caller]
+pub unsafe fn shmem_sync_all() {
+    record_collective(Op::Barrier, 0);
+    unsafe { sys::shmem_sync_all() }
+}
+
+#[track_caller]
+pub unsafe fn shmem_team_sync(team: shmem_team_t) -> c_int {
+    record_collective(Op::Barrier, 0);
+    unsafe { sys::shmem_team_sync(team) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_int_atomic_fetch_add(dest: *mut c_int, value: c_int, pe: c_int) -> c_int {
+    record_rma(Op::FetchAdd32, pe, INT);
+    unsafe { sys::shmem_int_atomic_fetch_add(dest, value, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_long_atomic_fetch_add(dest: *mut c_long, value: c_long, pe: c_int) -> c_long {
+    record_rma(Op::FetchAdd64, pe, LONG);
+    unsafe { sys::shmem_long_atomic_fetch_add(dest, value, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_int_cswap(dest: *mut c_int, cond: c_int, value: c_int, pe: c_int) -> c_int {
+    record_rma(Op::CompareAndSwap32, pe, INT);
+    unsafe { sys::shmem_int_cswap(dest, cond, value, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_long_cswap(
+    dest: *mut c_long,
+    cond: c_long,
+    value: c_long,
+    pe: c_int,
+) -> c_long {
+    record_rma(Op::CompareAndSwap64, pe, LONG);
+    unsafe { sys::shmem_long_cswap(dest, cond, value, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_long_atomic_compare_swap(
+    dest: *mut c_long,
+    cond: c_long,
+    value: c_long,
+    pe: c_int,
+) -> c_long {
+    record_rma(Op::CompareAndSwap64, pe, LONG);
+    unsafe { sys::shmem_long_atomic_compare_swap(dest, cond, value, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_long_atomic_fetch(source: *const c_long, pe: c_int) -> c_long {
+    record_rma(Op::AtomicFetch, pe, LONG);
+    unsafe { sys::shmem_long_atomic_fetch(source, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_long_atomic_set(dest: *mut c_long, value: c_long, pe: c_int) {
+    record_rma(Op::AtomicSet, pe, LONG);
+    unsafe { sys::shmem_long_atomic_set(dest, value, pe) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_long_wait_until(ivar: *mut c_long, cmp: c_int, value: c_long) {
+    record_collective(Op::WaitUntil, LONG);
+    unsafe { sys::shmem_long_wait_until(ivar, cmp, value) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_broadcastmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_root: c_int,
+) -> c_int {
+    record_collective(Op::Broadcast, nelems);
+    unsafe { sys::shmem_broadcastmem(team, dest, source, nelems, PE_root) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_fcollectmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+) -> c_int {
+    record_collective(Op::AllGather, nelems);
+    unsafe { sys::shmem_fcollectmem(team, dest, source, nelems) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_collectmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+) -> c_int {
+    record_collective(Op::AllGather, nelems);
+    unsafe { sys::shmem_collectmem(team, dest, source, nelems) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_alltoallmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+) -> c_int {
+    record_collective(Op::AllToAll, nelems);
+    unsafe { sys::shmem_alltoallmem(team, dest, source, nelems) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_int_sum_reduce(
+    team: shmem_team_t,
+    dest: *mut c_int,
+    source: *const c_int,
+    nreduce: size_t,
+) -> c_int {
+    record_collective(Op::AllReduce, nreduce * INT);
+    unsafe { sys::shmem_int_sum_reduce(team, dest, source, nreduce) }
+}
+
+#[track_caller]
+pub unsafe fn shmem_broadcast64(
+    target: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_root: c_int,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    pSync: *mut c_long,
+) {
+    record_collective(Op::Broadcast, nelems * 8);
+    unsafe {
+        sys::shmem_broadcast64(
+            target,
+            source,
+            nelems,
+            PE_root,
+            PE_start,
+            logPE_stride,
+            PE_size,
+            pSync,
+        )
+    }
+}
+
+#[track_caller]
+pub unsafe fn shmem_fcollect64(
+    target: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    pSync: *mut c_long,
+) {
+    record_collective(Op::AllGather, nelems * 8);
+    unsafe {
+        sys::shmem_fcollect64(
+            target,
+            source,
+            nelems,
+            PE_start,
+            logPE_stride,
+            PE_size,
+            pSync,
+        )
+    }
+}
+
+#[track_caller]
+pub unsafe fn shmem_alltoall64(
+    target: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    pSync: *mut c_long,
+) {
+    record_collective(Op::AllToAll, nelems * 8);
+    unsafe {
+        sys::shmem_alltoall64(
+            target,
+            source,
+            nelems,
+            PE_start,
+            logPE_stride,
+            PE_size,
+            pSync,
+        )
+    }
+}
+
+#[track_caller]
+pub unsafe fn shmem_int_sum_to_all(
+    target: *mut c_int,
+    source: *const c_int,
+    nreduce: c_int,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    pWrk: *mut c_int,
+    pSync: *mut c_long,
+) {
+    record_collective(Op::AllReduce, nreduce as usize * INT);
+    unsafe {
+        sys::shmem_int_sum_to_all(
+            target,
+            source,
+            nreduce,
+            PE_start,
+            logPE_stride,
+            PE_size,
+            pWrk,
+            pSync,
+        )
+    }
+}
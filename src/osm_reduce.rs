@@ -0,0 +1,361 @@
+use ref_cast::RefCast;
+
+use crate::{
+    osm_collective::{CollectiveScratch, as_bytes, as_bytes_mut},
+    osm_error::{ShmemError, check_size},
+    osm_ffi::shmem_fence,
+    osm_pod::ShmemPod,
+    osm_slice::OsmSlice,
+    osm_team::OsmTeam,
+    osm_wrapper::ShmemCmp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReduceOp {
+    Sum,
+    Prod,
+    Min,
+    Max,
+}
+
+/// Element types the builtin reduction operators apply to.
+pub trait Reducible: ShmemPod + Copy {
+    fn identity(op: ReduceOp) -> Self;
+    fn combine(op: ReduceOp, lhs: Self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_reducible_int {
+    ($($t:ty),*) => {
+        $(
+            impl Reducible for $t {
+                fn identity(op: ReduceOp) -> Self {
+                    match op {
+                        ReduceOp::Sum => 0,
+                        ReduceOp::Prod => 1,
+                        ReduceOp::Min => <$t>::MAX,
+                        ReduceOp::Max => <$t>::MIN,
+                    }
+                }
+
+                fn combine(op: ReduceOp, lhs: Self, rhs: Self) -> Self {
+                    match op {
+                        ReduceOp::Sum => lhs.wrapping_add(rhs),
+                        ReduceOp::Prod => lhs.wrapping_mul(rhs),
+                        ReduceOp::Min => lhs.min(rhs),
+                        ReduceOp::Max => lhs.max(rhs),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_reducible_float {
+    ($($t:ty),*) => {
+        $(
+            impl Reducible for $t {
+                fn identity(op: ReduceOp) -> Self {
+                    match op {
+                        ReduceOp::Sum => 0.0,
+                        ReduceOp::Prod => 1.0,
+                        ReduceOp::Min => <$t>::INFINITY,
+                        ReduceOp::Max => <$t>::NEG_INFINITY,
+                    }
+                }
+
+                fn combine(op: ReduceOp, lhs: Self, rhs: Self) -> Self {
+                    match op {
+                        ReduceOp::Sum => lhs + rhs,
+                        ReduceOp::Prod => lhs * rhs,
+                        ReduceOp::Min => lhs.min(rhs),
+                        ReduceOp::Max => lhs.max(rhs),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_reducible_int!(i32, i64, u32, u64, usize);
+impl_reducible_float!(f32, f64);
+
+/// Whether a user-defined reduction operator may be applied out of rank order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Commutativity {
+    /// Reduced with a ring reduce-scatter followed by a ring allgather.
+    Commutative,
+    /// Reduced in rank order with a recursive-doubling scan, then broadcast
+    /// from the last member.
+    NonCommutative,
+}
+
+impl OsmTeam {
+    /// Element-wise inclusive prefix reduction: member `r` gets `src[0] op .. op src[r]`.
+    ///
+    /// Needs `ceil(log2(n)) * size_of_val(src)` bytes of scratch staging.
+    #[track_caller]
+    pub fn inclusive_scan<T: Reducible>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        op: ReduceOp,
+        scratch: &mut CollectiveScratch,
+    ) {
+        self.try_inclusive_scan(src, dst, op, scratch)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    #[track_caller]
+    pub fn try_inclusive_scan<T: Reducible>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        op: ReduceOp,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<(), ShmemError> {
+        let needed = scan_staging(self.num_pes(), std::mem::size_of_val::<[T]>(src));
+        let args = check_reduce_args("inclusive_scan", src, dst, needed, scratch);
+        scratch.agree(self, "inclusive_scan", args)?;
+
+        let (total, _) = self.doubling_scan(src, |a, b| T::combine(op, *a, *b), scratch)?;
+        dst[..total.len()].copy_from_slice(&total);
+        Ok(())
+    }
+
+    /// Element-wise exclusive prefix reduction: member `r` gets `src[0] op .. op
+    /// src[r - 1]`, and member 0 gets the identity of `op`.
+    ///
+    /// Needs `ceil(log2(n)) * size_of_val(src)` bytes of scratch staging.
+    #[track_caller]
+    pub fn exclusive_scan<T: Reducible>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        op: ReduceOp,
+        scratch: &mut CollectiveScratch,
+    ) {
+        self.try_exclusive_scan(src, dst, op, scratch)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    #[track_caller]
+    pub fn try_exclusive_scan<T: Reducible>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        op: ReduceOp,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<(), ShmemError> {
+        let needed = scan_staging(self.num_pes(), std::mem::size_of_val::<[T]>(src));
+        let args = check_reduce_args("exclusive_scan", src, dst, needed, scratch);
+        scratch.agree(self, "exclusive_scan", args)?;
+
+        let (_, prefix) = self.doubling_scan(src, |a, b| T::combine(op, *a, *b), scratch)?;
+        let prefix = prefix.unwrap_or_else(|| vec![T::identity(op); src.len()]);
+        dst[..prefix.len()].copy_from_slice(&prefix);
+        Ok(())
+    }
+
+    /// Element-wise reduction of `src` over all members with a user-defined,
+    /// associative `op`, written to `dst` on every member.
+    ///
+    /// Needs `2 * (n - 1) * ceil(len / n)` elements of scratch staging for
+    /// commutative operators and `ceil(log2(n)) * size_of_val(src)` bytes for
+    /// non-commutative ones.
+    #[track_caller]
+    pub fn all_reduce_with<T: ShmemPod + Copy>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        op: impl Fn(&T, &T) -> T,
+        commutativity: Commutativity,
+        scratch: &mut CollectiveScratch,
+    ) {
+        self.try_all_reduce_with(src, dst, op, commutativity, scratch)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    #[track_caller]
+    pub fn try_all_reduce_with<T: ShmemPod + Copy>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        op: impl Fn(&T, &T) -> T,
+        commutativity: Commutativity,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<(), ShmemError> {
+        let bytes = std::mem::size_of_val::<[T]>(src);
+        let needed = match commutativity {
+            Commutativity::Commutative => ring_staging::<T>(self.num_pes(), src.len()),
+            // the result is broadcast from the staging area
+            Commutativity::NonCommutative => scan_staging(self.num_pes(), bytes).max(bytes),
+        };
+        let args = check_reduce_args("all_reduce_with", src, dst, needed, scratch);
+        scratch.agree(self, "all_reduce_with", args)?;
+
+        match commutativity {
+            Commutativity::Commutative => {
+                let result = self.ring_all_reduce(src, &op, scratch)?;
+                dst[..result.len()].copy_from_slice(&result);
+            }
+            Commutativity::NonCommutative => {
+                let (total, _) = self.doubling_scan(src, &op, scratch)?;
+                let last = self.num_pes() - 1;
+
+                if self.my_pe() == last {
+                    scratch.staging[..bytes].copy_from_slice(as_bytes(&total));
+                }
+                let dst = OsmSlice::ref_cast_mut(as_bytes_mut(&mut dst[..src.len()]));
+                self.try_broadcast(&scratch.staging[..bytes], dst, last)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[track_caller]
+    fn ring_all_reduce<T: ShmemPod + Copy>(
+        self,
+        values: &[T],
+        op: impl Fn(&T, &T) -> T,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<Vec<T>, ShmemError> {
+        let num_pes = self.num_pes() as usize;
+        let my_rank = self.my_pe() as usize;
+        let len = values.len();
+        let chunk = |c: usize| c * len / num_pes..(c + 1) * len / num_pes;
+        let slot_bytes = len.div_ceil(num_pes) * std::mem::size_of::<T>();
+        let steps = num_pes - 1;
+
+        *scratch.signals[0] = 0;
+        self.try_sync()?;
+
+        let next = self.translate_pe(((my_rank + 1) % num_pes) as i32, OsmTeam::world());
+        let mut acc = values.to_vec();
+
+        // step s ships one chunk to the next member through staging slot s and
+        // bumps its signal counter, so a counter of s + 1 means slot s arrived
+        let mut exchange = |step: usize, send: usize, recv: usize, acc: &mut Vec<T>| {
+            let slot = step * slot_bytes;
+            let outgoing = as_bytes(&acc[chunk(send)]);
+            OsmSlice::ref_cast(outgoing)
+                .put_to(&mut scratch.staging[slot..slot + outgoing.len()], next);
+            unsafe { shmem_fence() };
+            scratch.signals[0].fetch_add(1, next);
+
+            scratch.signals[0].wait_until(ShmemCmp::Ge, step as i64 + 1);
+            let mut received = acc[chunk(recv)].to_vec();
+            let incoming = as_bytes_mut(&mut received);
+            let incoming_len = incoming.len();
+            incoming.copy_from_slice(&scratch.staging[slot..slot + incoming_len]);
+            received
+        };
+
+        // reduce-scatter: afterwards chunk my_rank + 1 is complete
+        for step in 0..steps {
+            let send = (my_rank + num_pes - step) % num_pes;
+            let recv = (my_rank + 2 * num_pes - step - 1) % num_pes;
+            let received = exchange(step, send, recv, &mut acc);
+            for (a, b) in acc[chunk(recv)].iter_mut().zip(&received) {
+                *a = op(b, a);
+            }
+        }
+
+        // allgather the completed chunks around the ring
+        for step in 0..steps {
+            let send = (my_rank + 1 + num_pes - step) % num_pes;
+            let recv = (my_rank + num_pes - step) % num_pes;
+            let received = exchange(steps + step, send, recv, &mut acc);
+            acc[chunk(recv)].copy_from_slice(&received);
+        }
+
+        Ok(acc)
+    }
+
+    /// Hillis-Steele scan: in round `k` every member puts its running total to
+    /// the member `2^k` ranks ahead and folds in the one from `2^k` behind.
+    ///
+    /// Returns the inclusive result and, except on member 0, the exclusive one.
+    #[track_caller]
+    pub(crate) fn doubling_scan<T: ShmemPod + Copy>(
+        self,
+        values: &[T],
+        combine: impl Fn(&T, &T) -> T,
+        scratch: &mut CollectiveScratch,
+    ) -> Result<(Vec<T>, Option<Vec<T>>), ShmemError> {
+        let num_pes = self.num_pes() as usize;
+        let my_rank = self.my_pe() as usize;
+        let bytes = std::mem::size_of_val(values);
+        let rounds = scan_rounds(self.num_pes());
+
+        // every signal aimed at us in the previous call has been consumed, so
+        // they can be reset before anyone is allowed to send again
+        for round in 0..rounds {
+            *scratch.signals[round] = 0;
+        }
+        self.try_sync()?;
+
+        let zip = |lhs: &[T], rhs: &[T]| -> Vec<T> {
+            lhs.iter().zip(rhs).map(|(a, b)| combine(a, b)).collect()
+        };
+
+        let mut total = values.to_vec();
+        let mut prefix: Option<Vec<T>> = None;
+        for round in 0..rounds {
+            let distance = 1 << round;
+            let slot = round * bytes..(round + 1) * bytes;
+
+            if my_rank + distance < num_pes {
+                let pe = self.translate_pe((my_rank + distance) as i32, OsmTeam::world());
+                OsmSlice::ref_cast(as_bytes(&total)).put_to(&mut scratch.staging[slot.clone()], pe);
+                // the partial result must land before its signal
+                unsafe { shmem_fence() };
+                scratch.signals[round].atomic_set(1, pe);
+            }
+
+            if my_rank >= distance {
+                scratch.signals[round].wait_until(ShmemCmp::Eq, 1);
+                let mut received = values.to_vec();
+                as_bytes_mut(&mut received).copy_from_slice(&scratch.staging[slot]);
+
+                prefix = Some(match prefix {
+                    Some(prefix) => zip(&received, &prefix),
+                    None => received.clone(),
+                });
+                total = zip(&received, &total);
+            }
+        }
+
+        Ok((total, prefix))
+    }
+}
+
+fn scan_rounds(num_pes: i32) -> usize {
+    (u32::BITS - (num_pes as u32 - 1).leading_zeros()) as usize
+}
+
+/// Staging bytes [`OsmTeam::doubling_scan`] needs for `bytes` per member.
+fn scan_staging(num_pes: i32, bytes: usize) -> usize {
+    scan_rounds(num_pes).saturating_mul(bytes)
+}
+
+/// Staging bytes the ring all-reduce needs for `len` elements per member.
+fn ring_staging<T>(num_pes: i32, len: usize) -> usize {
+    let slot_bytes = len.div_ceil(num_pes as usize) * std::mem::size_of::<T>();
+    2 * (num_pes as usize - 1) * slot_bytes
+}
+
+/// `dst` holds the result and the scratch has `staging` bytes to work in.
+fn check_reduce_args<T>(
+    operation: &'static str,
+    src: &OsmSlice<T>,
+    dst: &OsmSlice<T>,
+    staging: usize,
+    scratch: &CollectiveScratch,
+) -> Result<(), ShmemError> {
+    check_size(
+        operation,
+        std::mem::size_of_val::<[T]>(src),
+        std::mem::size_of_val::<[T]>(dst),
+    )?;
+    check_size(operation, staging, scratch.staging.len())
+}
@@ -1,25 +1,110 @@
-use openshmem_sys::*;
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe, UnwindSafe},
+    sync::Once,
+};
 
+use serde::Serialize;
+
+use crate::{osm_ffi::*, osm_future::complete_epoch, osm_team::OsmTeam, osm_vec::ShVec};
+
+const HOSTNAME_LEN: usize = 256;
+
+/// An initialized SHMEM runtime on this PE.
+///
+/// The first scope installs a panic hook that, after the previous hook has
+/// reported the panic, logs the PE and calls `shmem_global_exit(1)`: a panic
+/// on any thread of the PE, including worker threads, ends the whole job
+/// instead of leaving the other PEs blocked in the next collective. A thread
+/// that expects to recover from a panic opts out with [`catch_panic`]. With
+/// the `sim` feature only the simulated PE threads themselves are covered.
 pub struct OsmScope;
 
+static PANIC_HOOK: Once = Once::new();
+
+thread_local! {
+    /// Number of [`catch_panic`] calls active on this thread.
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
 impl OsmScope {
     pub fn init() -> Self {
         unsafe { shmem_init() };
+        install_panic_hook();
+        #[cfg(feature = "record")]
+        crate::osm_record::start_from_env(unsafe { shmem_my_pe() });
         OsmScope
     }
 }
 
 impl Drop for OsmScope {
     fn drop(&mut self) {
+        // finalize is collective; the other PEs would never join it
+        if std::thread::panicking() {
+            abort_job();
+        }
+
+        #[cfg(feature = "record")]
+        crate::osm_record::stop_from_env();
+
         println!("Finalizing OpenSHMEM for pe {}", self.my_pe());
 
         unsafe { shmem_finalize() };
     }
 }
 
+/// Run `f` with a new scope and terminate all PEs if it panics, once the
+/// panic has unwound and dropped everything `f` owned.
 pub fn shmem_scope(f: impl FnOnce(OsmScope)) {
     let scope = OsmScope::init();
-    f(scope);
+    if catch_panic(AssertUnwindSafe(|| f(scope))).is_err() {
+        abort_job();
+    }
+}
+
+/// [`std::panic::catch_unwind`] that keeps the panic hook of [`OsmScope`]
+/// from terminating the job, for threads that recover from their panics.
+pub fn catch_panic<R>(f: impl FnOnce() -> R + UnwindSafe) -> std::thread::Result<R> {
+    CATCHING.set(CATCHING.get() + 1);
+    let result = panic::catch_unwind(f);
+    CATCHING.set(CATCHING.get() - 1);
+    result
+}
+
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous(info);
+            let caught = CATCHING.try_with(Cell::get).unwrap_or(0) > 0;
+            if !caught && panics_end_job() {
+                abort_job();
+            }
+        }));
+    });
+}
+
+/// Whether the panicking thread belongs to a PE. Outside the simulator every
+/// thread of the process does.
+fn panics_end_job() -> bool {
+    #[cfg(feature = "sim")]
+    return crate::osm_sim::on_pe_thread();
+    #[cfg(not(feature = "sim"))]
+    true
+}
+
+/// Tear down the whole job after a panic, instead of leaving the other PEs
+/// blocked in the next collective.
+fn abort_job() -> ! {
+    eprintln!("PE {} panicked, terminating all PEs", unsafe {
+        shmem_my_pe()
+    });
+    global_exit(1)
+}
+
+fn global_exit(code: i32) -> ! {
+    unsafe { shmem_global_exit(code) };
+    std::process::exit(code)
 }
 
 impl OsmScope {
@@ -27,19 +112,146 @@ impl OsmScope {
         unsafe { shmem_my_pe() }
     }
 
+    #[track_caller]
     pub fn barrier_all(&self) {
         unsafe { shmem_barrier_all() };
+        complete_epoch();
+    }
+
+    /// Synchronize all PEs without completing outstanding RMA.
+    #[track_caller]
+    pub fn sync_all(&self) {
+        unsafe { shmem_sync_all() };
     }
 
     pub fn num_pes(&self) -> i32 {
         unsafe { shmem_n_pes() }
     }
 
+    #[track_caller]
     pub fn quiet(&self) {
-        unsafe { shmem_quiet() }
+        unsafe { shmem_quiet() };
+        complete_epoch();
     }
 
+    #[track_caller]
     pub fn fence(&self) {
         unsafe { shmem_fence() }
     }
+
+    /// Terminate every PE in the job with exit status `code`.
+    pub fn global_exit(&self, code: i32) -> ! {
+        global_exit(code)
+    }
+}
+
+/// Description of the SHMEM runtime and where the PEs were placed.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeInfo {
+    pub library_name: String,
+    pub library_version: (i32, i32),
+    pub my_pe: i32,
+    pub num_pes: i32,
+    pub node_rank: i32,
+    pub node_size: i32,
+    /// Hostname of every PE, indexed by PE number.
+    pub hostnames: Vec<String>,
+    pub symmetric_heap_size: Option<usize>,
+}
+
+impl OsmScope {
+    pub fn library_version(&self) -> (i32, i32) {
+        let (mut major, mut minor) = (0, 0);
+        unsafe { shmem_info_get_version(&mut major, &mut minor) };
+        (major, minor)
+    }
+
+    pub fn library_name(&self) -> String {
+        let mut name = [0u8; SHMEM_MAX_NAME_LEN as usize];
+        unsafe { shmem_info_get_name(name.as_mut_ptr().cast()) };
+        c_buffer_to_string(&name)
+    }
+
+    /// Rank of this PE among the PEs sharing its node.
+    pub fn node_rank(&self) -> i32 {
+        OsmTeam::shared().my_pe()
+    }
+
+    /// Number of PEs sharing this PE's node.
+    pub fn node_size(&self) -> i32 {
+        OsmTeam::shared().num_pes()
+    }
+
+    pub fn hostname(&self) -> String {
+        let mut name = [0u8; HOSTNAME_LEN];
+        unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) };
+        c_buffer_to_string(&name)
+    }
+
+    /// Collectively gather the hostname of every PE, indexed by PE number.
+    pub fn hostnames(&self) -> Vec<String> {
+        let num_pes = self.num_pes() as usize;
+
+        let mut local = ShVec::with_capacity(HOSTNAME_LEN, self);
+        local.resize_with(HOSTNAME_LEN, || 0u8);
+        let hostname = self.hostname();
+        let len = hostname.len().min(HOSTNAME_LEN - 1);
+        local[..len].copy_from_slice(&hostname.as_bytes()[..len]);
+
+        let mut all = ShVec::with_capacity(HOSTNAME_LEN * num_pes, self);
+        all.resize_with(HOSTNAME_LEN * num_pes, || 0u8);
+
+        let mut p_sync = ShVec::with_capacity(SHMEM_COLLECT_SYNC_SIZE as usize, self);
+        p_sync.resize_with(SHMEM_COLLECT_SYNC_SIZE as usize, || {
+            _SHMEM_SYNC_VALUE as i64
+        });
+        // p_sync has to be initialized on every PE before the collective
+        self.barrier_all();
+
+        local.all_gather(&mut all, self, &mut p_sync);
+
+        all.chunks(HOSTNAME_LEN).map(c_buffer_to_string).collect()
+    }
+
+    /// Symmetric heap size requested through `SHMEM_SYMMETRIC_SIZE` (or the
+    /// legacy `SMA_SYMMETRIC_SIZE`), if set.
+    pub fn symmetric_heap_size(&self) -> Option<usize> {
+        ["SHMEM_SYMMETRIC_SIZE", "SMA_SYMMETRIC_SIZE"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok())
+            .and_then(|value| parse_size(&value))
+    }
+
+    /// Collect all runtime and placement information. Collective.
+    pub fn runtime_info(&self) -> RuntimeInfo {
+        RuntimeInfo {
+            library_name: self.library_name(),
+            library_version: self.library_version(),
+            my_pe: self.my_pe(),
+            num_pes: self.num_pes(),
+            node_rank: self.node_rank(),
+            node_size: self.node_size(),
+            hostnames: self.hostnames(),
+            symmetric_heap_size: self.symmetric_heap_size(),
+        }
+    }
+}
+
+fn c_buffer_to_string(buffer: &[u8]) -> String {
+    let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..len]).into_owned()
+}
+
+/// Parse sizes such as `1024`, `512K`, `64M` or `2G` (binary multiples).
+fn parse_size(value: &str) -> Option<usize> {
+    let value = value.trim();
+    let value = value.strip_suffix(['b', 'B']).unwrap_or(value);
+    let (digits, shift) = match value.chars().last()? {
+        'k' | 'K' => (&value[..value.len() - 1], 10),
+        'm' | 'M' => (&value[..value.len() - 1], 20),
+        'g' | 'G' => (&value[..value.len() - 1], 30),
+        't' | 'T' => (&value[..value.len() - 1], 40),
+        _ => (value, 0),
+    };
+    digits.trim().parse::<usize>().ok()?.checked_mul(1 << shift)
 }
@@ -0,0 +1,262 @@
+//! Threads-as-PEs stand-in for the OpenSHMEM runtime.
+//!
+//! With the `sim` feature [`crate::osm_ffi`] resolves to [`ffi`] instead of
+//! `openshmem-sys`, so the whole crate runs in one process: [`run`] starts a
+//! thread per PE, each with its own symmetric heap. Every PE allocates the
+//! same sequence of blocks from its heap, which keeps offsets identical, and
+//! remote addresses are translated by offset. Anything outside the heaps, such
+//! as a [`crate::symmetric_static!`], exists once and is shared by all PEs, so
+//! a put to a symmetric static writes the copy every PE sees.
+//!
+//! Setting [`SimConfig::check_races`] records every RMA, atomic and
+//! synchronization call, see [`Race`]. [`SimConfig::faults`] delays and
+//! reorders non-blocking RMA and stalls PEs at barriers, see [`FaultConfig`].
+
+mod fault;
+pub mod ffi;
+mod heap;
+mod race;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, atomic::AtomicUsize},
+};
+
+pub use fault::FaultConfig;
+pub use race::{Race, RaceAccess};
+
+use crate::osm_scope::OsmScope;
+use fault::Faults;
+use heap::Heap;
+use race::RaceChecker;
+
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub num_pes: usize,
+    /// Symmetric heap size of each PE in bytes. Pages are only committed once
+    /// touched, so this can be generous.
+    pub heap_size: usize,
+    /// Track accesses and report the ones not ordered by synchronization.
+    pub check_races: bool,
+    pub faults: FaultConfig,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            num_pes: 4,
+            heap_size: 1 << 30,
+            check_races: false,
+            faults: FaultConfig::default(),
+        }
+    }
+}
+
+/// What the checkers found over a whole [`run`].
+#[derive(Debug, Clone, Default)]
+pub struct SimReport {
+    /// Distinct races, in the order they were detected.
+    pub races: Vec<Race>,
+}
+
+/// Address ranges of the symmetric statics, which live outside the heaps and
+/// are shared by every PE.
+static STATICS: Mutex<Vec<std::ops::Range<usize>>> = Mutex::new(Vec::new());
+
+/// Let [`ffi::shmem_addr_accessible`] accept the `size` bytes at `addr`, as the
+/// runtime does for global and static data.
+pub(crate) fn register_static(addr: *const u8, size: usize) {
+    let start = addr as usize;
+    let mut statics = STATICS.lock().unwrap_or_else(PoisonError::into_inner);
+    if !statics.iter().any(|range| range.start == start) {
+        statics.push(start..start + size);
+    }
+}
+
+fn is_static(addr: *const u8) -> bool {
+    let statics = STATICS.lock().unwrap_or_else(PoisonError::into_inner);
+    statics.iter().any(|range| range.contains(&(addr as usize)))
+}
+
+/// Run `f` on `config.num_pes` threads, each acting as one PE.
+///
+/// Simulations share process-wide state, so concurrent calls run one after
+/// the other. A panic on any PE terminates the process, as it would
+/// terminate the job on the real runtime.
+pub fn run(config: SimConfig, f: impl Fn(OsmScope) + Sync) -> SimReport {
+    static RUNNING: Mutex<()> = Mutex::new(());
+    let _running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let job = Arc::new(Job::new(&config));
+    std::thread::scope(|threads| {
+        for pe in 0..config.num_pes {
+            let job = job.clone();
+            let f = &f;
+            std::thread::Builder::new()
+                .name(format!("pe-{pe}"))
+                .spawn_scoped(threads, move || {
+                    CURRENT.set(Some(Pe { pe, job }));
+                    f(OsmScope::init());
+                    CURRENT.set(None);
+                })
+                .expect("failed to spawn PE thread");
+        }
+    });
+
+    SimReport {
+        races: job
+            .checker
+            .as_ref()
+            .map(RaceChecker::races)
+            .unwrap_or_default(),
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Pe>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone)]
+struct Pe {
+    pe: usize,
+    job: Arc<Job>,
+}
+
+/// Whether the calling thread is one of the simulated PEs started by [`run`].
+pub(crate) fn on_pe_thread() -> bool {
+    CURRENT
+        .try_with(|current| current.borrow().is_some())
+        .unwrap_or(false)
+}
+
+fn current() -> Pe {
+    CURRENT.with_borrow(|current| {
+        current
+            .clone()
+            .expect("SHMEM call outside of a simulated PE, start it with osm_sim::run")
+    })
+}
+
+/// PEs `start, start + stride, ..` of a team or active set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TeamShape {
+    start: usize,
+    stride: usize,
+    size: usize,
+}
+
+impl TeamShape {
+    fn pe(self, rank: usize) -> usize {
+        self.start + rank * self.stride
+    }
+
+    fn rank(self, pe: usize) -> Option<usize> {
+        let offset = pe.checked_sub(self.start)?;
+        let rank = offset / self.stride;
+        (offset % self.stride == 0 && rank < self.size).then_some(rank)
+    }
+
+    fn members(self) -> impl Iterator<Item = usize> {
+        (0..self.size).map(move |rank| self.pe(rank))
+    }
+}
+
+#[derive(Default)]
+struct Rendezvous {
+    arrived: usize,
+    generation: u64,
+    clock: Vec<u64>,
+    released: Vec<u64>,
+}
+
+struct Job {
+    num_pes: usize,
+    heaps: Vec<Heap>,
+    rendezvous: Mutex<HashMap<TeamShape, Rendezvous>>,
+    released: Condvar,
+    /// Contribution size of each PE to the running `collectmem`.
+    collect_sizes: Vec<AtomicUsize>,
+    checker: Option<RaceChecker>,
+    faults: Faults,
+}
+
+impl Job {
+    fn new(config: &SimConfig) -> Self {
+        Job {
+            num_pes: config.num_pes,
+            heaps: (0..config.num_pes)
+                .map(|_| Heap::new(config.heap_size))
+                .collect(),
+            rendezvous: Mutex::new(HashMap::new()),
+            released: Condvar::new(),
+            collect_sizes: (0..config.num_pes).map(|_| AtomicUsize::new(0)).collect(),
+            checker: config.check_races.then(|| RaceChecker::new(config.num_pes)),
+            faults: Faults::new(&config.faults, config.num_pes),
+        }
+    }
+
+    fn world(&self) -> TeamShape {
+        TeamShape {
+            start: 0,
+            stride: 1,
+            size: self.num_pes,
+        }
+    }
+
+    /// Offset of `addr` in the heap of `pe`, if it points into it.
+    fn heap_offset(&self, pe: usize, addr: *const u8) -> Option<usize> {
+        self.heaps[pe].offset(addr)
+    }
+
+    /// Address on `pe` of the symmetric object at `addr` on `me`.
+    fn translate(&self, me: usize, addr: *const u8, pe: usize) -> *mut u8 {
+        assert!(
+            pe < self.num_pes,
+            "PE {pe} is out of range for {} PEs",
+            self.num_pes
+        );
+        match self.heap_offset(me, addr) {
+            Some(offset) => self.heaps[pe].at(offset),
+            None => addr as *mut u8,
+        }
+    }
+
+    /// Block until every member of `team` has arrived. With race checking
+    /// the members leave with the join of their clocks.
+    fn rendezvous(&self, me: usize, team: TeamShape) {
+        let clock = self.checker.as_ref().map(|checker| checker.clock(me));
+
+        let mut all = lock(&self.rendezvous);
+        let state = all.entry(team).or_default();
+        let generation = state.generation;
+        if let Some(clock) = clock {
+            race::join(&mut state.clock, &clock);
+        }
+        state.arrived += 1;
+
+        if state.arrived == team.size {
+            state.arrived = 0;
+            state.generation += 1;
+            state.released = std::mem::take(&mut state.clock);
+            self.released.notify_all();
+        } else {
+            while all[&team].generation == generation {
+                all = self
+                    .released
+                    .wait(all)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        }
+
+        // a later generation cannot complete before we arrive at it, so the
+        // released clock is still ours
+        if let Some(checker) = &self.checker {
+            checker.acquire_clock(me, &all[&team].released);
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
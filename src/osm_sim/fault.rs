@@ -0,0 +1,156 @@
+use std::{sync::Mutex, time::Duration};
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use super::lock;
+
+/// Perturbations that stay within what the memory model allows, to expose
+/// code that depends on a particular timing. All off by default.
+#[derive(Debug, Clone)]
+pub struct FaultConfig {
+    /// Seed of the per-PE random generators.
+    pub seed: u64,
+    /// Queue non-blocking RMA instead of performing it. Queued operations are
+    /// delivered in random batches and random order on later calls of the
+    /// issuing PE, and at the latest by the fence or quiet that requires it.
+    pub delay_nbi: bool,
+    /// Chance that a SHMEM call delivers a batch of queued operations.
+    pub delivery_probability: f64,
+    /// PEs that may sleep before entering a barrier.
+    pub stall_pes: Vec<i32>,
+    /// Chance that a stalling PE sleeps at a given barrier.
+    pub stall_probability: f64,
+    pub max_stall: Duration,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        FaultConfig {
+            seed: 0,
+            delay_nbi: false,
+            delivery_probability: 0.1,
+            stall_pes: Vec::new(),
+            stall_probability: 0.5,
+            max_stall: Duration::from_millis(10),
+        }
+    }
+}
+
+/// A non-blocking transfer that has been issued but not carried out. The
+/// source is only read on delivery, as the caller may not reuse it before
+/// the operation completes.
+struct Pending {
+    ctx: usize,
+    is_put: bool,
+    dest: *mut u8,
+    source: *const u8,
+    len: usize,
+}
+
+// only touched by the issuing PE, under its lock
+unsafe impl Send for Pending {}
+
+impl Pending {
+    fn deliver(&self) {
+        unsafe { std::ptr::copy(self.source, self.dest, self.len) };
+    }
+}
+
+struct PeFaults {
+    rng: StdRng,
+    queue: Vec<Pending>,
+}
+
+pub(super) struct Faults {
+    config: FaultConfig,
+    pes: Vec<Mutex<PeFaults>>,
+}
+
+impl Faults {
+    pub(super) fn new(config: &FaultConfig, num_pes: usize) -> Self {
+        let pes = (0..num_pes)
+            .map(|pe| {
+                Mutex::new(PeFaults {
+                    rng: StdRng::seed_from_u64(config.seed.wrapping_add(pe as u64)),
+                    queue: Vec::new(),
+                })
+            })
+            .collect();
+
+        Faults {
+            config: config.clone(),
+            pes,
+        }
+    }
+
+    /// Queue a transfer of `len` bytes from `source` to `dest` if non-blocking
+    /// RMA is delayed. Returns false if the caller has to perform it now.
+    pub(super) fn defer(
+        &self,
+        pe: usize,
+        ctx: usize,
+        is_put: bool,
+        dest: *mut u8,
+        source: *const u8,
+        len: usize,
+    ) -> bool {
+        if !self.config.delay_nbi {
+            return false;
+        }
+        lock(&self.pes[pe]).queue.push(Pending {
+            ctx,
+            is_put,
+            dest,
+            source,
+            len,
+        });
+        true
+    }
+
+    /// Maybe deliver a random batch of the operations queued by `pe`.
+    pub(super) fn progress(&self, pe: usize) {
+        let mut state = lock(&self.pes[pe]);
+        let state = &mut *state;
+        if state.queue.is_empty() || !state.rng.random_bool(self.config.delivery_probability) {
+            return;
+        }
+
+        state.queue.shuffle(&mut state.rng);
+        let batch = state.rng.random_range(1..=state.queue.len());
+        for pending in state.queue.drain(..batch) {
+            pending.deliver();
+        }
+    }
+
+    /// Deliver everything `pe` queued on `ctx`, only the puts for a fence,
+    /// in random order.
+    pub(super) fn flush(&self, pe: usize, ctx: usize, puts_only: bool) {
+        let mut state = lock(&self.pes[pe]);
+        let state = &mut *state;
+        let (mut due, kept) = std::mem::take(&mut state.queue)
+            .into_iter()
+            .partition::<Vec<_>, _>(|pending| pending.ctx == ctx && (pending.is_put || !puts_only));
+        state.queue = kept;
+
+        due.shuffle(&mut state.rng);
+        for pending in due {
+            pending.deliver();
+        }
+    }
+
+    /// Sleep for a while before a barrier if `pe` is chosen to stall.
+    pub(super) fn stall(&self, pe: usize) {
+        if !self.config.stall_pes.contains(&(pe as i32)) {
+            return;
+        }
+        let sleep = {
+            let mut state = lock(&self.pes[pe]);
+            if !state.rng.random_bool(self.config.stall_probability) {
+                return;
+            }
+            let max = self.config.max_stall.as_micros() as u64;
+            Duration::from_micros(state.rng.random_range(0..=max))
+        };
+        std::thread::sleep(sleep);
+    }
+}
@@ -0,0 +1,863 @@
+//! The subset of the `openshmem-sys` API the crate uses, implemented on top of
+//! the simulated job. Names, types and constants follow the bindings.
+
+#![allow(
+    non_camel_case_types,
+    non_snake_case,
+    non_upper_case_globals,
+    clippy::missing_safety_doc
+)]
+
+use std::{
+    ffi::{c_char, c_int, c_long, c_void},
+    sync::atomic::{AtomicI32, AtomicI64, AtomicUsize, Ordering},
+};
+
+use super::{Job, Pe, TeamShape, current, is_static, race::Kind};
+
+pub type size_t = usize;
+pub type ptrdiff_t = isize;
+
+pub const _SHMEM_SYNC_VALUE: i32 = -1;
+pub const SHMEM_BARRIER_SYNC_SIZE: u32 = 4;
+pub const SHMEM_COLLECT_SYNC_SIZE: u32 = 4;
+pub const _SHMEM_REDUCE_SYNC_SIZE: u32 = 4;
+pub const _SHMEM_REDUCE_MIN_WRKDATA_SIZE: u32 = 8;
+pub const SHMEM_MAX_NAME_LEN: u32 = 256;
+pub const SHMEM_CMP_EQ: u32 = 0;
+pub const SHMEM_CMP_NE: u32 = 1;
+pub const SHMEM_CMP_GT: u32 = 2;
+pub const SHMEM_CMP_LE: u32 = 3;
+pub const SHMEM_CMP_LT: u32 = 4;
+pub const SHMEM_CMP_GE: u32 = 5;
+pub const SHMEM_CTX_PRIVATE: u32 = 1;
+pub const SHMEM_CTX_SERIALIZED: u32 = 2;
+pub const SHMEM_CTX_NOSTORE: u32 = 4;
+
+const LIBRARY_NAME: &str = "openshmem-benchmark simulator";
+
+/// A team as the PEs `start, start + stride, ..`; a size of 0 means every PE.
+#[derive(Debug)]
+pub struct oshmem_team {
+    start: usize,
+    stride: usize,
+    size: usize,
+}
+
+pub type shmem_team_t = *mut oshmem_team;
+pub type shmem_ctx_t = *mut c_void;
+
+static WORLD: oshmem_team = oshmem_team {
+    start: 0,
+    stride: 1,
+    size: 0,
+};
+
+pub static mut oshmem_team_world: shmem_team_t = &raw const WORLD as shmem_team_t;
+/// All simulated PEs share the node.
+pub static mut oshmem_team_shared: shmem_team_t = &raw const WORLD as shmem_team_t;
+
+fn team_shape(job: &Job, team: shmem_team_t) -> Option<TeamShape> {
+    let team = unsafe { team.as_ref()? };
+    Some(match team.size {
+        0 => job.world(),
+        size => TeamShape {
+            start: team.start,
+            stride: team.stride,
+            size,
+        },
+    })
+}
+
+fn active_set(start: c_int, log_stride: c_int, size: c_int) -> TeamShape {
+    TeamShape {
+        start: start as usize,
+        stride: 1 << log_stride,
+        size: size as usize,
+    }
+}
+
+fn ctx_id(ctx: shmem_ctx_t) -> usize {
+    ctx as usize
+}
+
+// setup and queries
+
+pub unsafe fn shmem_init() {
+    current();
+}
+
+pub unsafe fn shmem_finalize() {
+    unsafe { shmem_barrier_all() };
+}
+
+pub unsafe fn shmem_global_exit(status: c_int) {
+    std::process::exit(status);
+}
+
+pub unsafe fn shmem_my_pe() -> c_int {
+    current().pe as c_int
+}
+
+pub unsafe fn shmem_n_pes() -> c_int {
+    current().job.num_pes as c_int
+}
+
+pub unsafe fn num_pes() -> c_int {
+    unsafe { shmem_n_pes() }
+}
+
+pub unsafe fn shmem_info_get_version(major: *mut c_int, minor: *mut c_int) {
+    unsafe {
+        *major = 1;
+        *minor = 5;
+    }
+}
+
+pub unsafe fn shmem_info_get_name(name: *mut c_char) {
+    unsafe {
+        std::ptr::copy_nonoverlapping(LIBRARY_NAME.as_ptr().cast(), name, LIBRARY_NAME.len());
+        *name.add(LIBRARY_NAME.len()) = 0;
+    }
+}
+
+/// Accessible are the calling PE's heap and registered symmetric statics, the
+/// simulated counterparts of the heap and the data sections.
+pub unsafe fn shmem_addr_accessible(addr: *const c_void, pe: c_int) -> c_int {
+    let Pe { pe: me, job } = current();
+    let symmetric = job.heap_offset(me, addr.cast()).is_some() || is_static(addr.cast());
+    ((0..job.num_pes as c_int).contains(&pe) && symmetric) as c_int
+}
+
+// symmetric heap, collective like the real allocator
+
+pub unsafe fn shmemalign(align: size_t, size: size_t) -> *mut c_void {
+    let Pe { pe, job } = current();
+    job.rendezvous(pe, job.world());
+    job.heaps[pe].alloc(align, size).cast()
+}
+
+pub unsafe fn shfree(ptr: *mut c_void) {
+    let Pe { pe, job } = current();
+    job.rendezvous(pe, job.world());
+    job.heaps[pe].free(ptr.cast());
+}
+
+pub unsafe fn shrealloc(ptr: *mut c_void, size: size_t) -> *mut c_void {
+    let Pe { pe, job } = current();
+    job.rendezvous(pe, job.world());
+    job.heaps[pe].realloc(ptr.cast(), size).cast()
+}
+
+// ordering and synchronization
+
+fn complete(ctx: usize, writes_only: bool) {
+    let Pe { pe, job } = current();
+    job.faults.flush(pe, ctx, writes_only);
+    std::sync::atomic::fence(Ordering::SeqCst);
+    if let Some(checker) = &job.checker {
+        checker.complete(pe, ctx, writes_only);
+    }
+}
+
+pub unsafe fn shmem_quiet() {
+    complete(0, false);
+}
+
+pub unsafe fn shmem_fence() {
+    complete(0, true);
+}
+
+pub unsafe fn shmem_barrier_all() {
+    complete(0, false);
+    let Pe { pe, job } = current();
+    job.faults.stall(pe);
+    job.rendezvous(pe, job.world());
+}
+
+pub unsafe fn shmem_sync_all() {
+    let Pe { pe, job } = current();
+    job.faults.stall(pe);
+    job.rendezvous(pe, job.world());
+}
+
+pub unsafe fn shmem_team_sync(team: shmem_team_t) -> c_int {
+    let Pe { pe, job } = current();
+    let Some(shape) = team_shape(&job, team) else {
+        return -1;
+    };
+    job.faults.stall(pe);
+    job.rendezvous(pe, shape);
+    0
+}
+
+// RMA
+
+fn put(
+    operation: &'static str,
+    ctx: usize,
+    dest: *mut c_void,
+    source: *const c_void,
+    len: usize,
+    pe: c_int,
+    nbi: bool,
+) {
+    let Pe { pe: me, job } = current();
+    job.faults.progress(me);
+    let target = job.translate(me, dest.cast(), pe as usize);
+    if !(nbi && job.faults.defer(me, ctx, true, target, source.cast(), len)) {
+        unsafe { std::ptr::copy(source.cast::<u8>(), target, len) };
+    }
+
+    // even a blocking put is only delivered by the next fence or quiet
+    if let (Some(checker), Some(offset)) = (&job.checker, job.heap_offset(me, dest.cast())) {
+        checker.access(
+            me,
+            ctx,
+            pe as usize,
+            offset..offset + len,
+            Kind::Write,
+            operation,
+            true,
+        );
+    }
+}
+
+fn get(
+    operation: &'static str,
+    ctx: usize,
+    dest: *mut c_void,
+    source: *const c_void,
+    len: usize,
+    pe: c_int,
+    pending: bool,
+) {
+    let Pe { pe: me, job } = current();
+    job.faults.progress(me);
+    let remote = job.translate(me, source.cast(), pe as usize);
+    if !(pending && job.faults.defer(me, ctx, false, dest.cast(), remote, len)) {
+        unsafe { std::ptr::copy(remote, dest.cast::<u8>(), len) };
+    }
+
+    if let (Some(checker), Some(offset)) = (&job.checker, job.heap_offset(me, source.cast())) {
+        checker.access(
+            me,
+            ctx,
+            pe as usize,
+            offset..offset + len,
+            Kind::Read,
+            operation,
+            pending,
+        );
+    }
+}
+
+pub unsafe fn shmem_putmem(dest: *mut c_void, source: *const c_void, len: size_t, pe: c_int) {
+    put("shmem_putmem", 0, dest, source, len, pe, false);
+}
+
+pub unsafe fn shmem_putmem_nbi(dest: *mut c_void, source: *const c_void, len: size_t, pe: c_int) {
+    put("shmem_putmem_nbi", 0, dest, source, len, pe, true);
+}
+
+pub unsafe fn shmem_getmem(dest: *mut c_void, source: *const c_void, len: size_t, pe: c_int) {
+    get("shmem_getmem", 0, dest, source, len, pe, false);
+}
+
+pub unsafe fn shmem_getmem_nbi(dest: *mut c_void, source: *const c_void, len: size_t, pe: c_int) {
+    get("shmem_getmem_nbi", 0, dest, source, len, pe, true);
+}
+
+/// `nelems` elements of `size` bytes, `dst` and `sst` elements apart, as one
+/// blocking put or get per element.
+#[allow(clippy::too_many_arguments)]
+fn strided(
+    operation: &'static str,
+    dest: *mut c_void,
+    source: *const c_void,
+    dst: ptrdiff_t,
+    sst: ptrdiff_t,
+    nelems: size_t,
+    size: usize,
+    pe: c_int,
+    is_put: bool,
+) {
+    for i in 0..nelems as isize {
+        let dest = dest.wrapping_byte_offset(i * dst * size as isize);
+        let source = source.wrapping_byte_offset(i * sst * size as isize);
+        if is_put {
+            put(operation, 0, dest, source, size, pe, false);
+        } else {
+            get(operation, 0, dest, source, size, pe, false);
+        }
+    }
+}
+
+macro_rules! strided_rma {
+    ($($put:ident, $get:ident, $size:expr;)*) => {
+        $(
+            pub unsafe fn $put(
+                dest: *mut c_void,
+                source: *const c_void,
+                dst: ptrdiff_t,
+                sst: ptrdiff_t,
+                nelems: size_t,
+                pe: c_int,
+            ) {
+                strided(stringify!($put), dest, source, dst, sst, nelems, $size, pe, true);
+            }
+
+            pub unsafe fn $get(
+                dest: *mut c_void,
+                source: *const c_void,
+                dst: ptrdiff_t,
+                sst: ptrdiff_t,
+                nelems: size_t,
+                pe: c_int,
+            ) {
+                strided(stringify!($get), dest, source, dst, sst, nelems, $size, pe, false);
+            }
+        )*
+    };
+}
+
+strided_rma! {
+    shmem_iput8, shmem_iget8, 1;
+    shmem_iput16, shmem_iget16, 2;
+    shmem_iput32, shmem_iget32, 4;
+    shmem_iput64, shmem_iget64, 8;
+    shmem_iput128, shmem_iget128, 16;
+}
+
+// atomics
+
+/// Run `op` on the atomic at `dest` on `pe`. Writers release their clock
+/// before the update lands, readers acquire after reading, so a PE that sees
+/// the new value also sees what the writer did before it.
+fn atomic<A, R>(
+    operation: &'static str,
+    dest: *const c_void,
+    pe: c_int,
+    kind: Kind,
+    acquires: bool,
+    op: impl FnOnce(&A) -> R,
+) -> R {
+    let Pe { pe: me, job } = current();
+    job.faults.progress(me);
+    let pe = pe as usize;
+    let target = job.translate(me, dest.cast(), pe);
+    let checked = job.checker.as_ref().zip(job.heap_offset(me, dest.cast()));
+
+    if let Some((checker, offset)) = checked {
+        let len = std::mem::size_of::<A>();
+        checker.access(me, 0, pe, offset..offset + len, kind, operation, false);
+        if kind == Kind::Atomic {
+            checker.release(me, pe, offset);
+        }
+    }
+    let result = op(unsafe { &*target.cast::<A>() });
+    if let Some((checker, offset)) = checked
+        && acquires
+    {
+        checker.acquire(me, pe, offset);
+    }
+    result
+}
+
+pub unsafe fn shmem_int_atomic_fetch_add(dest: *mut c_int, value: c_int, pe: c_int) -> c_int {
+    atomic(
+        "shmem_int_atomic_fetch_add",
+        dest.cast(),
+        pe,
+        Kind::Atomic,
+        true,
+        |a: &AtomicI32| a.fetch_add(value, Ordering::SeqCst),
+    )
+}
+
+pub unsafe fn shmem_long_atomic_fetch_add(dest: *mut c_long, value: c_long, pe: c_int) -> c_long {
+    atomic(
+        "shmem_long_atomic_fetch_add",
+        dest.cast(),
+        pe,
+        Kind::Atomic,
+        true,
+        |a: &AtomicI64| a.fetch_add(value, Ordering::SeqCst),
+    )
+}
+
+pub unsafe fn shmem_int_cswap(dest: *mut c_int, cond: c_int, value: c_int, pe: c_int) -> c_int {
+    atomic(
+        "shmem_int_cswap",
+        dest.cast(),
+        pe,
+        Kind::Atomic,
+        true,
+        |a: &AtomicI32| {
+            a.compare_exchange(cond, value, Ordering::SeqCst, Ordering::SeqCst)
+                .unwrap_or_else(|current| current)
+        },
+    )
+}
+
+pub unsafe fn shmem_long_cswap(
+    dest: *mut c_long,
+    cond: c_long,
+    value: c_long,
+    pe: c_int,
+) -> c_long {
+    atomic(
+        "shmem_long_cswap",
+        dest.cast(),
+        pe,
+        Kind::Atomic,
+        true,
+        |a: &AtomicI64| {
+            a.compare_exchange(cond, value, Ordering::SeqCst, Ordering::SeqCst)
+                .unwrap_or_else(|current| current)
+        },
+    )
+}
+
+pub unsafe fn shmem_long_atomic_compare_swap(
+    dest: *mut c_long,
+    cond: c_long,
+    value: c_long,
+    pe: c_int,
+) -> c_long {
+    atomic(
+        "shmem_long_atomic_compare_swap",
+        dest.cast(),
+        pe,
+        Kind::Atomic,
+        true,
+        |a: &AtomicI64| {
+            a.compare_exchange(cond, value, Ordering::SeqCst, Ordering::SeqCst)
+                .unwrap_or_else(|current| current)
+        },
+    )
+}
+
+pub unsafe fn shmem_long_atomic_fetch(source: *const c_long, pe: c_int) -> c_long {
+    atomic(
+        "shmem_long_atomic_fetch",
+        source.cast(),
+        pe,
+        Kind::AtomicRead,
+        true,
+        |a: &AtomicI64| a.load(Ordering::SeqCst),
+    )
+}
+
+pub unsafe fn shmem_long_atomic_set(dest: *mut c_long, value: c_long, pe: c_int) {
+    atomic(
+        "shmem_long_atomic_set",
+        dest.cast(),
+        pe,
+        Kind::Atomic,
+        false,
+        |a: &AtomicI64| a.store(value, Ordering::SeqCst),
+    )
+}
+
+pub unsafe fn shmem_long_wait_until(ivar: *mut c_long, cmp: c_int, value: c_long) {
+    let var = unsafe { AtomicI64::from_ptr(ivar) };
+    let satisfied = |current: c_long| match cmp as u32 {
+        SHMEM_CMP_EQ => current == value,
+        SHMEM_CMP_NE => current != value,
+        SHMEM_CMP_GT => current > value,
+        SHMEM_CMP_GE => current >= value,
+        SHMEM_CMP_LT => current < value,
+        SHMEM_CMP_LE => current <= value,
+        _ => panic!("shmem_long_wait_until: unknown comparison {cmp}"),
+    };
+    let Pe { pe, job } = current();
+    while !satisfied(var.load(Ordering::SeqCst)) {
+        job.faults.progress(pe);
+        std::thread::yield_now();
+    }
+
+    if let (Some(checker), Some(offset)) = (&job.checker, job.heap_offset(pe, ivar.cast())) {
+        checker.acquire(pe, pe, offset);
+    }
+}
+
+// collectives: every member reads the sources it needs between two
+// rendezvous, then writes its own destination
+
+fn exchange(team: TeamShape, dest: *mut c_void, read: impl FnOnce(&Job, usize) -> Vec<u8>) {
+    let Pe { pe, job } = current();
+    job.rendezvous(pe, team);
+    let data = read(&job, pe);
+    job.rendezvous(pe, team);
+    unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), dest.cast(), data.len()) };
+}
+
+fn read_remote(
+    job: &Job,
+    me: usize,
+    source: *const c_void,
+    pe: usize,
+    offset: usize,
+    len: usize,
+) -> Vec<u8> {
+    let remote = job.translate(me, source.cast(), pe);
+    unsafe { std::slice::from_raw_parts(remote.add(offset), len) }.to_vec()
+}
+
+fn broadcast(team: TeamShape, dest: *mut c_void, source: *const c_void, len: usize, root: usize) {
+    exchange(team, dest, |job, me| {
+        read_remote(job, me, source, team.pe(root), 0, len)
+    });
+}
+
+fn fcollect(team: TeamShape, dest: *mut c_void, source: *const c_void, len: usize) {
+    exchange(team, dest, |job, me| {
+        team.members()
+            .flat_map(|pe| read_remote(job, me, source, pe, 0, len))
+            .collect()
+    });
+}
+
+fn alltoall(team: TeamShape, dest: *mut c_void, source: *const c_void, len: usize) {
+    exchange(team, dest, |job, me| {
+        let rank = team
+            .rank(me)
+            .expect("alltoall called by a PE outside the team");
+        team.members()
+            .flat_map(|pe| read_remote(job, me, source, pe, rank * len, len))
+            .collect()
+    });
+}
+
+fn sum_int(team: TeamShape, dest: *mut c_int, source: *const c_int, count: usize) {
+    let len = count * std::mem::size_of::<c_int>();
+    exchange(team, dest.cast(), |job, me| {
+        let mut sums = vec![0 as c_int; count];
+        for pe in team.members() {
+            let bytes = read_remote(job, me, source.cast(), pe, 0, len);
+            for (sum, value) in sums.iter_mut().zip(bytes.chunks_exact(4)) {
+                *sum = sum.wrapping_add(c_int::from_ne_bytes(value.try_into().unwrap()));
+            }
+        }
+        sums.iter().flat_map(|sum| sum.to_ne_bytes()).collect()
+    });
+}
+
+pub unsafe fn shmem_broadcastmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_root: c_int,
+) -> c_int {
+    let Some(shape) = team_shape(&current().job, team) else {
+        return -1;
+    };
+    broadcast(shape, dest, source, nelems, PE_root as usize);
+    0
+}
+
+pub unsafe fn shmem_fcollectmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+) -> c_int {
+    let Some(shape) = team_shape(&current().job, team) else {
+        return -1;
+    };
+    fcollect(shape, dest, source, nelems);
+    0
+}
+
+pub unsafe fn shmem_collectmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+) -> c_int {
+    let Pe { pe, job } = current();
+    let Some(shape) = team_shape(&job, team) else {
+        return -1;
+    };
+    let sizes: &[AtomicUsize] = &job.collect_sizes;
+    sizes[pe].store(nelems, Ordering::SeqCst);
+
+    exchange(shape, dest, |job, me| {
+        shape
+            .members()
+            .flat_map(|pe| read_remote(job, me, source, pe, 0, sizes[pe].load(Ordering::SeqCst)))
+            .collect()
+    });
+    0
+}
+
+pub unsafe fn shmem_alltoallmem(
+    team: shmem_team_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+) -> c_int {
+    let Some(shape) = team_shape(&current().job, team) else {
+        return -1;
+    };
+    alltoall(shape, dest, source, nelems);
+    0
+}
+
+pub unsafe fn shmem_int_sum_reduce(
+    team: shmem_team_t,
+    dest: *mut c_int,
+    source: *const c_int,
+    nreduce: size_t,
+) -> c_int {
+    let Some(shape) = team_shape(&current().job, team) else {
+        return -1;
+    };
+    sum_int(shape, dest, source, nreduce);
+    0
+}
+
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn shmem_broadcast64(
+    target: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_root: c_int,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    _pSync: *mut c_long,
+) {
+    let set = active_set(PE_start, logPE_stride, PE_size);
+    let root = PE_root as usize;
+    let me = current().pe;
+    // the legacy broadcast leaves the root's target alone
+    if set.rank(me) == Some(root) {
+        exchange(set, target, |_, _| Vec::new());
+    } else {
+        broadcast(set, target, source, nelems * 8, root);
+    }
+}
+
+pub unsafe fn shmem_fcollect64(
+    target: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    _pSync: *mut c_long,
+) {
+    fcollect(
+        active_set(PE_start, logPE_stride, PE_size),
+        target,
+        source,
+        nelems * 8,
+    );
+}
+
+pub unsafe fn shmem_alltoall64(
+    target: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    _pSync: *mut c_long,
+) {
+    alltoall(
+        active_set(PE_start, logPE_stride, PE_size),
+        target,
+        source,
+        nelems * 8,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn shmem_int_sum_to_all(
+    target: *mut c_int,
+    source: *const c_int,
+    nreduce: c_int,
+    PE_start: c_int,
+    logPE_stride: c_int,
+    PE_size: c_int,
+    _pWrk: *mut c_int,
+    _pSync: *mut c_long,
+) {
+    sum_int(
+        active_set(PE_start, logPE_stride, PE_size),
+        target,
+        source,
+        nreduce as usize,
+    );
+}
+
+// teams
+
+pub unsafe fn shmem_team_my_pe(team: shmem_team_t) -> c_int {
+    let Pe { pe, job } = current();
+    team_shape(&job, team)
+        .and_then(|shape| shape.rank(pe))
+        .map_or(-1, |rank| rank as c_int)
+}
+
+pub unsafe fn shmem_team_n_pes(team: shmem_team_t) -> c_int {
+    team_shape(&current().job, team).map_or(-1, |shape| shape.size as c_int)
+}
+
+pub unsafe fn shmem_team_translate_pe(
+    src_team: shmem_team_t,
+    src_pe: c_int,
+    dest_team: shmem_team_t,
+) -> c_int {
+    let job = current().job;
+    let (Some(src), Some(dest)) = (team_shape(&job, src_team), team_shape(&job, dest_team)) else {
+        return -1;
+    };
+    if !(0..src.size as c_int).contains(&src_pe) {
+        return -1;
+    }
+    dest.rank(src.pe(src_pe as usize))
+        .map_or(-1, |rank| rank as c_int)
+}
+
+/// Configuration of a new team, ignored by the simulator.
+#[derive(Debug, Clone, Copy)]
+pub struct shmem_team_config_t {
+    pub num_contexts: c_int,
+}
+
+pub unsafe fn shmem_team_split_strided(
+    parent_team: shmem_team_t,
+    start: c_int,
+    stride: c_int,
+    size: c_int,
+    _config: *const shmem_team_config_t,
+    _config_mask: c_long,
+    new_team: *mut shmem_team_t,
+) -> c_int {
+    let Pe { pe, job } = current();
+    let Some(parent) = team_shape(&job, parent_team) else {
+        return -1;
+    };
+    let stride = stride.max(1) as usize;
+    let last = start as usize + (size.max(1) as usize - 1) * stride;
+    if start < 0 || size < 1 || last >= parent.size {
+        return -1;
+    }
+
+    let shape = TeamShape {
+        start: parent.pe(start as usize),
+        stride: parent.stride * stride,
+        size: size as usize,
+    };
+    let team = match shape.rank(pe) {
+        Some(_) => Box::into_raw(Box::new(oshmem_team {
+            start: shape.start,
+            stride: shape.stride,
+            size: shape.size,
+        })),
+        None => std::ptr::null_mut(),
+    };
+    unsafe { *new_team = team };
+
+    job.rendezvous(pe, parent);
+    0
+}
+
+// contexts: RMA is carried out like on the default context, only completion
+// is tracked per context
+
+static NEXT_CTX: AtomicUsize = AtomicUsize::new(1);
+
+pub unsafe fn shmem_ctx_create(_options: c_long, ctx: *mut shmem_ctx_t) -> c_int {
+    let id = NEXT_CTX.fetch_add(1, Ordering::Relaxed);
+    unsafe { *ctx = std::ptr::without_provenance_mut(id) };
+    0
+}
+
+pub unsafe fn shmem_ctx_destroy(ctx: shmem_ctx_t) {
+    complete(ctx_id(ctx), false);
+}
+
+pub unsafe fn shmem_ctx_quiet(ctx: shmem_ctx_t) {
+    complete(ctx_id(ctx), false);
+}
+
+pub unsafe fn shmem_ctx_fence(ctx: shmem_ctx_t) {
+    complete(ctx_id(ctx), true);
+}
+
+pub unsafe fn shmem_ctx_putmem(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    put(
+        "shmem_ctx_putmem",
+        ctx_id(ctx),
+        dest,
+        source,
+        nelems,
+        pe,
+        false,
+    );
+}
+
+pub unsafe fn shmem_ctx_putmem_nbi(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    put(
+        "shmem_ctx_putmem_nbi",
+        ctx_id(ctx),
+        dest,
+        source,
+        nelems,
+        pe,
+        true,
+    );
+}
+
+pub unsafe fn shmem_ctx_getmem(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    get(
+        "shmem_ctx_getmem",
+        ctx_id(ctx),
+        dest,
+        source,
+        nelems,
+        pe,
+        false,
+    );
+}
+
+pub unsafe fn shmem_ctx_getmem_nbi(
+    ctx: shmem_ctx_t,
+    dest: *mut c_void,
+    source: *const c_void,
+    nelems: size_t,
+    pe: c_int,
+) {
+    get(
+        "shmem_ctx_getmem_nbi",
+        ctx_id(ctx),
+        dest,
+        source,
+        nelems,
+        pe,
+        true,
+    );
+}
@@ -0,0 +1,144 @@
+use std::{alloc::Layout, collections::BTreeMap, sync::Mutex};
+
+use super::lock;
+
+const PAGE: usize = 4096;
+
+/// Symmetric heap of one simulated PE.
+///
+/// First fit over an address-ordered free list, so PEs that make the same
+/// calls get the same offsets.
+pub(super) struct Heap {
+    /// Start of the reservation, `base` rounded down.
+    reserved: *mut u8,
+    base: *mut u8,
+    size: usize,
+    blocks: Mutex<Blocks>,
+}
+
+// the memory is only reached through SHMEM calls, which synchronize themselves
+unsafe impl Send for Heap {}
+unsafe impl Sync for Heap {}
+
+struct Blocks {
+    /// Offset to length of every free block, coalesced.
+    free: BTreeMap<usize, usize>,
+    /// Offset to size and alignment of every allocated block.
+    used: BTreeMap<usize, (usize, usize)>,
+}
+
+impl Heap {
+    pub(super) fn new(size: usize) -> Self {
+        let size = size.max(PAGE).next_multiple_of(PAGE);
+        // a small alignment keeps this on calloc, whose fresh pages are only
+        // committed once touched, where a page alignment would zero them all
+        let reserved = unsafe { std::alloc::alloc_zeroed(Self::layout(size)) };
+        assert!(
+            !reserved.is_null(),
+            "failed to reserve a {size} byte symmetric heap"
+        );
+
+        Heap {
+            reserved,
+            base: reserved.wrapping_add(reserved.align_offset(PAGE)),
+            size,
+            blocks: Mutex::new(Blocks {
+                free: BTreeMap::from([(0, size)]),
+                used: BTreeMap::new(),
+            }),
+        }
+    }
+
+    fn layout(size: usize) -> Layout {
+        Layout::from_size_align(size + PAGE, 16).unwrap()
+    }
+
+    pub(super) fn offset(&self, addr: *const u8) -> Option<usize> {
+        let offset = (addr as usize).checked_sub(self.base as usize)?;
+        (offset < self.size).then_some(offset)
+    }
+
+    pub(super) fn at(&self, offset: usize) -> *mut u8 {
+        self.base.wrapping_add(offset)
+    }
+
+    pub(super) fn alloc(&self, align: usize, size: usize) -> *mut u8 {
+        let align = align.max(1);
+        let size = size.max(1);
+        let mut blocks = lock(&self.blocks);
+
+        let found = blocks.free.iter().find_map(|(&start, &len)| {
+            let aligned = start.next_multiple_of(align);
+            (aligned + size <= start + len).then_some((start, len, aligned))
+        });
+        let Some((start, len, aligned)) = found else {
+            return std::ptr::null_mut();
+        };
+
+        blocks.free.remove(&start);
+        if aligned > start {
+            blocks.free.insert(start, aligned - start);
+        }
+        if aligned + size < start + len {
+            blocks
+                .free
+                .insert(aligned + size, start + len - aligned - size);
+        }
+        blocks.used.insert(aligned, (size, align));
+
+        self.at(aligned)
+    }
+
+    pub(super) fn free(&self, ptr: *mut u8) {
+        if ptr.is_null() {
+            return;
+        }
+        let offset = self
+            .offset(ptr)
+            .expect("shfree of a pointer outside the symmetric heap");
+        let mut blocks = lock(&self.blocks);
+        let (mut len, _) = blocks
+            .used
+            .remove(&offset)
+            .expect("shfree of a pointer that was not allocated");
+        let mut start = offset;
+
+        if let Some((&next, &next_len)) = blocks.free.range(start + len..).next()
+            && next == start + len
+        {
+            blocks.free.remove(&next);
+            len += next_len;
+        }
+        if let Some((&prev, &prev_len)) = blocks.free.range(..start).next_back()
+            && prev + prev_len == start
+        {
+            blocks.free.remove(&prev);
+            start = prev;
+            len += prev_len;
+        }
+        blocks.free.insert(start, len);
+    }
+
+    pub(super) fn realloc(&self, ptr: *mut u8, size: usize) -> *mut u8 {
+        if ptr.is_null() {
+            return self.alloc(1, size);
+        }
+        let offset = self
+            .offset(ptr)
+            .expect("shrealloc of a pointer outside the symmetric heap");
+        let (old, align) = lock(&self.blocks).used[&offset];
+
+        let new = self.alloc(align, size);
+        if !new.is_null() {
+            unsafe { std::ptr::copy_nonoverlapping(ptr, new, old.min(size)) };
+            self.free(ptr);
+        }
+        new
+    }
+}
+
+impl Drop for Heap {
+    fn drop(&mut self) {
+        unsafe { std::alloc::dealloc(self.reserved, Self::layout(self.size)) };
+    }
+}
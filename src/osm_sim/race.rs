@@ -0,0 +1,285 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter},
+    ops::Range,
+    sync::Mutex,
+};
+
+use super::lock;
+
+/// Records per target PE before ordered ones are pruned.
+const PRUNE_THRESHOLD: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum Kind {
+    Read,
+    Write,
+    AtomicRead,
+    Atomic,
+}
+
+impl Kind {
+    /// Atomics never race with each other, anything else does if either side
+    /// modifies memory.
+    fn conflicts(self, other: Kind) -> bool {
+        (self.modifies() || other.modifies()) && !(self.is_atomic() && other.is_atomic())
+    }
+
+    fn modifies(self) -> bool {
+        matches!(self, Kind::Write | Kind::Atomic)
+    }
+
+    fn is_atomic(self) -> bool {
+        matches!(self, Kind::AtomicRead | Kind::Atomic)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Read => "read",
+            Kind::Write => "write",
+            Kind::AtomicRead => "atomic read",
+            Kind::Atomic => "atomic update",
+        }
+    }
+}
+
+/// Two accesses to the same symmetric bytes that are not ordered by quiet,
+/// fence, barrier or wait, and at least one of which modifies them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Race {
+    /// PE whose memory was accessed.
+    pub target_pe: i32,
+    /// Overlapping bytes, as offsets into the symmetric heap.
+    pub bytes: Range<usize>,
+    pub first: RaceAccess,
+    pub second: RaceAccess,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceAccess {
+    /// PE that issued the call.
+    pub pe: i32,
+    /// Name of the SHMEM routine.
+    pub operation: &'static str,
+    pub kind: &'static str,
+    /// Whether a quiet or fence had completed the access when the race was detected.
+    pub completed: bool,
+}
+
+impl Display for RaceAccess {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}) by PE {}", self.operation, self.kind, self.pe)?;
+        if !self.completed {
+            write!(f, ", not yet completed")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Race {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "data race on PE {} heap bytes {:?}: {} and {} are not ordered by quiet, fence, \
+             barrier or wait",
+            self.target_pe, self.bytes, self.first, self.second
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Access {
+    pe: usize,
+    ctx: usize,
+    kind: Kind,
+    bytes: Range<usize>,
+    operation: &'static str,
+    /// Epoch of the issuer the access completed in; `None` until a quiet or
+    /// fence completes it.
+    epoch: Option<u64>,
+}
+
+impl Access {
+    fn describe(&self) -> RaceAccess {
+        RaceAccess {
+            pe: self.pe as i32,
+            operation: self.operation,
+            kind: self.kind.name(),
+            completed: self.epoch.is_some(),
+        }
+    }
+}
+
+struct State {
+    /// Vector clock of every PE. A PE's own entry is its current epoch.
+    clocks: Vec<Vec<u64>>,
+    /// Accesses to the heap of every PE.
+    accesses: Vec<Vec<Access>>,
+    /// Clock released by the last atomic to a heap location, acquired by the
+    /// atomics and waits that read it.
+    sync_vars: HashMap<(usize, usize), Vec<u64>>,
+    races: Vec<Race>,
+    reported: HashSet<(usize, usize, &'static str, usize, &'static str)>,
+}
+
+/// Vector clock happens-before checker over the SHMEM calls of all PEs.
+///
+/// Every PE's epoch advances at quiet, fence, barrier, wait and atomics. An
+/// earlier access is ordered before a later one by another PE if the later
+/// PE's clock has caught up with the epoch the earlier access completed in:
+/// puts complete at the issuer's next fence or quiet, non-blocking gets at its
+/// next quiet, blocking gets and atomics on return. Loads and stores through
+/// local references are not seen.
+pub(super) struct RaceChecker {
+    state: Mutex<State>,
+}
+
+impl RaceChecker {
+    pub(super) fn new(num_pes: usize) -> Self {
+        let clocks = (0..num_pes)
+            .map(|pe| {
+                let mut clock = vec![0; num_pes];
+                clock[pe] = 1;
+                clock
+            })
+            .collect();
+
+        RaceChecker {
+            state: Mutex::new(State {
+                clocks,
+                accesses: vec![Vec::new(); num_pes],
+                sync_vars: HashMap::new(),
+                races: Vec::new(),
+                reported: HashSet::new(),
+            }),
+        }
+    }
+
+    pub(super) fn races(&self) -> Vec<Race> {
+        lock(&self.state).races.clone()
+    }
+
+    pub(super) fn clock(&self, pe: usize) -> Vec<u64> {
+        lock(&self.state).clocks[pe].clone()
+    }
+
+    /// Join `clock` into the clock of `pe`, as when leaving a barrier.
+    pub(super) fn acquire_clock(&self, pe: usize, clock: &[u64]) {
+        let mut state = lock(&self.state);
+        join(&mut state.clocks[pe], clock);
+        state.clocks[pe][pe] += 1;
+    }
+
+    /// Record an access by `pe` to `bytes` of the heap of `target`.
+    /// `pending` accesses stay unordered until [`Self::complete`] sees them.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn access(
+        &self,
+        pe: usize,
+        ctx: usize,
+        target: usize,
+        bytes: Range<usize>,
+        kind: Kind,
+        operation: &'static str,
+        pending: bool,
+    ) {
+        let mut state = lock(&self.state);
+        let state = &mut *state;
+        let access = Access {
+            pe,
+            ctx,
+            kind,
+            bytes,
+            operation,
+            epoch: (!pending).then_some(state.clocks[pe][pe]),
+        };
+
+        for earlier in &state.accesses[target] {
+            let overlap = earlier.bytes.start.max(access.bytes.start)
+                ..earlier.bytes.end.min(access.bytes.end);
+            if overlap.is_empty()
+                || !earlier.kind.conflicts(kind)
+                || ordered(earlier, &state.clocks[pe])
+            {
+                continue;
+            }
+
+            let key = (target, earlier.pe, earlier.operation, pe, operation);
+            if state.reported.insert(key) {
+                let race = Race {
+                    target_pe: target as i32,
+                    bytes: overlap,
+                    first: earlier.describe(),
+                    second: access.describe(),
+                };
+                eprintln!("{race}");
+                state.races.push(race);
+            }
+        }
+
+        state.accesses[target].push(access);
+        if state.accesses[target].len() > PRUNE_THRESHOLD {
+            prune(state, target);
+        }
+    }
+
+    /// Complete the pending accesses of `pe` on `ctx`, only the ones that
+    /// modify memory for a fence, then start a new epoch.
+    pub(super) fn complete(&self, pe: usize, ctx: usize, writes_only: bool) {
+        let mut state = lock(&self.state);
+        let state = &mut *state;
+        let epoch = state.clocks[pe][pe];
+        for access in state.accesses.iter_mut().flatten() {
+            if access.pe == pe
+                && access.ctx == ctx
+                && access.epoch.is_none()
+                && (!writes_only || access.kind.modifies())
+            {
+                access.epoch = Some(epoch);
+            }
+        }
+        state.clocks[pe][pe] += 1;
+    }
+
+    /// Take the clock released at `offset` on `target`, for atomics that read it.
+    pub(super) fn acquire(&self, pe: usize, target: usize, offset: usize) {
+        let mut state = lock(&self.state);
+        let state = &mut *state;
+        if let Some(released) = state.sync_vars.get(&(target, offset)) {
+            join(&mut state.clocks[pe], released);
+        }
+        state.clocks[pe][pe] += 1;
+    }
+
+    /// Publish the clock of `pe` at `offset` on `target`, for atomics that
+    /// modify it.
+    pub(super) fn release(&self, pe: usize, target: usize, offset: usize) {
+        let mut state = lock(&self.state);
+        let state = &mut *state;
+        let clock = state.clocks[pe].clone();
+        join(state.sync_vars.entry((target, offset)).or_default(), &clock);
+        state.clocks[pe][pe] += 1;
+    }
+}
+
+/// Whether `earlier` happens before the next access of the PE owning `clock`.
+fn ordered(earlier: &Access, clock: &[u64]) -> bool {
+    earlier
+        .epoch
+        .is_some_and(|epoch| epoch <= clock[earlier.pe])
+}
+
+/// Drop the accesses every PE is already ordered after.
+fn prune(state: &mut State, target: usize) {
+    let clocks = &state.clocks;
+    state.accesses[target].retain(|access| !clocks.iter().all(|clock| ordered(access, clock)));
+}
+
+pub(super) fn join(clock: &mut Vec<u64>, other: &[u64]) {
+    if clock.len() < other.len() {
+        clock.resize(other.len(), 0);
+    }
+    for (mine, theirs) in clock.iter_mut().zip(other) {
+        *mine = (*mine).max(*theirs);
+    }
+}
@@ -1,14 +1,24 @@
 use std::ops::{Deref, DerefMut, Index, IndexMut};
 
-use openshmem_sys::{
-    _SHMEM_SYNC_VALUE, SHMEM_BARRIER_SYNC_SIZE, shmem_alltoall64, shmem_broadcast64,
-    shmem_fcollect64, shmem_getmem, shmem_getmem_nbi, shmem_int_atomic_fetch_add, shmem_int_cswap,
-    shmem_int_sum_reduce, shmem_int_sum_to_all, shmem_long_atomic_fetch_add, shmem_long_cswap,
-    shmem_putmem, shmem_putmem_nbi,
-};
 use ref_cast::RefCast;
 
-use crate::{osm_scope::OsmScope, osm_vec::ShVec, osm_wrapper::OsmWrapper};
+use crate::{
+    osm_collective::CollectiveScratch,
+    osm_error::{ShmemError, check_remote, check_size},
+    osm_ffi::{
+        _SHMEM_SYNC_VALUE, SHMEM_BARRIER_SYNC_SIZE, shmem_alltoall64, shmem_broadcast64,
+        shmem_fcollect64, shmem_getmem, shmem_getmem_nbi, shmem_int_atomic_fetch_add,
+        shmem_int_cswap, shmem_int_sum_reduce, shmem_int_sum_to_all, shmem_long_atomic_fetch_add,
+        shmem_long_cswap, shmem_my_pe, shmem_putmem, shmem_putmem_nbi,
+    },
+    osm_future::NbiHandle,
+    osm_pod::ShmemPod,
+    osm_reduce::Commutativity,
+    osm_scope::OsmScope,
+    osm_team::OsmTeam,
+    osm_vec::ShVec,
+    osm_wrapper::OsmWrapper,
+};
 
 #[derive(Debug, RefCast)]
 #[repr(transparent)]
@@ -143,7 +153,46 @@ impl<T> IndexMut<std::ops::RangeToInclusive<usize>> for OsmSlice<T> {
 
 const P2P_SIZE: usize = 1024;
 
-impl<T> OsmSlice<T> {
+impl<T: ShmemPod> OsmSlice<T> {
+    /// Reinterpret the elements as `U`.
+    ///
+    /// Panics if the data is not aligned for `U` or its size is not a multiple
+    /// of `size_of::<U>()`. An empty slice casts to an empty slice at a
+    /// dangling, well-aligned address.
+    pub fn cast_slice<U: ShmemPod>(&self) -> &OsmSlice<U> {
+        let (ptr, len) = Self::cast_parts::<U>(self.as_ptr(), std::mem::size_of_val(&self.data));
+        unsafe { OsmSlice::from_raw_parts(ptr, len) }
+    }
+
+    pub fn cast_slice_mut<U: ShmemPod>(&mut self) -> &mut OsmSlice<U> {
+        let (ptr, len) = Self::cast_parts::<U>(self.as_ptr(), std::mem::size_of_val(&self.data));
+        unsafe { OsmSlice::from_raw_parts_mut(ptr, len) }
+    }
+
+    fn cast_parts<U>(ptr: *const T, bytes: usize) -> (*mut U, usize) {
+        assert!(
+            std::mem::size_of::<U>() > 0,
+            "cannot cast to a zero-sized type"
+        );
+        if bytes == 0 {
+            // the original pointer need not be aligned for `U`
+            return (std::ptr::NonNull::<U>::dangling().as_ptr(), 0);
+        }
+        assert_eq!(
+            bytes % std::mem::size_of::<U>(),
+            0,
+            "{bytes} bytes do not divide into elements of {} bytes",
+            std::mem::size_of::<U>()
+        );
+        assert!(
+            ptr.cast::<U>().is_aligned(),
+            "{ptr:?} is not aligned to {} bytes",
+            std::mem::align_of::<U>()
+        );
+        (ptr as *mut U, bytes / std::mem::size_of::<U>())
+    }
+
+    #[track_caller]
     pub fn put_to(&self, other: &mut Self, target_pe: i32) {
         unsafe {
             shmem_putmem(
@@ -155,7 +204,8 @@ impl<T> OsmSlice<T> {
         }
     }
 
-    pub fn put_to_nbi(&self, other: &mut Self, target_pe: i32) {
+    #[track_caller]
+    pub fn put_to_nbi(&self, other: &mut Self, target_pe: i32) -> NbiHandle {
         unsafe {
             shmem_putmem_nbi(
                 other.as_mut_ptr().cast(),
@@ -164,8 +214,10 @@ impl<T> OsmSlice<T> {
                 target_pe,
             );
         }
+        NbiHandle::issue()
     }
 
+    #[track_caller]
     pub fn get_from(&mut self, other: &Self, target_pe: i32) {
         unsafe {
             shmem_getmem(
@@ -177,7 +229,8 @@ impl<T> OsmSlice<T> {
         }
     }
 
-    pub fn get_from_nbi(&mut self, other: &Self, target_pe: i32) {
+    #[track_caller]
+    pub fn get_from_nbi(&mut self, other: &Self, target_pe: i32) -> NbiHandle {
         unsafe {
             shmem_getmem_nbi(
                 self.as_mut_ptr().cast(),
@@ -186,8 +239,75 @@ impl<T> OsmSlice<T> {
                 target_pe,
             );
         }
+        NbiHandle::issue()
+    }
+
+    /// [`OsmSlice::put_to`] after checking the target PE, that `other` is
+    /// remotely accessible and large enough.
+    #[track_caller]
+    pub fn try_put_to(&self, other: &mut Self, target_pe: i32) -> Result<(), ShmemError> {
+        self.check_transfer("put_to", other, target_pe)?;
+        self.put_to(other, target_pe);
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn try_put_to_nbi(
+        &self,
+        other: &mut Self,
+        target_pe: i32,
+    ) -> Result<NbiHandle, ShmemError> {
+        self.check_transfer("put_to_nbi", other, target_pe)?;
+        Ok(self.put_to_nbi(other, target_pe))
+    }
+
+    #[track_caller]
+    pub fn try_get_from(&mut self, other: &Self, target_pe: i32) -> Result<(), ShmemError> {
+        self.check_transfer("get_from", other, target_pe)?;
+        self.get_from(other, target_pe);
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn try_get_from_nbi(
+        &mut self,
+        other: &Self,
+        target_pe: i32,
+    ) -> Result<NbiHandle, ShmemError> {
+        self.check_transfer("get_from_nbi", other, target_pe)?;
+        Ok(self.get_from_nbi(other, target_pe))
+    }
+
+    /// Check the target PE, that `remote` is remotely accessible and can hold
+    /// this slice.
+    pub(crate) fn check_transfer(
+        &self,
+        operation: &'static str,
+        remote: &Self,
+        pe: i32,
+    ) -> Result<(), ShmemError> {
+        check_remote(operation, remote.as_ptr().cast(), pe)?;
+        check_size(
+            operation,
+            std::mem::size_of_val::<[T]>(self),
+            std::mem::size_of_val::<[T]>(remote),
+        )
+    }
+
+    fn check_atomic<U>(&self, operation: &'static str) -> Result<(), ShmemError> {
+        let actual = std::mem::size_of_val::<[T]>(self);
+        if actual != std::mem::size_of::<U>() {
+            return Err(ShmemError::Size {
+                operation,
+                pe: unsafe { shmem_my_pe() },
+                expected: std::mem::size_of::<U>(),
+                actual,
+            });
+        }
+        Ok(())
     }
 
+    #[track_caller]
     pub fn broadcast(
         &self,
         other: &mut Self,
@@ -214,6 +334,7 @@ impl<T> OsmSlice<T> {
         }
     }
 
+    #[track_caller]
     pub fn all_gather(&self, other: &mut Self, scope: &OsmScope, p_sync: &mut ShVec<i64>) -> usize {
         let other_len = other.len();
         let my_pe = scope.my_pe() as usize;
@@ -247,6 +368,10 @@ impl<T> OsmSlice<T> {
         num_ops
     }
 
+    /// Send `self.len()` elements to every PE of the active set. The block for
+    /// the `i`-th PE starts `i * self.len()` elements after `self`, so `self`
+    /// is the first block of a buffer holding one per PE.
+    #[track_caller]
     pub fn all_to_all(
         &self,
         other: &mut Self,
@@ -273,6 +398,8 @@ impl<T> OsmSlice<T> {
         1
     }
 
+    /// Sum `self`, read as `i32`s, over all PEs into `other`.
+    #[track_caller]
     pub fn all_reduce(
         &self,
         other: &mut Self,
@@ -289,7 +416,7 @@ impl<T> OsmSlice<T> {
             shmem_int_sum_to_all(
                 other.as_mut_ptr().cast(),
                 self.as_ptr().cast(),
-                (self.len() * std::mem::size_of::<T>() / std::mem::size_of::<u64>()) as i32,
+                (self.len() * std::mem::size_of::<T>() / std::mem::size_of::<i32>()) as i32,
                 pe_start,
                 log_pe_stride,
                 pe_size,
@@ -312,43 +439,91 @@ impl<T> OsmSlice<T> {
         num_ops
     }
 
+    /// Reduce with a user-defined `op` over all PEs, see [`OsmTeam::all_reduce_with`].
+    #[track_caller]
+    pub fn all_reduce_with(
+        &self,
+        other: &mut Self,
+        op: impl Fn(&T, &T) -> T,
+        commutativity: Commutativity,
+        scratch: &mut CollectiveScratch,
+    ) where
+        T: Copy,
+    {
+        OsmTeam::world().all_reduce_with(self, other, op, commutativity, scratch);
+    }
+
+    #[track_caller]
     pub fn fetch_add_i32(&mut self, value: i32, target_pe: i32) -> i32 {
-        unsafe {
-            if self.len() != size_of::<i32>() {
-                panic!("fetch_add_i32 only works for i32");
-            }
+        self.check_atomic::<i32>("fetch_add_i32")
+            .unwrap_or_else(|err| panic!("{err}"));
+        unsafe { shmem_int_atomic_fetch_add(self.as_mut_ptr().cast(), value, target_pe) }
+    }
 
-            shmem_int_atomic_fetch_add(self.as_mut_ptr().cast(), value, target_pe)
-        }
+    /// [`OsmSlice::fetch_add_i32`] that also checks the target PE and that this slice
+    /// is remotely accessible.
+    #[track_caller]
+    pub fn try_fetch_add_i32(&mut self, value: i32, target_pe: i32) -> Result<i32, ShmemError> {
+        self.check_atomic::<i32>("fetch_add_i32")?;
+        check_remote("fetch_add_i32", self.as_ptr().cast(), target_pe)?;
+        Ok(unsafe { shmem_int_atomic_fetch_add(self.as_mut_ptr().cast(), value, target_pe) })
     }
 
+    #[track_caller]
     pub fn fetch_add_i64(&mut self, value: i64, target_pe: i32) -> i64 {
-        unsafe {
-            if self.len() != size_of::<i64>() {
-                panic!("fetch_add_i64 only works for i64");
-            }
+        self.check_atomic::<i64>("fetch_add_i64")
+            .unwrap_or_else(|err| panic!("{err}"));
+        unsafe { shmem_long_atomic_fetch_add(self.as_mut_ptr().cast(), value, target_pe) }
+    }
 
-            shmem_long_atomic_fetch_add(self.as_mut_ptr().cast(), value, target_pe)
-        }
+    /// [`OsmSlice::fetch_add_i64`] that also checks the target PE and that this slice
+    /// is remotely accessible.
+    #[track_caller]
+    pub fn try_fetch_add_i64(&mut self, value: i64, target_pe: i32) -> Result<i64, ShmemError> {
+        self.check_atomic::<i64>("fetch_add_i64")?;
+        check_remote("fetch_add_i64", self.as_ptr().cast(), target_pe)?;
+        Ok(unsafe { shmem_long_atomic_fetch_add(self.as_mut_ptr().cast(), value, target_pe) })
     }
 
+    #[track_caller]
     pub fn compare_and_swap_i32(&mut self, expected: i32, desired: i32, target_pe: i32) -> i32 {
-        unsafe {
-            if self.len() != size_of::<i32>() {
-                panic!("compare_and_exchange_i32 only works for i32");
-            }
+        self.check_atomic::<i32>("compare_and_swap_i32")
+            .unwrap_or_else(|err| panic!("{err}"));
+        unsafe { shmem_int_cswap(self.as_mut_ptr().cast(), expected, desired, target_pe) }
+    }
 
-            shmem_int_cswap(self.as_mut_ptr().cast(), expected, desired, target_pe)
-        }
+    /// [`OsmSlice::compare_and_swap_i32`] that also checks the target PE and that this slice
+    /// is remotely accessible.
+    #[track_caller]
+    pub fn try_compare_and_swap_i32(
+        &mut self,
+        expected: i32,
+        desired: i32,
+        target_pe: i32,
+    ) -> Result<i32, ShmemError> {
+        self.check_atomic::<i32>("compare_and_swap_i32")?;
+        check_remote("compare_and_swap_i32", self.as_ptr().cast(), target_pe)?;
+        Ok(unsafe { shmem_int_cswap(self.as_mut_ptr().cast(), expected, desired, target_pe) })
     }
 
+    #[track_caller]
     pub fn compare_and_swap_i64(&mut self, expected: i64, desired: i64, target_pe: i32) -> i64 {
-        unsafe {
-            if self.len() != size_of::<i64>() {
-                panic!("compare_and_exchange_i64 only works for i64");
-            }
+        self.check_atomic::<i64>("compare_and_swap_i64")
+            .unwrap_or_else(|err| panic!("{err}"));
+        unsafe { shmem_long_cswap(self.as_mut_ptr().cast(), expected, desired, target_pe) }
+    }
 
-            shmem_long_cswap(self.as_mut_ptr().cast(), expected, desired, target_pe)
-        }
+    /// [`OsmSlice::compare_and_swap_i64`] that also checks the target PE and that this slice
+    /// is remotely accessible.
+    #[track_caller]
+    pub fn try_compare_and_swap_i64(
+        &mut self,
+        expected: i64,
+        desired: i64,
+        target_pe: i32,
+    ) -> Result<i64, ShmemError> {
+        self.check_atomic::<i64>("compare_and_swap_i64")?;
+        check_remote("compare_and_swap_i64", self.as_ptr().cast(), target_pe)?;
+        Ok(unsafe { shmem_long_cswap(self.as_mut_ptr().cast(), expected, desired, target_pe) })
     }
 }
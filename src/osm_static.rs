@@ -0,0 +1,101 @@
+use std::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use ref_cast::RefCast;
+
+use crate::{osm_ffi::shmem_addr_accessible, osm_scope::OsmScope, osm_wrapper::OsmWrapper};
+
+/// Declare `static` items that live in symmetric memory.
+///
+/// OpenSHMEM treats global and static data as symmetric, so these can be used
+/// as RMA and atomic targets without a heap allocation, once every PE has
+/// called [`SymmetricStatic::init`]:
+///
+/// ```ignore
+/// symmetric_static! {
+///     static RUNNING: AtomicBool = AtomicBool::new(true);
+/// }
+///
+/// RUNNING.init(&scope);
+/// let running = RUNNING.get(&scope);
+/// ```
+#[macro_export]
+macro_rules! symmetric_static {
+    ($($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr;)*) => {
+        $(
+            $(#[$attr])*
+            $vis static $name: $crate::osm_static::SymmetricStatic<$ty> =
+                $crate::osm_static::SymmetricStatic::new($init);
+        )*
+    };
+}
+
+/// A `static` whose address is symmetric across PEs.
+///
+/// The `UnsafeCell` keeps the item out of read-only sections, which the
+/// runtime does not register as remotely accessible.
+///
+/// With `sim` all PEs are threads of one process, so there is a single copy
+/// of the static that every PE reads and writes, not one per PE. A put to it
+/// from any PE lands in that copy, which makes it unsuitable as a per-PE RMA
+/// target in simulated runs; use an [`OsmBox`](crate::osm_box::OsmBox) there.
+pub struct SymmetricStatic<T> {
+    value: UnsafeCell<T>,
+    verified: AtomicBool,
+}
+
+unsafe impl<T: Sync> Sync for SymmetricStatic<T> {}
+
+impl<T> SymmetricStatic<T> {
+    pub const fn new(value: T) -> Self {
+        SymmetricStatic {
+            value: UnsafeCell::new(value),
+            verified: AtomicBool::new(false),
+        }
+    }
+
+    /// Check that every PE can access this static, then synchronize, so that no
+    /// PE targets it before all of them have checked. Collective over all PEs.
+    #[track_caller]
+    pub fn init(&'static self, scope: &OsmScope) {
+        let addr = self.value.get() as *const std::ffi::c_void;
+        // the simulator has no data sections to check against
+        #[cfg(feature = "sim")]
+        crate::osm_sim::register_static(addr.cast(), std::mem::size_of::<T>().max(1));
+        for pe in 0..scope.num_pes() {
+            if unsafe { shmem_addr_accessible(addr, pe) } != 1 {
+                panic!("symmetric static at {addr:?} is not accessible from PE {pe}");
+            }
+        }
+        self.verified.store(true, Ordering::Release);
+        scope.barrier_all();
+    }
+
+    /// The scope only proves that the runtime is still initialized.
+    #[track_caller]
+    pub fn get(&'static self, _scope: &OsmScope) -> &'static OsmWrapper<T> {
+        self.verify();
+        unsafe { OsmWrapper::ref_cast(&*self.value.get()) }
+    }
+
+    /// # Safety
+    ///
+    /// The caller must not hold any other reference to this static while the
+    /// returned one is alive.
+    #[allow(clippy::mut_from_ref)]
+    #[track_caller]
+    pub unsafe fn get_mut(&'static self, _scope: &OsmScope) -> &'static mut OsmWrapper<T> {
+        self.verify();
+        unsafe { &mut *(self.value.get() as *mut OsmWrapper<T>) }
+    }
+
+    #[track_caller]
+    fn verify(&self) {
+        assert!(
+            self.verified.load(Ordering::Acquire),
+            "symmetric static used before SymmetricStatic::init"
+        );
+    }
+}
@@ -2,10 +2,17 @@ use core::str;
 use std::mem::{MaybeUninit, transmute};
 
 use libc::DS;
-use openshmem_sys::{oshmem_team_world, shmem_broadcast64, shmem_team_split_strided};
-use openshmem_sys::{shmem_broadcastmem, shmem_team_t};
 
-use crate::osm_slice::OsmSlice;
+use crate::{
+    osm_error::{ShmemError, check, check_size},
+    osm_ffi::{
+        oshmem_team_shared, oshmem_team_world, shmem_broadcast64, shmem_broadcastmem,
+        shmem_team_my_pe, shmem_team_n_pes, shmem_team_split_strided, shmem_team_sync,
+        shmem_team_t, shmem_team_translate_pe,
+    },
+    osm_pod::ShmemPod,
+    osm_slice::OsmSlice,
+};
 
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy)]
@@ -18,12 +25,38 @@ impl OsmTeam {
         unsafe { OsmTeam { inner: oshmem_team_world } }
     }
 
-    pub fn split_strided(
-        self,
-        start: i32,
-        stride: i32,
-        size: i32,
-    ) -> Result<Self, TeamCreationError> {
+    /// PEs that can access each other's symmetric memory directly, usually the node.
+    pub fn shared() -> Self {
+        unsafe { OsmTeam { inner: oshmem_team_shared } }
+    }
+
+    pub fn my_pe(self) -> i32 {
+        unsafe { shmem_team_my_pe(self.inner) }
+    }
+
+    pub fn num_pes(self) -> i32 {
+        unsafe { shmem_team_n_pes(self.inner) }
+    }
+
+    /// Number of `pe` (a rank in this team) in `other`, or -1 if it is not a member.
+    pub fn translate_pe(self, pe: i32, other: OsmTeam) -> i32 {
+        unsafe { shmem_team_translate_pe(self.inner, pe, other.inner) }
+    }
+
+    /// Synchronize the team. Unlike a barrier this does not complete outstanding RMA.
+    #[track_caller]
+    pub fn sync(self) {
+        self.try_sync().unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    #[track_caller]
+    pub fn try_sync(self) -> Result<(), ShmemError> {
+        check("shmem_team_sync", unsafe { shmem_team_sync(self.inner) })
+    }
+
+    /// Create a team of `size` members, every `stride`-th one starting at
+    /// `start`. Collective over this team.
+    pub fn split_strided(self, start: i32, stride: i32, size: i32) -> Result<Self, ShmemError> {
         let mut team = MaybeUninit::uninit();
         unsafe {
             let result = shmem_team_split_strided(
@@ -35,10 +68,7 @@ impl OsmTeam {
                 0,
                 team.as_mut_ptr(),
             );
-
-            if result != 0 {
-                return Err(TeamCreationError::Fail);
-            }
+            check("shmem_team_split_strided", result)?;
 
             Ok(OsmTeam {
                 inner: team.assume_init(),
@@ -46,19 +76,34 @@ impl OsmTeam {
         }
     }
 
-    pub fn broadcast<T>(self, src: &OsmSlice<T>, dst: &mut OsmSlice<T>, pe_root: i32) {
-        unsafe {
+    #[track_caller]
+    pub fn broadcast<T: ShmemPod>(self, src: &OsmSlice<T>, dst: &mut OsmSlice<T>, pe_root: i32) {
+        self.try_broadcast(src, dst, pe_root)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    #[track_caller]
+    pub fn try_broadcast<T: ShmemPod>(
+        self,
+        src: &OsmSlice<T>,
+        dst: &mut OsmSlice<T>,
+        pe_root: i32,
+    ) -> Result<(), ShmemError> {
+        let bytes = std::mem::size_of::<T>() * src.len();
+        check_size("shmem_broadcastmem", bytes, std::mem::size_of_val::<[T]>(dst))?;
+
+        let result = unsafe {
             shmem_broadcastmem(
                 self.inner,
                 dst.as_mut_ptr().cast(),
                 src.as_ptr().cast(),
-                std::mem::size_of::<T>() * src.len(),
+                bytes,
                 pe_root,
-            );
-        }
+            )
+        };
+        check("shmem_broadcastmem", result)
     }
 }
 
-pub enum TeamCreationError {
-    Fail,
-}
+/// The error of [`OsmTeam::split_strided`].
+pub type TeamCreationError = ShmemError;
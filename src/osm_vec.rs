@@ -4,7 +4,8 @@ use std::{
 };
 
 use crate::{
-    osm_alloc::OsmMalloc, osm_scope::OsmScope, osm_slice::OsmSlice, osm_wrapper::OsmWrapper,
+    osm_alloc::OsmMalloc, osm_error::ShmemError, osm_scope::OsmScope, osm_slice::OsmSlice,
+    osm_wrapper::OsmWrapper,
 };
 
 #[derive(Debug)]
@@ -31,9 +32,32 @@ impl<'a, T> ShVec<'a, T> {
         ShVec { data }
     }
 
+    pub fn try_with_capacity(size: usize, scope: &'a OsmScope) -> Result<Self, ShmemError> {
+        let data = Vec::try_with_capacity_in(size, OsmMalloc::new(scope))
+            .map_err(|_| Self::alloc_error("shmemalign", size))?;
+        Ok(ShVec { data })
+    }
+
     pub fn resize_with(&mut self, size: usize, f: impl Fn() -> T) {
         self.data.resize_with(size, f);
     }
+
+    /// Like [`ShVec::resize_with`], but reports a failed reallocation instead of aborting.
+    pub fn try_resize_with(&mut self, size: usize, f: impl Fn() -> T) -> Result<(), ShmemError> {
+        self.data
+            .try_reserve(size.saturating_sub(self.data.len()))
+            .map_err(|_| Self::alloc_error("shrealloc", size))?;
+        self.data.resize_with(size, f);
+        Ok(())
+    }
+
+    fn alloc_error(operation: &'static str, len: usize) -> ShmemError {
+        crate::osm_error::alloc_error(
+            operation,
+            len.saturating_mul(std::mem::size_of::<T>()),
+            std::mem::align_of::<T>(),
+        )
+    }
 }
 
 impl<'a, T> Deref for ShVec<'a, T> {
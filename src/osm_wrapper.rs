@@ -3,17 +3,52 @@ use std::{
     ffi::c_void,
     fmt::Display,
     ops::{Deref, DerefMut},
+    sync::atomic::AtomicI64,
 };
 
-use openshmem_sys::shmem_putmem;
 use ref_cast::RefCast;
 
+use crate::{
+    osm_error::{ShmemError, check_remote},
+    osm_ffi::{
+        SHMEM_CMP_EQ, SHMEM_CMP_GE, SHMEM_CMP_GT, SHMEM_CMP_LE, SHMEM_CMP_LT, SHMEM_CMP_NE,
+        shmem_getmem, shmem_getmem_nbi, shmem_long_atomic_compare_swap, shmem_long_atomic_fetch,
+        shmem_long_atomic_fetch_add, shmem_long_atomic_set, shmem_long_wait_until, shmem_putmem,
+        shmem_putmem_nbi,
+    },
+    osm_future::NbiHandle,
+    osm_pod::ShmemPod,
+};
+
 #[derive(Debug, RefCast)]
 #[repr(transparent)]
 pub struct OsmWrapper<T> {
     data: T,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShmemCmp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl ShmemCmp {
+    fn as_raw(self) -> i32 {
+        (match self {
+            ShmemCmp::Eq => SHMEM_CMP_EQ,
+            ShmemCmp::Ne => SHMEM_CMP_NE,
+            ShmemCmp::Gt => SHMEM_CMP_GT,
+            ShmemCmp::Ge => SHMEM_CMP_GE,
+            ShmemCmp::Lt => SHMEM_CMP_LT,
+            ShmemCmp::Le => SHMEM_CMP_LE,
+        }) as i32
+    }
+}
+
 impl<T: PartialEq> PartialEq for OsmWrapper<T> {
     fn eq(&self, other: &Self) -> bool {
         self.data == other.data
@@ -40,7 +75,8 @@ impl<T: Display> Display for OsmWrapper<T> {
     }
 }
 
-impl<T> OsmWrapper<T> {
+impl<T: ShmemPod> OsmWrapper<T> {
+    #[track_caller]
     pub fn put_to(&self, target: &mut Self, pe: i32) {
         unsafe {
             shmem_putmem(
@@ -52,20 +88,23 @@ impl<T> OsmWrapper<T> {
         }
     }
 
-    pub fn put_to_nbi(&self, target: &mut Self, pe: i32) {
+    #[track_caller]
+    pub fn put_to_nbi(&self, target: &mut Self, pe: i32) -> NbiHandle {
         unsafe {
-            shmem_putmem(
+            shmem_putmem_nbi(
                 target.deref_mut() as *mut T as *mut c_void,
                 &self.data as *const T as *const c_void,
                 std::mem::size_of::<T>(),
                 pe,
             );
         }
+        NbiHandle::issue()
     }
 
+    #[track_caller]
     pub fn get_from(&mut self, source: &Self, size: usize, pe: i32) {
         unsafe {
-            shmem_putmem(
+            shmem_getmem(
                 &mut self.data as *mut T as *mut c_void,
                 source.deref() as *const T as *const c_void,
                 size,
@@ -74,14 +113,100 @@ impl<T> OsmWrapper<T> {
         }
     }
 
-    pub fn get_from_nbi(&mut self, source: &Self, size: usize, pe: i32) {
+    #[track_caller]
+    pub fn get_from_nbi(&mut self, source: &Self, size: usize, pe: i32) -> NbiHandle {
         unsafe {
-            shmem_putmem(
+            shmem_getmem_nbi(
                 &mut self.data as *mut T as *mut c_void,
                 source.deref() as *const T as *const c_void,
                 size,
                 pe,
             );
         }
+        NbiHandle::issue()
     }
-}
\ No newline at end of file
+
+    /// Read the value of this symmetric object on `pe` into a local copy.
+    #[track_caller]
+    pub fn get_value(&self, pe: i32) -> T
+    where
+        T: Copy,
+    {
+        let mut value = std::mem::MaybeUninit::<T>::uninit();
+        unsafe {
+            shmem_getmem(
+                value.as_mut_ptr() as *mut c_void,
+                &self.data as *const T as *const c_void,
+                std::mem::size_of::<T>(),
+                pe,
+            );
+            value.assume_init()
+        }
+    }
+
+    /// [`OsmWrapper::put_to`] after checking the target PE and that `target`
+    /// is remotely accessible.
+    #[track_caller]
+    pub fn try_put_to(&self, target: &mut Self, pe: i32) -> Result<(), ShmemError> {
+        check_remote("put_to", &target.data as *const T as *const c_void, pe)?;
+        self.put_to(target, pe);
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn try_get_value(&self, pe: i32) -> Result<T, ShmemError>
+    where
+        T: Copy,
+    {
+        check_remote("get_value", &self.data as *const T as *const c_void, pe)?;
+        Ok(self.get_value(pe))
+    }
+}
+
+impl OsmWrapper<i64> {
+    #[track_caller]
+    pub fn compare_and_swap(&mut self, expected: i64, desired: i64, pe: i32) -> i64 {
+        unsafe { shmem_long_atomic_compare_swap(&mut self.data, expected, desired, pe) }
+    }
+
+    #[track_caller]
+    pub fn fetch_add(&mut self, value: i64, pe: i32) -> i64 {
+        unsafe { shmem_long_atomic_fetch_add(&mut self.data, value, pe) }
+    }
+
+    #[track_caller]
+    pub fn atomic_fetch(&self, pe: i32) -> i64 {
+        unsafe { shmem_long_atomic_fetch(&self.data, pe) }
+    }
+
+    #[track_caller]
+    pub fn atomic_set(&mut self, value: i64, pe: i32) {
+        unsafe { shmem_long_atomic_set(&mut self.data, value, pe) }
+    }
+
+    /// Block until the local copy of this variable satisfies `cmp` against `value`.
+    #[track_caller]
+    pub fn wait_until(&mut self, cmp: ShmemCmp, value: i64) {
+        unsafe { shmem_long_wait_until(&mut self.data, cmp.as_raw(), value) }
+    }
+}
+
+/// A symmetric word that local threads read with atomic loads while other PEs
+/// update it with SHMEM atomics. Not `ShmemPod`, so it only moves through
+/// these calls.
+impl OsmWrapper<AtomicI64> {
+    #[track_caller]
+    pub fn compare_and_swap(&self, expected: i64, desired: i64, pe: i32) -> i64 {
+        unsafe { shmem_long_atomic_compare_swap(self.data.as_ptr(), expected, desired, pe) }
+    }
+
+    #[track_caller]
+    pub fn atomic_fetch(&self, pe: i32) -> i64 {
+        unsafe { shmem_long_atomic_fetch(self.data.as_ptr(), pe) }
+    }
+
+    #[track_caller]
+    pub fn atomic_set(&self, value: i64, pe: i32) {
+        unsafe { shmem_long_atomic_set(self.data.as_ptr(), value, pe) }
+    }
+}
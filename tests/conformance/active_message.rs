@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+
+use openshmem_benchmark::{osm_active_message::ActiveMessages, osm_scope::OsmScope};
+
+use crate::{next_pe, prev_pe};
+
+tests![round_trip_through_progress];
+
+const PING: u32 = 1;
+const PONG: u32 = 2;
+const ROUNDS: u32 = 8;
+
+fn round_trip_through_progress(scope: &OsmScope) {
+    // handlers cannot send, so pings are answered after `progress` returns
+    let pings = RefCell::new(Vec::new());
+    let pongs = RefCell::new(Vec::new());
+    // two slots per source, so the pings wrap around the rings
+    let mut messages = ActiveMessages::new(2, 8, scope);
+    messages.register(PING, |source, payload| {
+        pings.borrow_mut().push((source, payload.to_vec()))
+    });
+    messages.register(PONG, |source, payload| {
+        pongs.borrow_mut().push((source, payload.to_vec()))
+    });
+    scope.barrier_all();
+
+    for round in 0..ROUNDS {
+        messages.send(next_pe(scope), PING, &round.to_ne_bytes());
+    }
+    let mut answered = 0;
+    while pongs.borrow().len() < ROUNDS as usize || answered < ROUNDS {
+        messages.progress();
+        for (source, payload) in pings.take() {
+            assert_eq!(source, prev_pe(scope));
+            messages.send(source, PONG, &payload);
+            answered += 1;
+        }
+    }
+    messages.quiesce(scope);
+
+    let pongs = pongs.take();
+    assert_eq!(pongs.len(), ROUNDS as usize);
+    for (round, (source, payload)) in (0..ROUNDS).zip(pongs) {
+        assert_eq!(source, next_pe(scope));
+        assert_eq!(payload, round.to_ne_bytes());
+    }
+    assert!(pings.borrow().is_empty());
+}
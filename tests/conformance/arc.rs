@@ -0,0 +1,25 @@
+use openshmem_benchmark::{osm_arc::OsmArc, osm_scope::OsmScope, osm_wrapper::OsmWrapper};
+
+use crate::{next_pe, pattern};
+
+tests![new_and_deref, try_new, remote_source];
+
+fn new_and_deref(scope: &OsmScope) {
+    let arc = OsmArc::new(5i64, scope);
+    let wrapper: &OsmWrapper<i64> = &arc;
+    assert_eq!(**wrapper, 5);
+}
+
+fn try_new(scope: &OsmScope) {
+    let arc = OsmArc::try_new([1u32, 2, 3], scope).unwrap();
+    assert_eq!(**arc, [1, 2, 3]);
+}
+
+fn remote_source(scope: &OsmScope) {
+    let arc = OsmArc::new(pattern(scope.my_pe(), 0) as i64, scope);
+    scope.barrier_all();
+
+    let expected = pattern(next_pe(scope), 0) as i64;
+    assert_eq!(arc.get_value(next_pe(scope)), expected);
+    assert_eq!(arc.atomic_fetch(next_pe(scope)), expected);
+}
@@ -0,0 +1,244 @@
+use openshmem_benchmark::{
+    osm_array::{SymArray2, SymArray3},
+    osm_scope::OsmScope,
+};
+
+use crate::{next_pe, pattern, prev_pe};
+
+tests![
+    layout,
+    views,
+    empty_split,
+    put_row,
+    put_column,
+    get_column,
+    put_box,
+    halo_exchange,
+    put_faces,
+    odd_element_size,
+];
+
+const ROWS: usize = 5;
+const COLS: usize = 7;
+
+/// A `ROWS` x `COLS` array of [`pattern`] values in row-major order.
+fn filled(scope: &OsmScope) -> SymArray2<'_, u64> {
+    let mut array = SymArray2::new([ROWS, COLS], 0, scope);
+    let me = scope.my_pe();
+    array
+        .view_mut([0..ROWS, 0..COLS])
+        .copy_from((0..ROWS * COLS).map(|i| pattern(me, i)));
+    scope.barrier_all();
+    array
+}
+
+fn layout(scope: &OsmScope) {
+    let array = filled(scope);
+    let me = scope.my_pe();
+    assert_eq!(array.shape(), [ROWS, COLS]);
+    assert_eq!(*array[[2, 3]], pattern(me, 2 * COLS + 3));
+    assert_eq!(array.as_slice().len(), ROWS * COLS);
+    assert_eq!(*array.as_slice()[COLS], pattern(me, COLS));
+}
+
+fn views(scope: &OsmScope) {
+    let mut array = filled(scope);
+    let me = scope.my_pe();
+
+    let row = array.row(1);
+    assert_eq!(row.shape(), [COLS]);
+    assert_eq!(
+        row.to_vec(),
+        (COLS..2 * COLS).map(|i| pattern(me, i)).collect::<Vec<_>>()
+    );
+
+    let column = array.column(2);
+    assert_eq!(
+        column.to_vec(),
+        (0..ROWS)
+            .map(|r| pattern(me, r * COLS + 2))
+            .collect::<Vec<_>>()
+    );
+
+    let view = array.view([1..3, 4..6]);
+    assert_eq!(*view[[1, 1]], pattern(me, 2 * COLS + 5));
+    assert!(array.view([2..2, 0..COLS]).is_empty());
+
+    *array.column_mut(0)[[4]] = 0;
+    assert_eq!(*array[[4, 0]], 0);
+}
+
+fn empty_split(scope: &OsmScope) {
+    let mut array = filled(scope);
+
+    // an empty range on either side overlaps nothing
+    let (read, write) = array.split_mut([0..ROWS, 0..COLS], [2..2, 0..COLS]);
+    assert_eq!(read.len(), ROWS * COLS);
+    assert!(write.is_empty());
+    let (read, write) = array.split_mut([0..ROWS, 3..3], [0..ROWS, 0..COLS]);
+    assert!(read.is_empty());
+    assert_eq!(write.len(), ROWS * COLS);
+}
+
+fn put_row(scope: &OsmScope) {
+    let mut array = filled(scope);
+    let (me, prev) = (scope.my_pe(), prev_pe(scope));
+
+    // row 0 to row 4 of the next PE, contiguous
+    let (row, mut target) = array.split_mut([0..1, 0..COLS], [4..5, 0..COLS]);
+    row.put_to(&mut target, next_pe(scope));
+    scope.barrier_all();
+
+    assert_eq!(
+        array.row(4).to_vec(),
+        (0..COLS).map(|i| pattern(prev, i)).collect::<Vec<_>>()
+    );
+    assert_eq!(*array[[3, 0]], pattern(me, 3 * COLS));
+}
+
+fn put_column(scope: &OsmScope) {
+    let mut array = filled(scope);
+    let (me, prev) = (scope.my_pe(), prev_pe(scope));
+
+    // column 1 to column 6 of the next PE, strided
+    let (column, mut target) = array.split_mut([0..ROWS, 1..2], [0..ROWS, 6..7]);
+    column.put_to(&mut target, next_pe(scope));
+    scope.barrier_all();
+
+    for r in 0..ROWS {
+        assert_eq!(*array[[r, 6]], pattern(prev, r * COLS + 1));
+        assert_eq!(*array[[r, 5]], pattern(me, r * COLS + 5));
+    }
+}
+
+fn get_column(scope: &OsmScope) {
+    let mut array = filled(scope);
+    let next = next_pe(scope);
+
+    let (column, mut target) = array.split_mut([0..ROWS, 3..4], [0..ROWS, 0..1]);
+    target.get_from(&column, next);
+    scope.barrier_all();
+
+    for r in 0..ROWS {
+        assert_eq!(*array[[r, 0]], pattern(next, r * COLS + 3));
+    }
+}
+
+fn put_box(scope: &OsmScope) {
+    let mut array = filled(scope);
+    let (me, prev) = (scope.my_pe(), prev_pe(scope));
+
+    // full-width rows merge into one run, a narrower box is one run per row
+    let (rows, mut target) = array.split_mut([0..2, 0..COLS], [3..5, 0..COLS]);
+    rows.put_to(&mut target, next_pe(scope));
+    scope.barrier_all();
+    let (inner, mut target) = array.split_mut([1..3, 1..4], [1..3, 4..7]);
+    inner.put_to(&mut target, next_pe(scope));
+    scope.barrier_all();
+
+    for r in 0..ROWS {
+        for c in 0..COLS {
+            let expected = match (r, c) {
+                (3..5, _) => pattern(prev, (r - 3) * COLS + c),
+                (1..3, 4..7) => pattern(prev, r * COLS + c - 3),
+                _ => pattern(me, r * COLS + c),
+            };
+            assert_eq!(*array[[r, c]], expected, "element [{r}, {c}]");
+        }
+    }
+}
+
+fn halo_exchange(scope: &OsmScope) {
+    // rows 1..=INTERIOR are owned, rows 0 and INTERIOR + 1 are ghosts
+    const INTERIOR: usize = 3;
+    let me = scope.my_pe();
+    let (prev, next) = (prev_pe(scope), next_pe(scope));
+    let mut grid = SymArray2::new([INTERIOR + 2, COLS], 0u64, scope);
+    for r in 1..=INTERIOR {
+        grid.row_mut(r)
+            .copy_from((0..COLS).map(|c| pattern(me, r * COLS + c)));
+    }
+    scope.barrier_all();
+
+    let (last, mut ghost) = grid.split_mut([INTERIOR..INTERIOR + 1, 0..COLS], [0..1, 0..COLS]);
+    last.put_to(&mut ghost, next);
+    let (first, mut ghost) = grid.split_mut([1..2, 0..COLS], [INTERIOR + 1..INTERIOR + 2, 0..COLS]);
+    first.put_to(&mut ghost, prev);
+    scope.barrier_all();
+
+    for c in 0..COLS {
+        assert_eq!(*grid[[0, c]], pattern(prev, INTERIOR * COLS + c));
+        assert_eq!(*grid[[INTERIOR + 1, c]], pattern(next, COLS + c));
+    }
+}
+
+fn put_faces(scope: &OsmScope) {
+    const SHAPE: [usize; 3] = [3, 4, 5];
+    let (me, prev) = (scope.my_pe(), prev_pe(scope));
+    let flat = |[z, y, x]: [usize; 3]| (z * SHAPE[1] + y) * SHAPE[2] + x;
+    let new_cube = || {
+        let mut cube = SymArray3::new(SHAPE, 0u64, scope);
+        cube.view_mut(SHAPE.map(|len| 0..len))
+            .copy_from((0..SHAPE.iter().product()).map(|i| pattern(me, i)));
+        scope.barrier_all();
+        cube
+    };
+
+    // the x face is strided along both of its axes
+    let mut cube = new_cube();
+    assert_eq!(cube.face(2, 1).shape(), [3, 4]);
+    assert_eq!(*cube.face(2, 1)[[2, 3]], pattern(me, flat([2, 3, 1])));
+    *cube.face_mut(0, 2)[[3, 4]] = 0;
+    assert_eq!(*cube[[2, 3, 4]], 0);
+    drop(cube);
+
+    // the first face along each axis to the last one of the next PE
+    for axis in 0..3 {
+        let mut cube = new_cube();
+        let last = SHAPE[axis] - 1;
+        let (mut read, mut write) = (SHAPE.map(|len| 0..len), SHAPE.map(|len| 0..len));
+        read[axis] = 0..1;
+        write[axis] = last..last + 1;
+        let (face, mut ghost) = cube.split_mut(read, write);
+        face.put_to(&mut ghost, next_pe(scope));
+        scope.barrier_all();
+
+        for z in 0..SHAPE[0] {
+            for y in 0..SHAPE[1] {
+                for x in 0..SHAPE[2] {
+                    let at = [z, y, x];
+                    let expected = if at[axis] == last {
+                        let mut source = at;
+                        source[axis] = 0;
+                        pattern(prev, flat(source))
+                    } else {
+                        pattern(me, flat(at))
+                    };
+                    assert_eq!(*cube[at], expected, "axis {axis} at {at:?}");
+                }
+            }
+        }
+        scope.barrier_all();
+    }
+}
+
+fn odd_element_size(scope: &OsmScope) {
+    // no strided call moves 3-byte elements, so they move one at a time
+    let me = scope.my_pe() as u8;
+    let mut array = SymArray2::new([4, 3], [0u8; 3], scope);
+    for r in 0..4 {
+        for c in 0..3 {
+            *array[[r, c]] = [me, r as u8, c as u8];
+        }
+    }
+    scope.barrier_all();
+
+    let (column, mut target) = array.split_mut([0..4, 0..1], [0..4, 2..3]);
+    column.put_to(&mut target, next_pe(scope));
+    scope.barrier_all();
+
+    let prev = prev_pe(scope) as u8;
+    for r in 0..4 {
+        assert_eq!(*array[[r, 2]], [prev, r as u8, 0]);
+    }
+}
@@ -0,0 +1,30 @@
+use openshmem_benchmark::{osm_box::OsmBox, osm_scope::OsmScope, osm_wrapper::OsmWrapper};
+
+use crate::{next_pe, pattern, prev_pe};
+
+tests![new_and_deref, try_new, remote_target];
+
+fn new_and_deref(scope: &OsmScope) {
+    let mut boxed = OsmBox::new(5i64, scope);
+    assert_eq!(**boxed, 5);
+
+    **boxed = 7;
+    let wrapper: &OsmWrapper<i64> = &boxed;
+    assert_eq!(**wrapper, 7);
+}
+
+fn try_new(scope: &OsmScope) {
+    let boxed = OsmBox::try_new([1u32, 2, 3], scope).unwrap();
+    assert_eq!(**boxed, [1, 2, 3]);
+}
+
+fn remote_target(scope: &OsmScope) {
+    let src = OsmBox::new(pattern(scope.my_pe(), 0), scope);
+    let mut dst = OsmBox::new(0u64, scope);
+    scope.barrier_all();
+
+    src.put_to(&mut dst, next_pe(scope));
+    scope.barrier_all();
+
+    assert_eq!(**dst, pattern(prev_pe(scope), 0));
+}
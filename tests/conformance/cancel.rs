@@ -0,0 +1,66 @@
+use openshmem_benchmark::{
+    osm_cancel::{CancelReason, Cancellation, GlobalCancel},
+    osm_scope::OsmScope,
+    osm_team::OsmTeam,
+};
+
+use crate::sym_vec;
+
+tests![first_cancel_wins, trigger_delivered_by_poll];
+
+/// The cancellation every PE has seen, checked to be the same on all of them.
+fn agreed(cancel: &GlobalCancel, scope: &OsmScope) -> Cancellation {
+    let cancellation = cancel.cancelled().expect("not cancelled after the barrier");
+    let src = sym_vec(scope, [cancellation.pe]);
+    let mut all = sym_vec(scope, vec![-1; scope.num_pes() as usize]);
+    OsmTeam::world().fcollect(&src, &mut all);
+    assert!(
+        all.iter().all(|&pe| pe == cancellation.pe),
+        "{:?}",
+        all.to_vec()
+    );
+    cancellation
+}
+
+fn first_cancel_wins(scope: &OsmScope) {
+    let mut cancel = GlobalCancel::new(scope);
+    assert_eq!(cancel.poll(), None);
+    scope.barrier_all();
+
+    // every PE races to cancel, exactly one of them wins
+    cancel.cancel(CancelReason::Requested);
+    scope.barrier_all();
+    let cancellation = agreed(&cancel, scope);
+    assert!((0..scope.num_pes()).contains(&cancellation.pe));
+    assert_eq!(cancellation.reason, CancelReason::Requested);
+
+    // later cancellations do not replace the first one
+    cancel.cancel(CancelReason::Timeout);
+    scope.barrier_all();
+    assert_eq!(agreed(&cancel, scope), cancellation);
+}
+
+fn trigger_delivered_by_poll(scope: &OsmScope) {
+    let mut cancel = GlobalCancel::new(scope);
+    let last = scope.num_pes() - 1;
+
+    if scope.my_pe() == last {
+        let trigger = cancel.trigger();
+        std::thread::spawn(move || trigger.cancel(CancelReason::Timeout))
+            .join()
+            .unwrap();
+        // a trigger only marks the cancellation pending on its PE
+        assert_eq!(cancel.cancelled(), None);
+    }
+    // poll at the end of the iteration, check after the next barrier
+    cancel.poll();
+    scope.barrier_all();
+
+    assert_eq!(
+        agreed(&cancel, scope),
+        Cancellation {
+            pe: last,
+            reason: CancelReason::Timeout,
+        }
+    );
+}
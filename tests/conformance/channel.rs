@@ -0,0 +1,38 @@
+use openshmem_benchmark::{osm_channel::ShmemChannel, osm_scope::OsmScope};
+
+use crate::pattern;
+
+tests![wrap_around_with_flow_control];
+
+const CAPACITY: usize = 4;
+const MESSAGES: usize = 16 * CAPACITY;
+
+fn wrap_around_with_flow_control(scope: &OsmScope) {
+    let (sender, receiver) = (0, scope.num_pes() - 1);
+    let mut channel = ShmemChannel::<u64>::new(sender, receiver, CAPACITY, scope);
+    let me = scope.my_pe();
+    assert_eq!(channel.capacity(), CAPACITY);
+
+    if sender == receiver {
+        // a single PE cannot wait for its own acknowledgements
+        for i in 0..MESSAGES {
+            channel.send(pattern(sender, i));
+            assert_eq!(channel.recv(), pattern(sender, i));
+        }
+    } else if me == sender {
+        // 16 rings' worth, so the sender wraps around and has to wait for the
+        // receiver's acknowledgements
+        for i in 0..MESSAGES {
+            channel.send(pattern(sender, i));
+        }
+    } else if me == receiver {
+        for i in 0..MESSAGES {
+            assert_eq!(channel.recv(), pattern(sender, i), "message {i}");
+        }
+    }
+
+    if me == receiver {
+        assert_eq!(channel.try_recv(), None);
+    }
+    scope.barrier_all();
+}
@@ -0,0 +1,48 @@
+use std::{sync::OnceLock, time::Duration};
+
+use openshmem_benchmark::{
+    osm_clock::{Clock, ClockOffset},
+    osm_scope::OsmScope,
+};
+
+tests![now_ns_is_monotonic, offset_within_round_trip];
+
+/// One calibration per process, so simulated PEs all read the same clock.
+fn clock() -> &'static Clock {
+    static CLOCK: OnceLock<Clock> = OnceLock::new();
+    CLOCK.get_or_init(|| Clock::calibrate_for(Duration::from_millis(5)))
+}
+
+fn now_ns_is_monotonic(_scope: &OsmScope) {
+    let clock = clock();
+    assert!(clock.frequency() > 0.0);
+
+    let mut last = clock.now_ns();
+    for _ in 0..1000 {
+        let now = clock.now_ns();
+        assert!(now >= last, "{now} < {last}");
+        last = now;
+    }
+    assert_eq!(clock.ticks_to_duration(0), Duration::ZERO);
+}
+
+fn offset_within_round_trip(scope: &OsmScope) {
+    let clock = clock();
+    let offset = ClockOffset::estimate_with(clock, 20, scope);
+
+    if scope.my_pe() == 0 {
+        assert_eq!(offset.offset_ns, 0);
+    } else {
+        assert!(offset.round_trip_ns >= 0);
+    }
+    // only a shared clock bounds the offset, real PEs may be on other nodes
+    #[cfg(feature = "sim")]
+    assert!(offset.offset_ns.abs() <= offset.round_trip_ns, "{offset:?}");
+
+    let mut last = offset.to_reference(clock.now_ns());
+    for _ in 0..1000 {
+        let now = offset.to_reference(clock.now_ns());
+        assert!(now >= last, "{now} < {last}");
+        last = now;
+    }
+}
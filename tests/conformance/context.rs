@@ -0,0 +1,119 @@
+use openshmem_benchmark::{
+    osm_context::{ContextOptions, OsmContext},
+    osm_error::ShmemError,
+    osm_scope::OsmScope,
+};
+
+use crate::{next_pe, pattern, prev_pe, sym_vec};
+
+tests![put_get, nbi, try_put_to, try_get_from];
+
+fn put_get(scope: &OsmScope) {
+    let ctx = OsmContext::new(ContextOptions::default(), scope);
+    let src = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8]);
+    let mut local = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    ctx.put_to(&src, &mut dst, next_pe(scope));
+    ctx.quiet();
+    scope.barrier_all();
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+
+    ctx.get_from(&mut local, &src, next_pe(scope));
+    let next = next_pe(scope);
+    assert!(
+        local
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(next, i))
+    );
+}
+
+fn nbi(scope: &OsmScope) {
+    let ctx = OsmContext::new(ContextOptions::default(), scope);
+    let src = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    let handle = ctx.put_to_nbi(&src, &mut dst, next_pe(scope));
+    // the default context's quiet does not complete the context's RMA
+    scope.quiet();
+    assert!(!handle.is_complete());
+    // completing the context's own RMA is enough for the barrier
+    ctx.quiet();
+    assert!(handle.is_complete());
+    scope.barrier_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+
+    let mut local = sym_vec(scope, vec![0u64; 8]);
+    let handle = ctx.get_from_nbi(&mut local, &dst, next_pe(scope));
+    handle.wait();
+    assert!(handle.is_complete());
+    assert!(
+        local
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(scope.my_pe(), i))
+    );
+    scope.barrier_all();
+}
+
+fn try_put_to(scope: &OsmScope) {
+    let ctx = OsmContext::try_new(ContextOptions::default(), scope).unwrap();
+    let src = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    ctx.try_put_to(&src, &mut dst, next_pe(scope)).unwrap();
+    let err = ctx
+        .try_put_to(&src, &mut dst[..4], next_pe(scope))
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ShmemError::Size {
+                expected: 64,
+                actual: 32,
+                ..
+            }
+        ),
+        "{err}"
+    );
+    let err = ctx
+        .try_put_to_nbi(&src, &mut dst, scope.num_pes())
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+    ctx.quiet();
+    scope.barrier_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn try_get_from(scope: &OsmScope) {
+    let ctx = OsmContext::try_new(ContextOptions::default(), scope).unwrap();
+    let remote = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut local = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    ctx.try_get_from(&mut local, &remote, next_pe(scope))
+        .unwrap();
+    let err = ctx
+        .try_get_from_nbi(&mut local, &remote[..7], next_pe(scope))
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { .. }), "{err}");
+    let err = ctx.try_get_from(&mut local, &remote, -1).unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+
+    let next = next_pe(scope);
+    assert!(
+        local
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(next, i))
+    );
+}
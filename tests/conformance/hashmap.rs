@@ -0,0 +1,54 @@
+use openshmem_benchmark::{
+    osm_hashmap::{DistHashMap, DistHashMapError},
+    osm_scope::OsmScope,
+};
+
+use crate::pattern;
+
+tests![insert_get_across_owners, full];
+
+const KEYS_PER_PE: u64 = 32;
+
+fn insert_get_across_owners(scope: &OsmScope) {
+    let mut map = DistHashMap::<u64, u64>::new(64, scope);
+    let me = scope.my_pe();
+    let keys = |pe: i32| (0..KEYS_PER_PE).map(move |i| pe as u64 * KEYS_PER_PE + i);
+
+    for key in keys(me) {
+        map.insert(key, pattern(me, key as usize)).unwrap();
+    }
+    scope.barrier_all();
+
+    for pe in 0..scope.num_pes() {
+        for key in keys(pe) {
+            assert_eq!(map.get(&key), Some(pattern(pe, key as usize)), "key {key}");
+        }
+    }
+    let absent = scope.num_pes() as u64 * KEYS_PER_PE;
+    assert_eq!(map.get(&absent), None);
+    assert!(!map.contains_key(&absent));
+
+    // with several PEs, some keys land on another PE than their inserter
+    if scope.num_pes() > 1 {
+        assert!(keys(me).any(|key| map.owner_of(&key) != me));
+    }
+}
+
+fn full(scope: &OsmScope) {
+    let mut map = DistHashMap::<u64, u64>::new(2, scope);
+    scope.barrier_all();
+
+    if scope.my_pe() == 0 {
+        // a key only ever probes the buckets of its owner
+        let keys: Vec<u64> = (0..).filter(|key| map.owner_of(key) == 0).take(3).collect();
+        for &key in &keys[..2] {
+            map.insert(key, key).unwrap();
+        }
+        assert_eq!(map.insert(keys[0], 7), Ok(()));
+        assert_eq!(map.insert(keys[2], 0), Err(DistHashMapError::Full));
+        // an overwrite is a plain put, complete it before reading it back
+        scope.quiet();
+        assert_eq!(map.get(&keys[0]), Some(7));
+    }
+    scope.barrier_all();
+}
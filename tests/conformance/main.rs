@@ -0,0 +1,196 @@
+//! Conformance tests for the SHMEM wrappers.
+//!
+//! Every PE runs every test in the same order, with a barrier before and
+//! after each one, and PE 0 reports progress. A failed assertion panics,
+//! which tears down all PEs with a non-zero exit status. Tests work with any
+//! number of PEs, but only exercise remote paths with two or more.
+//!
+//! Against the real runtime, start the suite through the launcher:
+//!
+//! ```text
+//! nu run.nu conformance 4
+//! ```
+//!
+//! With the `sim` feature it runs on simulated PEs, once with the race checker
+//! and once with delayed non-blocking RMA and stalled barriers:
+//!
+//! ```text
+//! cargo test --no-default-features --features sim --test conformance
+//! ```
+//!
+//! Adding the `record` feature also checks the traces it writes against the
+//! format `trace-execution` reads.
+//!
+//! An argument restricts the run to tests whose name contains it.
+
+use openshmem_benchmark::{
+    osm_ffi::{
+        _SHMEM_REDUCE_SYNC_SIZE, _SHMEM_SYNC_VALUE, SHMEM_BARRIER_SYNC_SIZE,
+        SHMEM_COLLECT_SYNC_SIZE,
+    },
+    osm_scope::OsmScope,
+    osm_vec::ShVec,
+};
+
+type Test = (&'static str, fn(&OsmScope));
+
+macro_rules! tests {
+    ($($test:ident),* $(,)?) => {
+        pub const TESTS: &[crate::Test] = &[
+            $((concat!(module_path!(), "::", stringify!($test)), $test)),*
+        ];
+    };
+}
+
+mod active_message;
+mod arc;
+mod array;
+mod boxed;
+mod cancel;
+mod channel;
+mod clock;
+mod context;
+mod hashmap;
+mod object;
+mod queue;
+#[cfg(feature = "record")]
+mod record;
+mod scope;
+mod slice;
+mod symmetric_static;
+mod team;
+mod vec;
+mod wrapper;
+
+const SUITES: &[&[Test]] = &[
+    scope::TESTS,
+    vec::TESTS,
+    boxed::TESTS,
+    arc::TESTS,
+    wrapper::TESTS,
+    symmetric_static::TESTS,
+    slice::TESTS,
+    context::TESTS,
+    team::TESTS,
+    array::TESTS,
+    hashmap::TESTS,
+    queue::TESTS,
+    channel::TESTS,
+    active_message::TESTS,
+    object::TESTS,
+    cancel::TESTS,
+    clock::TESTS,
+    #[cfg(feature = "record")]
+    record::TESTS,
+];
+
+fn run_all(scope: &OsmScope, filter: Option<&str>) {
+    let mut passed = 0;
+    for &(name, test) in SUITES.iter().copied().flatten() {
+        if filter.is_some_and(|filter| !name.contains(filter)) {
+            continue;
+        }
+
+        scope.barrier_all();
+        test(scope);
+        scope.barrier_all();
+
+        if scope.my_pe() == 0 {
+            println!("test {name} ... ok");
+        }
+        passed += 1;
+    }
+
+    if scope.my_pe() == 0 {
+        println!(
+            "test result: ok. {passed} passed on {} PEs",
+            scope.num_pes()
+        );
+    }
+}
+
+fn filter() -> Option<String> {
+    std::env::args().skip(1).find(|arg| !arg.starts_with('-'))
+}
+
+#[cfg(not(feature = "sim"))]
+fn main() {
+    let filter = filter();
+    openshmem_benchmark::osm_scope::shmem_scope(|scope| run_all(&scope, filter.as_deref()));
+}
+
+#[cfg(feature = "sim")]
+fn main() {
+    use openshmem_benchmark::osm_sim::{self, FaultConfig, SimConfig};
+
+    let filter = filter();
+    let num_pes = std::env::var("CONFORMANCE_PES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(4);
+
+    let checked = SimConfig {
+        num_pes,
+        check_races: true,
+        ..Default::default()
+    };
+    let report = osm_sim::run(checked, |scope| run_all(&scope, filter.as_deref()));
+    assert!(
+        report.races.is_empty(),
+        "{} data races between the tests' SHMEM calls",
+        report.races.len()
+    );
+
+    let perturbed = SimConfig {
+        num_pes,
+        faults: FaultConfig {
+            seed: 42,
+            delay_nbi: true,
+            stall_pes: vec![num_pes as i32 - 1],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    osm_sim::run(perturbed, |scope| run_all(&scope, filter.as_deref()));
+}
+
+// helpers shared by the test modules
+
+/// Value of element `index` on `pe`, distinct across PEs and elements.
+pub fn pattern(pe: i32, index: usize) -> u64 {
+    ((pe as u64) << 32) | index as u64
+}
+
+pub fn next_pe(scope: &OsmScope) -> i32 {
+    (scope.my_pe() + 1) % scope.num_pes()
+}
+
+pub fn prev_pe(scope: &OsmScope) -> i32 {
+    (scope.my_pe() + scope.num_pes() - 1) % scope.num_pes()
+}
+
+/// Allocate a symmetric vector holding `values`. Collective.
+pub fn sym_vec<'a, T>(scope: &'a OsmScope, values: impl IntoIterator<Item = T>) -> ShVec<'a, T> {
+    let values: Vec<T> = values.into_iter().collect();
+    let mut vec = ShVec::with_capacity(values.len(), scope);
+    for value in values {
+        vec.push(value);
+    }
+    vec
+}
+
+/// Symmetric `pSync` array for the active-set collectives, ready for use on
+/// every PE once this returns. Collective.
+pub fn p_sync(scope: &OsmScope) -> ShVec<'_, i64> {
+    let len = [
+        SHMEM_BARRIER_SYNC_SIZE,
+        SHMEM_COLLECT_SYNC_SIZE,
+        _SHMEM_REDUCE_SYNC_SIZE,
+    ]
+    .into_iter()
+    .max()
+    .unwrap() as usize;
+    let p_sync = sym_vec(scope, (0..len).map(|_| _SHMEM_SYNC_VALUE as i64));
+    scope.barrier_all();
+    p_sync
+}
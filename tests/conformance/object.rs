@@ -0,0 +1,67 @@
+use openshmem_benchmark::{osm_object::ObjectBuffer, osm_scope::OsmScope};
+use serde::{Deserialize, Serialize};
+
+use crate::{next_pe, prev_pe};
+
+tests![put_value_to_next, put_value_many_to_one, broadcast_value];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Message {
+    from: i32,
+    text: String,
+    values: Vec<u64>,
+}
+
+fn message(from: i32) -> Message {
+    Message {
+        from,
+        text: format!("hello from PE {from}"),
+        values: (0..from as u64 + 3).collect(),
+    }
+}
+
+fn put_value_to_next(scope: &OsmScope) {
+    let mut buffer = ObjectBuffer::new(256, scope);
+    assert_eq!(buffer.capacity(), 256);
+
+    buffer.put_value(&message(scope.my_pe()), next_pe(scope));
+    assert_eq!(buffer.get_value::<Message>(), message(prev_pe(scope)));
+    assert_eq!(buffer.try_get_value::<Message>(), None);
+    scope.barrier_all();
+}
+
+fn put_value_many_to_one(scope: &OsmScope) {
+    // the inbox holds one message, so the senders take turns
+    let mut buffer = ObjectBuffer::new(256, scope);
+    if scope.my_pe() == 0 {
+        let mut senders: Vec<i32> = (1..scope.num_pes())
+            .map(|_| {
+                let received = buffer.get_value::<Message>();
+                assert_eq!(received, message(received.from));
+                received.from
+            })
+            .collect();
+        senders.sort();
+        assert_eq!(senders, (1..scope.num_pes()).collect::<Vec<_>>());
+    } else {
+        buffer.put_value(&message(scope.my_pe()), 0);
+    }
+    scope.barrier_all();
+}
+
+fn broadcast_value(scope: &OsmScope) {
+    let mut buffer = ObjectBuffer::new(256, scope);
+    let root = scope.num_pes() - 1;
+
+    let value = (scope.my_pe() == root).then(|| message(root));
+    assert_eq!(
+        buffer.broadcast_value(value.as_ref(), root, scope),
+        message(root)
+    );
+    // a second broadcast from another root reuses the buffer
+    let value = (scope.my_pe() == 0).then(|| vec![String::from("again"); 3]);
+    assert_eq!(
+        buffer.broadcast_value(value.as_ref(), 0, scope),
+        vec!["again"; 3]
+    );
+}
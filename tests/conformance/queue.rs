@@ -0,0 +1,52 @@
+use openshmem_benchmark::{osm_queue::DistQueue, osm_scope::OsmScope, osm_team::OsmTeam};
+
+use crate::{pattern, sym_vec};
+
+tests![concurrent_enqueue_dequeue, try_on_full_and_empty];
+
+const PER_PE: usize = 16;
+
+fn concurrent_enqueue_dequeue(scope: &OsmScope) {
+    // fewer slots than values, so tickets wrap around the ring several times
+    let num_pes = scope.num_pes() as usize;
+    let mut queue = DistQueue::<u64>::new(num_pes, scope);
+    let me = scope.my_pe();
+
+    // every PE has enqueued before it dequeues, so the ring never overflows
+    let mut received = sym_vec(scope, vec![0u64; PER_PE]);
+    for i in 0..PER_PE {
+        queue.enqueue(pattern(me, i), 0);
+        *received[i] = queue.dequeue(0);
+    }
+    scope.barrier_all();
+    assert!(queue.is_empty(0));
+
+    let mut all = sym_vec(scope, vec![0u64; PER_PE * num_pes]);
+    OsmTeam::world().fcollect(&received, &mut all);
+    let mut all = all.to_vec();
+    all.sort();
+    let mut sent: Vec<_> = (0..scope.num_pes())
+        .flat_map(|pe| (0..PER_PE).map(move |i| pattern(pe, i)))
+        .collect();
+    sent.sort();
+    assert_eq!(all, sent);
+}
+
+fn try_on_full_and_empty(scope: &OsmScope) {
+    // every PE works on the ring it hosts
+    let mut queue = DistQueue::<u64>::new(2, scope);
+    let me = scope.my_pe();
+
+    assert_eq!(queue.try_dequeue(me), None);
+    queue.try_enqueue(1, me).unwrap();
+    queue.try_enqueue(2, me).unwrap();
+    assert_eq!(queue.len(me), 2);
+    assert_eq!(queue.try_enqueue(3, me), Err(3));
+
+    assert_eq!(queue.try_dequeue(me), Some(1));
+    queue.try_enqueue(3, me).unwrap();
+    assert_eq!(queue.dequeue(me), 2);
+    assert_eq!(queue.dequeue(me), 3);
+    assert!(queue.is_empty(me));
+    scope.barrier_all();
+}
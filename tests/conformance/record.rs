@@ -0,0 +1,72 @@
+use std::fs::File;
+
+use openshmem_benchmark::{osm_box::OsmBox, osm_record::Recording, osm_scope::OsmScope};
+use serde::Deserialize;
+
+use crate::{next_pe, pattern, sym_vec};
+
+// the rows must parse the way the replay reads them
+#[path = "../../src/bin/trace-execution/operations.rs"]
+#[allow(dead_code)]
+mod operations;
+
+use operations::{Operation, OperationType};
+
+tests![trace_rows];
+
+#[derive(Deserialize)]
+struct CallSite {
+    call_site: String,
+}
+
+fn trace_rows(scope: &OsmScope) {
+    let (me, next) = (scope.my_pe(), next_pe(scope));
+    let src = sym_vec(scope, (0..8).map(|i| pattern(me, i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8]);
+    let mut dst_nbi = sym_vec(scope, vec![0u64; 8]);
+    let mut flag = OsmBox::new(0i64, scope);
+    let prefix = std::env::temp_dir().join(format!("osm_conformance_{}", std::process::id()));
+    scope.barrier_all();
+
+    let recording = Recording::start(&prefix, scope).unwrap();
+    src.put_to(&mut dst, next);
+    src.put_to_nbi(&mut dst_nbi, next);
+    scope.quiet();
+    scope.barrier_all();
+    flag.atomic_set(1, next);
+    let path = recording.path().to_owned();
+    recording.finish().unwrap();
+    scope.barrier_all();
+
+    let operations = csv::Reader::from_reader(File::open(&path).unwrap())
+        .deserialize()
+        .collect::<Result<Vec<Operation>, _>>()
+        .unwrap();
+    let rows = operations
+        .iter()
+        .map(|op| (op.op_type, op.src, op.dst, op.size))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        [
+            (OperationType::Put, me, next, 64),
+            (OperationType::PutNonBlocking, me, next, 64),
+            (OperationType::Quiet, -1, -1, 0),
+            (OperationType::Barrier, -1, -1, 0),
+            (OperationType::AtomicSet, me, next, 8),
+        ]
+    );
+
+    let call_sites = csv::Reader::from_reader(File::open(&path).unwrap())
+        .deserialize()
+        .collect::<Result<Vec<CallSite>, _>>()
+        .unwrap();
+    for row in &call_sites {
+        assert!(
+            row.call_site.starts_with(file!()),
+            "call site {}",
+            row.call_site
+        );
+    }
+    std::fs::remove_file(path).unwrap();
+}
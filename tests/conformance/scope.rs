@@ -0,0 +1,155 @@
+use openshmem_benchmark::{
+    osm_box::OsmBox,
+    osm_scope::{OsmScope, catch_panic},
+    osm_team::OsmTeam,
+    osm_wrapper::ShmemCmp,
+};
+
+use crate::{next_pe, pattern, prev_pe, sym_vec};
+
+tests![
+    my_pe_and_num_pes,
+    barrier_all_completes_nbi_puts,
+    quiet_completes_nbi_puts,
+    sync_all_after_quiet,
+    fence_orders_puts_to_a_pe,
+    library_version_and_name,
+    node_rank_and_size,
+    hostnames,
+    symmetric_heap_size,
+    runtime_info,
+    caught_panic_on_spawned_thread,
+];
+
+fn my_pe_and_num_pes(scope: &OsmScope) {
+    let num_pes = scope.num_pes();
+    assert!(num_pes >= 1);
+    assert!((0..num_pes).contains(&scope.my_pe()));
+
+    // every PE number is taken exactly once
+    let src = sym_vec(scope, [scope.my_pe()]);
+    let mut all = sym_vec(scope, vec![-1; num_pes as usize]);
+    OsmTeam::world().fcollect(&src, &mut all);
+    assert_eq!(all.to_vec(), (0..num_pes).collect::<Vec<_>>());
+}
+
+fn barrier_all_completes_nbi_puts(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..64).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 64]);
+    scope.barrier_all();
+
+    src.put_to_nbi(&mut dst, next_pe(scope));
+    scope.barrier_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn quiet_completes_nbi_puts(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..64).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 64]);
+    let mut flag = OsmBox::new(0i64, scope);
+    scope.barrier_all();
+
+    let handle = src.put_to_nbi(&mut dst, next_pe(scope));
+    scope.quiet();
+    assert!(handle.is_complete());
+    // the data is delivered before the flag is even sent
+    flag.atomic_set(1, next_pe(scope));
+
+    flag.wait_until(ShmemCmp::Eq, 1);
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn sync_all_after_quiet(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..16).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    scope.barrier_all();
+
+    src.put_to_nbi(&mut dst, next_pe(scope));
+    scope.quiet();
+    scope.sync_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn fence_orders_puts_to_a_pe(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..256).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 256]);
+    let mut flag = OsmBox::new(0i64, scope);
+    scope.barrier_all();
+
+    src.put_to_nbi(&mut dst, next_pe(scope));
+    scope.fence();
+    flag.atomic_set(1, next_pe(scope));
+
+    flag.wait_until(ShmemCmp::Eq, 1);
+    let mut received = sym_vec(scope, vec![0u64; 256]);
+    received.get_from(&dst, scope.my_pe());
+    let prev = prev_pe(scope);
+    assert!(
+        received
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(prev, i))
+    );
+}
+
+fn library_version_and_name(scope: &OsmScope) {
+    let (major, minor) = scope.library_version();
+    assert!(major >= 1, "version {major}.{minor}");
+    assert!(!scope.library_name().is_empty());
+}
+
+fn node_rank_and_size(scope: &OsmScope) {
+    let size = scope.node_size();
+    assert!((1..=scope.num_pes()).contains(&size));
+    assert!((0..size).contains(&scope.node_rank()));
+}
+
+fn hostnames(scope: &OsmScope) {
+    let hostname = scope.hostname();
+    assert!(!hostname.is_empty());
+
+    let hostnames = scope.hostnames();
+    assert_eq!(hostnames.len(), scope.num_pes() as usize);
+    assert_eq!(hostnames[scope.my_pe() as usize], hostname);
+}
+
+fn symmetric_heap_size(scope: &OsmScope) {
+    let requested = ["SHMEM_SYMMETRIC_SIZE", "SMA_SYMMETRIC_SIZE"]
+        .iter()
+        .any(|name| std::env::var(name).is_ok());
+    if let Some(size) = scope.symmetric_heap_size() {
+        assert!(requested && size > 0);
+    }
+}
+
+fn runtime_info(scope: &OsmScope) {
+    let info = scope.runtime_info();
+    assert_eq!(info.library_name, scope.library_name());
+    assert_eq!(info.library_version, scope.library_version());
+    assert_eq!(info.my_pe, scope.my_pe());
+    assert_eq!(info.num_pes, scope.num_pes());
+    assert_eq!(info.node_rank, scope.node_rank());
+    assert_eq!(info.node_size, scope.node_size());
+    assert_eq!(info.hostnames, scope.hostnames());
+    assert_eq!(info.symmetric_heap_size, scope.symmetric_heap_size());
+}
+
+fn caught_panic_on_spawned_thread(scope: &OsmScope) {
+    // only a panic caught through catch_panic leaves the job running
+    assert!(catch_panic(|| panic!("caught on the PE's thread")).is_err());
+    let result =
+        std::thread::spawn(|| catch_panic(|| panic!("caught on a spawned thread")).is_err()).join();
+    assert!(matches!(result, Ok(true)));
+
+    let src = sym_vec(scope, [scope.my_pe()]);
+    let mut dst = sym_vec(scope, [-1]);
+    scope.barrier_all();
+    src.put_to(&mut dst, next_pe(scope));
+    scope.barrier_all();
+    assert_eq!(*dst[0], prev_pe(scope));
+}
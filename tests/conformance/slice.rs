@@ -0,0 +1,461 @@
+use openshmem_benchmark::{
+    osm_collective::CollectiveScratch, osm_error::ShmemError,
+    osm_ffi::_SHMEM_REDUCE_MIN_WRKDATA_SIZE, osm_reduce::Commutativity, osm_scope::OsmScope,
+    osm_slice::OsmSlice,
+};
+use ref_cast::RefCast;
+
+use crate::{next_pe, p_sync, pattern, prev_pe, sym_vec};
+
+tests![
+    from_raw_parts,
+    index_ranges,
+    cast_slice,
+    put_to,
+    put_to_nbi,
+    get_from,
+    get_from_nbi,
+    try_put_to,
+    try_put_to_nbi,
+    try_get_from,
+    try_get_from_nbi,
+    broadcast,
+    all_gather,
+    all_to_all,
+    all_reduce,
+    all_reduce_with,
+    fetch_add,
+    try_fetch_add,
+    compare_and_swap,
+    try_compare_and_swap,
+];
+
+fn from_raw_parts(scope: &OsmScope) {
+    let mut vec = sym_vec(scope, 0..8u64);
+
+    let slice = unsafe { OsmSlice::from_raw_parts(vec.as_mut_ptr(), 8) };
+    assert_eq!(slice.to_vec(), (0..8).collect::<Vec<_>>());
+
+    let slice = unsafe { OsmSlice::from_raw_parts_mut(vec.as_mut_ptr().add(2), 4) };
+    slice.fill(0);
+    assert_eq!(vec.to_vec(), [0, 1, 0, 0, 0, 0, 6, 7]);
+}
+
+fn index_ranges(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..16).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    assert_eq!(*src[3], pattern(scope.my_pe(), 3));
+    assert_eq!(src[2..5].len(), 3);
+    assert_eq!(src[12..].len(), 4);
+    assert_eq!(src[..4].len(), 4);
+    assert_eq!(src[4..=7].len(), 4);
+    assert_eq!(src[..=3].len(), 4);
+    scope.barrier_all();
+
+    // sub-slices keep their offset on the remote side
+    let next = next_pe(scope);
+    src[..4].put_to(&mut dst[12..], next);
+    src[4..8].put_to(&mut dst[8..12], next);
+    src[8..=11].put_to(&mut dst[4..=7], next);
+    src[12..].put_to(&mut dst[..=3], next);
+    scope.barrier_all();
+
+    let prev = prev_pe(scope);
+    for block in 0..4 {
+        let expected: Vec<_> = (0..4).map(|i| pattern(prev, (3 - block) * 4 + i)).collect();
+        assert_eq!(dst[block * 4..block * 4 + 4].to_vec(), expected);
+    }
+}
+
+fn cast_slice(scope: &OsmScope) {
+    let mut vec = sym_vec(scope, [0x0102_0304_0506_0708u64, 0]);
+
+    let bytes = vec.cast_slice::<u8>();
+    assert_eq!(bytes.len(), 16);
+    assert_eq!(bytes[..8].to_vec(), 0x0102_0304_0506_0708u64.to_ne_bytes());
+
+    let words = vec.cast_slice_mut::<u32>();
+    assert_eq!(words.len(), 4);
+    *words[2] = u32::MAX;
+    *words[3] = u32::MAX;
+    assert_eq!(*vec[1], u64::MAX);
+
+    // an empty cast does not inherit a misaligned address
+    let empty = vec.cast_slice::<u8>()[1..1].cast_slice::<u64>();
+    assert!(empty.is_empty());
+    assert!(empty.as_ptr().is_aligned());
+}
+
+fn put_to(scope: &OsmScope) {
+    let mut src = sym_vec(scope, (0..1024).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 1024]);
+    scope.barrier_all();
+
+    src.put_to(&mut dst, next_pe(scope));
+    // the source may be reused as soon as a blocking put returns
+    src.fill(0);
+    scope.barrier_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn put_to_nbi(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..1024).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 1024]);
+    scope.barrier_all();
+
+    let handle = src.put_to_nbi(&mut dst, next_pe(scope));
+    scope.quiet();
+    assert!(handle.is_complete());
+    scope.sync_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn get_from(scope: &OsmScope) {
+    let remote = sym_vec(scope, (0..1024).map(|i| pattern(scope.my_pe(), i)));
+    scope.barrier_all();
+
+    let mut local = vec![0u64; 1024];
+    OsmSlice::ref_cast_mut(&mut local[..]).get_from(&remote, next_pe(scope));
+
+    let next = next_pe(scope);
+    assert!(
+        local
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(next, i))
+    );
+    assert!(
+        remote
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(scope.my_pe(), i))
+    );
+}
+
+fn get_from_nbi(scope: &OsmScope) {
+    let remote = sym_vec(scope, (0..1024).map(|i| pattern(scope.my_pe(), i)));
+    let mut local = sym_vec(scope, vec![0u64; 1024]);
+    scope.barrier_all();
+
+    let handles = [
+        local[..512].get_from_nbi(&remote[..512], next_pe(scope)),
+        local[512..].get_from_nbi(&remote[512..], next_pe(scope)),
+    ];
+    scope.quiet();
+    assert!(handles.iter().all(|handle| handle.is_complete()));
+
+    let next = next_pe(scope);
+    assert!(
+        local
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(next, i))
+    );
+}
+
+fn try_put_to(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    src.try_put_to(&mut dst, next_pe(scope)).unwrap();
+    let err = src.try_put_to(&mut dst[..4], next_pe(scope)).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ShmemError::Size {
+                expected: 64,
+                actual: 32,
+                ..
+            }
+        ),
+        "{err}"
+    );
+    let err = src.try_put_to(&mut dst, scope.num_pes()).unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+    // process-local memory is not symmetric
+    let mut local = [0u64; 8];
+    let err = src
+        .try_put_to(OsmSlice::ref_cast_mut(&mut local[..]), next_pe(scope))
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Inaccessible { .. }), "{err}");
+    scope.barrier_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn try_put_to_nbi(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    let handle = src.try_put_to_nbi(&mut dst, next_pe(scope)).unwrap();
+    let err = src
+        .try_put_to_nbi(&mut dst[1..], next_pe(scope))
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { .. }), "{err}");
+    let err = src.try_put_to_nbi(&mut dst, -1).unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+    handle.wait(scope);
+    scope.sync_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
+
+fn try_get_from(scope: &OsmScope) {
+    let remote = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut local = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    local.try_get_from(&remote, next_pe(scope)).unwrap();
+    let err = local
+        .try_get_from(&remote[..7], next_pe(scope))
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { .. }), "{err}");
+    let err = local.try_get_from(&remote, scope.num_pes()).unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+
+    let next = next_pe(scope);
+    assert!(
+        local
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(next, i))
+    );
+}
+
+fn try_get_from_nbi(scope: &OsmScope) {
+    let remote = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut local = sym_vec(scope, vec![0u64; 8]);
+    scope.barrier_all();
+
+    let handle = local.try_get_from_nbi(&remote, next_pe(scope)).unwrap();
+    let err = local
+        .try_get_from_nbi(&remote[4..], next_pe(scope))
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { .. }), "{err}");
+    handle.wait(scope);
+
+    let next = next_pe(scope);
+    assert!(
+        local
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(next, i))
+    );
+}
+
+fn broadcast(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..16).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    scope.barrier_all();
+
+    src.broadcast(&mut dst, 0, 0, 0, scope.num_pes());
+    scope.barrier_all();
+
+    // the root's destination is left alone
+    if scope.my_pe() != 0 {
+        assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(0, i)));
+    }
+}
+
+fn all_gather(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let src = sym_vec(scope, (0..16).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16 * num_pes]);
+    let mut p_sync = p_sync(scope);
+
+    assert_eq!(src.all_gather(&mut dst, scope, &mut p_sync), 1);
+
+    for (pe, block) in dst.chunks(16).enumerate() {
+        assert!(
+            block
+                .iter()
+                .enumerate()
+                .all(|(i, &v)| v == pattern(pe as i32, i))
+        );
+    }
+}
+
+fn all_to_all(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    // block `pe` of src goes to `pe`, and comes back as block `me` there
+    let src = sym_vec(scope, (0..4 * num_pes).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 4 * num_pes]);
+    let mut p_sync = p_sync(scope);
+
+    let ops = src[..4].all_to_all(&mut dst, 0, 0, num_pes as i32, &mut p_sync, scope);
+    assert_eq!(ops, 1);
+
+    let me = scope.my_pe() as usize;
+    for (pe, block) in dst.chunks(4).enumerate() {
+        let expected: Vec<_> = (0..4).map(|i| pattern(pe as i32, me * 4 + i)).collect();
+        assert_eq!(block, &expected[..]);
+    }
+}
+
+fn all_reduce(scope: &OsmScope) {
+    let num_pes = scope.num_pes();
+    let src = sym_vec(scope, (0..32).map(|i| scope.my_pe() + i));
+    let mut dst = sym_vec(scope, vec![0i32; 32]);
+    let work = (_SHMEM_REDUCE_MIN_WRKDATA_SIZE as usize).max(32 / 2 + 1);
+    let mut p_wrk = sym_vec(scope, vec![0i32; work]);
+    let mut p_sync = p_sync(scope);
+
+    assert_eq!(src.all_reduce(&mut dst, scope, &mut p_wrk, &mut p_sync), 1);
+
+    let pe_sum = num_pes * (num_pes - 1) / 2;
+    assert!(
+        dst.iter()
+            .enumerate()
+            .all(|(i, &v)| v == pe_sum + num_pes * i as i32)
+    );
+}
+
+fn all_reduce_with(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as u64;
+    let src = sym_vec(scope, (0..64).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 64]);
+    let steps = (num_pes as usize).next_power_of_two().trailing_zeros() as usize;
+    let staging = (2 * 64 * 8).max(steps.max(1) * 64 * 8);
+    let mut scratch = CollectiveScratch::with_staging(staging, scope);
+
+    src.all_reduce_with(
+        &mut dst,
+        |a, b| a + b,
+        Commutativity::Commutative,
+        &mut scratch,
+    );
+    let pe_sum = (0..num_pes).map(|pe| pe << 32).sum::<u64>();
+    assert!(
+        dst.iter()
+            .enumerate()
+            .all(|(i, &v)| v == pe_sum + num_pes * i as u64)
+    );
+
+    // keeping the right operand yields the last PE's data
+    src.all_reduce_with(
+        &mut dst,
+        |_, b| *b,
+        Commutativity::NonCommutative,
+        &mut scratch,
+    );
+    let last = num_pes as i32 - 1;
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(last, i)));
+}
+
+fn fetch_add(scope: &OsmScope) {
+    let mut counter32 = sym_vec(scope, [0i32]);
+    let mut counter64 = sym_vec(scope, [0i64]);
+    scope.barrier_all();
+
+    let me = scope.my_pe();
+    let before32 = counter32.fetch_add_i32(me + 1, 0);
+    let before64 = counter64.fetch_add_i64((me as i64 + 1) << 32, 0);
+    assert!(before32 >= 0 && before64 >= 0);
+    scope.barrier_all();
+
+    if me == 0 {
+        let n = scope.num_pes();
+        assert_eq!(*counter32[0], n * (n + 1) / 2);
+        assert_eq!(*counter64[0], ((n * (n + 1) / 2) as i64) << 32);
+    }
+}
+
+fn try_fetch_add(scope: &OsmScope) {
+    let mut counter32 = sym_vec(scope, [0i32, 0]);
+    let mut counter64 = sym_vec(scope, [0i64, 0]);
+    scope.barrier_all();
+
+    counter32[..1].try_fetch_add_i32(1, 0).unwrap();
+    counter64[1..].try_fetch_add_i64(1, 0).unwrap();
+    let err = counter32.try_fetch_add_i32(1, 0).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ShmemError::Size {
+                expected: 4,
+                actual: 8,
+                ..
+            }
+        ),
+        "{err}"
+    );
+    let err = counter64[..1]
+        .try_fetch_add_i64(1, scope.num_pes())
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+    scope.barrier_all();
+
+    if scope.my_pe() == 0 {
+        assert_eq!(counter32.to_vec(), [scope.num_pes(), 0]);
+        assert_eq!(counter64.to_vec(), [0, scope.num_pes() as i64]);
+    }
+}
+
+fn compare_and_swap(scope: &OsmScope) {
+    let mut owner32 = sym_vec(scope, [-1i32]);
+    let mut owner64 = sym_vec(scope, [-1i64]);
+    let mut winners = sym_vec(scope, [0i32]);
+    scope.barrier_all();
+
+    let me = scope.my_pe();
+    if owner32.compare_and_swap_i32(-1, me, 0) == -1 {
+        winners.fetch_add_i32(1, 0);
+    }
+    if owner64.compare_and_swap_i64(-1, me as i64, 0) == -1 {
+        winners.fetch_add_i32(1, 0);
+    }
+    scope.barrier_all();
+
+    if me == 0 {
+        assert_eq!(*winners[0], 2);
+        assert!((0..scope.num_pes()).contains(&*owner32[0]));
+        assert!((0..scope.num_pes() as i64).contains(&*owner64[0]));
+    }
+}
+
+fn try_compare_and_swap(scope: &OsmScope) {
+    let mut owner32 = sym_vec(scope, [-1i32, -1]);
+    let mut owner64 = sym_vec(scope, [-1i64, -1]);
+    scope.barrier_all();
+
+    let me = scope.my_pe();
+    let previous = owner32[1..].try_compare_and_swap_i32(-1, me, 0).unwrap();
+    assert!(previous == -1 || (0..scope.num_pes()).contains(&previous));
+    let previous = owner64[..1]
+        .try_compare_and_swap_i64(-1, me as i64, 0)
+        .unwrap();
+    assert!(previous == -1 || (0..scope.num_pes() as i64).contains(&previous));
+
+    let err = owner64.try_compare_and_swap_i64(-1, 0, 0).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ShmemError::Size {
+                expected: 8,
+                actual: 16,
+                ..
+            }
+        ),
+        "{err}"
+    );
+    let err = owner32[..1]
+        .try_compare_and_swap_i32(-1, 0, -1)
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+    scope.barrier_all();
+
+    if me == 0 {
+        assert_eq!(*owner32[0], -1);
+        assert_ne!(*owner32[1], -1);
+        assert_ne!(*owner64[0], -1);
+        assert_eq!(*owner64[1], -1);
+    }
+}
@@ -0,0 +1,39 @@
+use std::{panic::AssertUnwindSafe, sync::atomic::AtomicI64};
+
+use openshmem_benchmark::{
+    osm_scope::{OsmScope, catch_panic},
+    symmetric_static,
+};
+
+tests![atomic_on_pe_0, get_before_init];
+
+symmetric_static! {
+    static COUNTER: AtomicI64 = AtomicI64::new(0);
+    static NEVER_INITIALIZED: AtomicI64 = AtomicI64::new(0);
+}
+
+fn atomic_on_pe_0(scope: &OsmScope) {
+    COUNTER.init(scope);
+    let counter = COUNTER.get(scope);
+    let before = counter.atomic_fetch(0);
+    scope.barrier_all();
+
+    // one copy on PE 0 with the runtime, the only copy with `sim`
+    loop {
+        let current = counter.atomic_fetch(0);
+        if counter.compare_and_swap(current, current + 1, 0) == current {
+            break;
+        }
+    }
+    scope.barrier_all();
+
+    assert_eq!(counter.atomic_fetch(0), before + i64::from(scope.num_pes()));
+    scope.barrier_all();
+}
+
+fn get_before_init(scope: &OsmScope) {
+    let result = catch_panic(AssertUnwindSafe(|| {
+        NEVER_INITIALIZED.get(scope);
+    }));
+    assert!(result.is_err());
+}
@@ -0,0 +1,775 @@
+use openshmem_benchmark::{
+    osm_barrier::Barrier,
+    osm_collective::{AlltoallvSchedule, CollectiveScratch},
+    osm_error::ShmemError,
+    osm_reduce::{Commutativity, ReduceOp},
+    osm_scope::OsmScope,
+    osm_team::OsmTeam,
+};
+
+use crate::{next_pe, pattern, prev_pe, sym_vec};
+
+const STAGING: usize = 1 << 16;
+
+tests![
+    world,
+    shared,
+    split_strided,
+    split_strided_fails,
+    translate_pe,
+    sync,
+    barrier_wait,
+    broadcast,
+    try_broadcast,
+    fcollect,
+    try_fcollect,
+    collect,
+    try_collect,
+    gather,
+    try_gather,
+    scatter,
+    try_scatter,
+    alltoallv,
+    try_alltoallv,
+    inclusive_scan,
+    exclusive_scan,
+    try_scan,
+    all_reduce_with,
+    try_all_reduce_with,
+];
+
+/// PEs 0, 2, 4, .. of the world team; `None` on the others.
+fn evens() -> Option<OsmTeam> {
+    let world = OsmTeam::world();
+    let team = world
+        .split_strided(0, 2, (world.num_pes() + 1) / 2)
+        .unwrap();
+    (world.my_pe() % 2 == 0).then_some(team)
+}
+
+fn world(scope: &OsmScope) {
+    let world = OsmTeam::world();
+    assert_eq!(world.my_pe(), scope.my_pe());
+    assert_eq!(world.num_pes(), scope.num_pes());
+}
+
+fn shared(scope: &OsmScope) {
+    let shared = OsmTeam::shared();
+    assert!((1..=scope.num_pes()).contains(&shared.num_pes()));
+    assert!((0..shared.num_pes()).contains(&shared.my_pe()));
+    assert_eq!(
+        shared.translate_pe(shared.my_pe(), OsmTeam::world()),
+        scope.my_pe()
+    );
+}
+
+fn split_strided(scope: &OsmScope) {
+    let me = scope.my_pe();
+    match evens() {
+        Some(team) => {
+            assert_eq!(team.my_pe(), me / 2);
+            assert_eq!(team.num_pes(), (scope.num_pes() + 1) / 2);
+        }
+        None => assert_eq!(me % 2, 1),
+    }
+
+    // a team of one
+    let last = scope.num_pes() - 1;
+    let team = OsmTeam::world().split_strided(last, 1, 1).unwrap();
+    if me == last {
+        assert_eq!(team.my_pe(), 0);
+        assert_eq!(team.num_pes(), 1);
+    }
+}
+
+fn split_strided_fails(scope: &OsmScope) {
+    // more members than the parent has fails on every PE
+    let err = OsmTeam::world()
+        .split_strided(0, 1, scope.num_pes() + 1)
+        .unwrap_err();
+    assert!(
+        matches!(err, ShmemError::Runtime { pe, .. } if pe == scope.my_pe()),
+        "{err}"
+    );
+}
+
+fn translate_pe(scope: &OsmScope) {
+    let world = OsmTeam::world();
+    let me = scope.my_pe();
+    assert_eq!(world.translate_pe(me, world), me);
+
+    if let Some(team) = evens() {
+        assert_eq!(world.translate_pe(me, team), me / 2);
+        for rank in 0..team.num_pes() {
+            assert_eq!(team.translate_pe(rank, world), rank * 2);
+        }
+        // odd PEs are not members
+        for pe in (1..scope.num_pes()).step_by(2) {
+            assert_eq!(world.translate_pe(pe, team), -1);
+        }
+    }
+}
+
+fn sync(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..16).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    scope.barrier_all();
+
+    // sync only orders, the quiet completes the put
+    src.put_to_nbi(&mut dst, next_pe(scope));
+    scope.quiet();
+    OsmTeam::world().sync();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+    OsmTeam::world().try_sync().unwrap();
+
+    if let Some(team) = evens() {
+        team.sync();
+        team.try_sync().unwrap();
+    }
+}
+
+fn barrier_wait(scope: &OsmScope) {
+    let src = sym_vec(scope, [pattern(scope.my_pe(), 0)]);
+    let mut dst = sym_vec(scope, [0u64]);
+    scope.barrier_all();
+
+    src.put_to(&mut dst, next_pe(scope));
+    scope.quiet();
+    let mut world = OsmTeam::world();
+    world.wait();
+
+    assert_eq!(*dst[0], pattern(prev_pe(scope), 0));
+}
+
+fn broadcast(scope: &OsmScope) {
+    let last = scope.num_pes() - 1;
+    let src = sym_vec(scope, (0..16).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    scope.barrier_all();
+
+    OsmTeam::world().broadcast(&src, &mut dst, last);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(last, i)));
+
+    // team ranks, not PE numbers, pick the root
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    scope.barrier_all();
+    if let Some(team) = evens() {
+        let root = team.num_pes() - 1;
+        team.broadcast(&src, &mut dst, root);
+        assert!(
+            dst.iter()
+                .enumerate()
+                .all(|(i, &v)| v == pattern(root * 2, i))
+        );
+    }
+}
+
+fn try_broadcast(scope: &OsmScope) {
+    let src = sym_vec(scope, (0..16).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    scope.barrier_all();
+
+    // detected locally, before anyone enters the collective
+    let err = OsmTeam::world()
+        .try_broadcast(&src, &mut dst[..8], 0)
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { .. }), "{err}");
+
+    OsmTeam::world().try_broadcast(&src, &mut dst, 0).unwrap();
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(0, i)));
+}
+
+fn fcollect(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let src = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8 * num_pes]);
+    scope.barrier_all();
+
+    OsmTeam::world().fcollect(&src, &mut dst);
+    for (pe, block) in dst.chunks(8).enumerate() {
+        assert!(
+            block
+                .iter()
+                .enumerate()
+                .all(|(i, &v)| v == pattern(pe as i32, i))
+        );
+    }
+}
+
+fn try_fcollect(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let src = sym_vec(scope, (0..8).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 8 * num_pes]);
+    scope.barrier_all();
+
+    let err = OsmTeam::world()
+        .try_fcollect(&src, &mut dst[..8 * num_pes - 1])
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { .. }), "{err}");
+
+    if let Some(team) = evens() {
+        team.try_fcollect(&src, &mut dst).unwrap();
+        for rank in 0..team.num_pes() {
+            let block = &dst[rank as usize * 8..rank as usize * 8 + 8];
+            assert!(
+                block
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &v)| v == pattern(rank * 2, i))
+            );
+        }
+    }
+}
+
+fn collect(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let me = scope.my_pe();
+    let total = num_pes * (num_pes + 1) / 2;
+    // PE i contributes i + 1 elements, from an allocation of the same size everywhere
+    let src = sym_vec(scope, (0..num_pes).map(|i| pattern(me, i)));
+    let src = &src[..me as usize + 1];
+    let mut dst = sym_vec(scope, vec![0u64; total]);
+    let mut scratch = CollectiveScratch::new(scope);
+
+    let counts = OsmTeam::world().collect(src, &mut dst, &mut scratch);
+    assert_eq!(counts, (1..=num_pes).collect::<Vec<_>>());
+
+    let mut offset = 0;
+    for (pe, count) in counts.into_iter().enumerate() {
+        let expected: Vec<_> = (0..count).map(|i| pattern(pe as i32, i)).collect();
+        assert_eq!(dst[offset..offset + count].to_vec(), expected);
+        offset += count;
+    }
+
+    let err = OsmTeam::world()
+        .try_collect(src, &mut dst[..total - 1], &mut scratch)
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { .. }), "{err}");
+}
+
+fn try_collect(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let last = scope.num_pes() - 1;
+    let src = sym_vec(scope, (0..4).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 4 * num_pes]);
+    let mut scratch = CollectiveScratch::new(scope);
+
+    // only the last PE is short, and every PE reports it instead of hanging
+    let len = if scope.my_pe() == last {
+        4 * num_pes - 1
+    } else {
+        4 * num_pes
+    };
+    let err = OsmTeam::world()
+        .try_collect(&src, &mut dst[..len], &mut scratch)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ShmemError::Size {
+            operation: "shmem_collectmem",
+            pe: last,
+            expected: 32 * num_pes,
+            actual: 32 * num_pes - 8,
+        }
+    );
+
+    let counts = OsmTeam::world()
+        .try_collect(&src, &mut dst, &mut scratch)
+        .unwrap();
+    assert_eq!(counts, vec![4; num_pes]);
+}
+
+fn gather(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let me = scope.my_pe();
+    let root = scope.num_pes() - 1;
+    let total = num_pes * (num_pes + 1) / 2;
+    let src = sym_vec(scope, (0..num_pes).map(|i| pattern(me, i)));
+    let src = &src[..me as usize + 1];
+    let mut dst = sym_vec(scope, vec![0u64; total]);
+    let mut scratch = CollectiveScratch::new(scope);
+
+    let counts = OsmTeam::world().gather(src, &mut dst, root, &mut scratch);
+    assert_eq!(counts, (1..=num_pes).collect::<Vec<_>>());
+
+    if me == root {
+        let expected: Vec<_> = counts
+            .iter()
+            .enumerate()
+            .flat_map(|(pe, &count)| (0..count).map(move |i| pattern(pe as i32, i)))
+            .collect();
+        assert_eq!(dst.to_vec(), expected);
+    } else {
+        assert!(dst.iter().all(|&v| v == 0));
+    }
+
+    let mut dst = sym_vec(scope, vec![0u64; total]);
+    OsmTeam::world()
+        .try_gather(src, &mut dst, root, &mut scratch)
+        .unwrap();
+}
+
+fn try_gather(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let root = scope.num_pes() - 1;
+    let src = sym_vec(scope, (0..4).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 4 * num_pes]);
+    let mut scratch = CollectiveScratch::new(scope);
+
+    let err = OsmTeam::world()
+        .try_gather(&src, &mut dst[..4 * num_pes - 1], root, &mut scratch)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ShmemError::Size {
+            operation: "gather",
+            pe: root,
+            expected: 32 * num_pes,
+            actual: 32 * num_pes - 8,
+        }
+    );
+
+    // a short buffer on a non-root member fails on every member too
+    let len = if scope.my_pe() == 0 { 3 } else { 4 * num_pes };
+    let err = OsmTeam::world()
+        .try_gather(&src, &mut dst[..len], root, &mut scratch)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ShmemError::Size {
+            operation: "gather",
+            pe: 0,
+            expected: 32,
+            actual: 24,
+        }
+    );
+
+    let err = OsmTeam::world()
+        .try_gather(&src, &mut dst, root + 1, &mut scratch)
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+
+    OsmTeam::world()
+        .try_gather(&src, &mut dst, root, &mut scratch)
+        .unwrap();
+}
+
+fn scatter(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let me = scope.my_pe() as usize;
+    let total = num_pes * (num_pes + 1) / 2;
+    // PE i receives i + 1 elements, the ones starting at i * (i + 1) / 2
+    let counts: Vec<usize> = (1..=num_pes).collect();
+    let src = sym_vec(scope, (0..total).map(|i| pattern(0, i)));
+    let mut dst = sym_vec(scope, vec![0u64; num_pes]);
+    let mut scratch = CollectiveScratch::new(scope);
+
+    let received = OsmTeam::world().scatter(&src, &mut dst, &counts, 0, &mut scratch);
+    assert_eq!(received, me + 1);
+    let start = me * (me + 1) / 2;
+    let expected: Vec<_> = (start..start + me + 1).map(|i| pattern(0, i)).collect();
+    assert_eq!(dst[..me + 1].to_vec(), expected);
+
+    let received = OsmTeam::world()
+        .try_scatter(&src, &mut dst, &counts, 0, &mut scratch)
+        .unwrap();
+    assert_eq!(received, me + 1);
+}
+
+fn alltoallv(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let me = scope.my_pe() as usize;
+    // PE i sends j + 1 elements to PE j, tagged with both ends
+    let send_counts: Vec<usize> = (1..=num_pes).collect();
+    let send_displs: Vec<usize> = (0..num_pes).map(|j| j * (j + 1) / 2).collect();
+    let total = num_pes * (num_pes + 1) / 2;
+    let src = sym_vec(
+        scope,
+        (0..num_pes).flat_map(|j| (0..j + 1).map(move |i| pattern(me as i32, j * 1000 + i))),
+    );
+    assert_eq!(src.len(), total);
+    let mut scratch = CollectiveScratch::with_staging(STAGING, scope);
+
+    // symmetric allocations have the same size everywhere
+    let dst_len = num_pes * num_pes;
+    for schedule in [
+        AlltoallvSchedule::Linear,
+        AlltoallvSchedule::Pairwise,
+        AlltoallvSchedule::Bruck,
+    ] {
+        let mut dst = sym_vec(scope, vec![0u64; dst_len]);
+        scope.barrier_all();
+
+        let received = OsmTeam::world().alltoallv(
+            &src,
+            &send_counts,
+            &send_displs,
+            &mut dst,
+            schedule,
+            &mut scratch,
+        );
+        assert_eq!(received, vec![me + 1; num_pes], "{schedule:?}");
+
+        let expected: Vec<_> = (0..num_pes)
+            .flat_map(|pe| (0..me + 1).map(move |i| pattern(pe as i32, me * 1000 + i)))
+            .collect();
+        assert_eq!(dst[..expected.len()].to_vec(), expected, "{schedule:?}");
+    }
+
+    let mut dst = sym_vec(scope, vec![0u64; dst_len]);
+    scope.barrier_all();
+    OsmTeam::world()
+        .try_alltoallv(
+            &src,
+            &send_counts,
+            &send_displs,
+            &mut dst,
+            AlltoallvSchedule::default(),
+            &mut scratch,
+        )
+        .unwrap();
+}
+
+fn try_scatter(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let counts = vec![2; num_pes];
+    let src = sym_vec(scope, (0..2 * num_pes).map(|i| pattern(0, i)));
+    let mut dst = sym_vec(scope, vec![0u64; 2]);
+    let mut scratch = CollectiveScratch::new(scope);
+
+    // counts are only read on the root, the others learn from it
+    let err = OsmTeam::world()
+        .try_scatter(&src, &mut dst, &counts[1..], 0, &mut scratch)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ShmemError::Size {
+            operation: "scatter",
+            pe: 0,
+            expected: 8 * num_pes,
+            actual: 8 * (num_pes - 1),
+        }
+    );
+
+    let err = OsmTeam::world()
+        .try_scatter(&src[1..], &mut dst, &counts, 0, &mut scratch)
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ShmemError::Size {
+                operation: "scatter",
+                pe: 0,
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let received = OsmTeam::world()
+        .try_scatter(&src, &mut dst, &counts, 0, &mut scratch)
+        .unwrap();
+    assert_eq!(received, 2);
+}
+
+fn try_alltoallv(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as usize;
+    let last = scope.num_pes() - 1;
+    let me = scope.my_pe();
+    let counts = vec![2; num_pes];
+    let displs: Vec<usize> = (0..num_pes).map(|j| 2 * j).collect();
+    let src = sym_vec(scope, (0..2 * num_pes).map(|i| pattern(me, i)));
+    let mut dst = sym_vec(scope, vec![0u64; 2 * num_pes]);
+    let mut scratch = CollectiveScratch::with_staging(STAGING, scope);
+    let world = OsmTeam::world();
+
+    // a bad argument on one PE fails the call on all of them
+    let short = if me == last { num_pes - 1 } else { num_pes };
+    let err = world
+        .try_alltoallv(
+            &src,
+            &counts[..short],
+            &displs,
+            &mut dst,
+            AlltoallvSchedule::Linear,
+            &mut scratch,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ShmemError::Size {
+            operation: "alltoallv",
+            pe: last,
+            expected: 8 * num_pes,
+            actual: 8 * num_pes - 8,
+        }
+    );
+
+    let src_len = if me == last {
+        2 * num_pes - 1
+    } else {
+        2 * num_pes
+    };
+    let err = world
+        .try_alltoallv(
+            &src[..src_len],
+            &counts,
+            &displs,
+            &mut dst,
+            AlltoallvSchedule::Pairwise,
+            &mut scratch,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, ShmemError::Size { operation: "alltoallv", pe, .. } if pe == last),
+        "{err}"
+    );
+
+    // detected after the counts are exchanged
+    for schedule in [AlltoallvSchedule::Linear, AlltoallvSchedule::Bruck] {
+        let dst_len = if me == last {
+            2 * num_pes - 1
+        } else {
+            2 * num_pes
+        };
+        let err = world
+            .try_alltoallv(
+                &src,
+                &counts,
+                &displs,
+                &mut dst[..dst_len],
+                schedule,
+                &mut scratch,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ShmemError::Size {
+                operation: "alltoallv",
+                pe: last,
+                expected: 16 * num_pes,
+                actual: 16 * num_pes - 8,
+            },
+            "{schedule:?}"
+        );
+    }
+
+    // and in the first Bruck round that does not fit the staging area
+    if num_pes > 1 {
+        let mut small = CollectiveScratch::with_staging(8, scope);
+        let err = world
+            .try_alltoallv(
+                &src,
+                &counts,
+                &displs,
+                &mut dst,
+                AlltoallvSchedule::Bruck,
+                &mut small,
+            )
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ShmemError::Size {
+                    operation: "alltoallv",
+                    actual: 8,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
+
+    let received = world
+        .try_alltoallv(
+            &src,
+            &counts,
+            &displs,
+            &mut dst,
+            AlltoallvSchedule::Bruck,
+            &mut scratch,
+        )
+        .unwrap();
+    assert_eq!(received, counts);
+    for pe in 0..num_pes {
+        let expected = [
+            pattern(pe as i32, 2 * me as usize),
+            pattern(pe as i32, 2 * me as usize + 1),
+        ];
+        assert_eq!(dst[2 * pe..2 * pe + 2].to_vec(), expected);
+    }
+}
+
+fn inclusive_scan(scope: &OsmScope) {
+    let me = scope.my_pe() as i64;
+    let src = sym_vec(scope, (0..8).map(|i| me + 1 + i));
+    let mut dst = sym_vec(scope, vec![0i64; 8]);
+    let mut scratch = CollectiveScratch::with_staging(STAGING, scope);
+
+    OsmTeam::world().inclusive_scan(&src, &mut dst, ReduceOp::Sum, &mut scratch);
+    let expected: Vec<_> = (0..8)
+        .map(|i| (0..=me).map(|pe| pe + 1 + i).sum::<i64>())
+        .collect();
+    assert_eq!(dst.to_vec(), expected);
+
+    OsmTeam::world().inclusive_scan(&src, &mut dst, ReduceOp::Max, &mut scratch);
+    assert_eq!(dst.to_vec(), src.to_vec());
+}
+
+fn exclusive_scan(scope: &OsmScope) {
+    let me = scope.my_pe() as i64;
+    let src = sym_vec(scope, (0..8).map(|i| me + 1 + i));
+    let mut dst = sym_vec(scope, vec![-1i64; 8]);
+    let mut scratch = CollectiveScratch::with_staging(STAGING, scope);
+
+    OsmTeam::world().exclusive_scan(&src, &mut dst, ReduceOp::Sum, &mut scratch);
+    let expected: Vec<_> = (0..8)
+        .map(|i| (0..me).map(|pe| pe + 1 + i).sum::<i64>())
+        .collect();
+    assert_eq!(dst.to_vec(), expected);
+
+    // PE 0 gets the identity
+    OsmTeam::world().exclusive_scan(&src, &mut dst, ReduceOp::Min, &mut scratch);
+    if me == 0 {
+        assert_eq!(dst.to_vec(), vec![i64::MAX; 8]);
+    } else {
+        assert_eq!(dst.to_vec(), (0..8).map(|i| 1 + i).collect::<Vec<_>>());
+    }
+}
+
+fn try_scan(scope: &OsmScope) {
+    let me = scope.my_pe();
+    let last = scope.num_pes() - 1;
+    let src = sym_vec(scope, (0..8).map(|i| me as i64 + i));
+    let mut dst = sym_vec(scope, vec![0i64; 8]);
+    let mut scratch = CollectiveScratch::with_staging(STAGING, scope);
+    let world = OsmTeam::world();
+
+    let len = if me == last { 7 } else { 8 };
+    let expected = ShmemError::Size {
+        operation: "inclusive_scan",
+        pe: last,
+        expected: 64,
+        actual: 56,
+    };
+    let err = world
+        .try_inclusive_scan(&src, &mut dst[..len], ReduceOp::Sum, &mut scratch)
+        .unwrap_err();
+    assert_eq!(err, expected);
+
+    // one round per doubling, each needing the whole src
+    if scope.num_pes() > 1 {
+        let mut small = CollectiveScratch::with_staging(8, scope);
+        let err = world
+            .try_exclusive_scan(&src, &mut dst, ReduceOp::Sum, &mut small)
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ShmemError::Size {
+                    operation: "exclusive_scan",
+                    actual: 8,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
+
+    world
+        .try_inclusive_scan(&src, &mut dst, ReduceOp::Max, &mut scratch)
+        .unwrap();
+    assert_eq!(dst.to_vec(), src.to_vec());
+    world
+        .try_exclusive_scan(&src, &mut dst, ReduceOp::Sum, &mut scratch)
+        .unwrap();
+    let expected: Vec<_> = (0..8)
+        .map(|i| (0..me as i64).map(|pe| pe + i).sum::<i64>())
+        .collect();
+    assert_eq!(dst.to_vec(), expected);
+}
+
+fn all_reduce_with(scope: &OsmScope) {
+    let num_pes = scope.num_pes() as u64;
+    let src = sym_vec(scope, (0..64).map(|i| pattern(scope.my_pe(), i)));
+    let mut dst = sym_vec(scope, vec![0u64; 64]);
+    let mut scratch = CollectiveScratch::with_staging(STAGING, scope);
+
+    OsmTeam::world().all_reduce_with(
+        &src,
+        &mut dst,
+        |a, b| (*a).max(*b),
+        Commutativity::Commutative,
+        &mut scratch,
+    );
+    let last = num_pes as i32 - 1;
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(last, i)));
+
+    // keeping the left operand yields PE 0's data
+    OsmTeam::world().all_reduce_with(
+        &src,
+        &mut dst,
+        |a, _| *a,
+        Commutativity::NonCommutative,
+        &mut scratch,
+    );
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(0, i)));
+}
+
+fn try_all_reduce_with(scope: &OsmScope) {
+    let me = scope.my_pe();
+    let src = sym_vec(scope, (0..16).map(|i| pattern(me, i)));
+    let mut dst = sym_vec(scope, vec![0u64; 16]);
+    let mut scratch = CollectiveScratch::with_staging(STAGING, scope);
+    let world = OsmTeam::world();
+
+    let len = if me == 0 { 15 } else { 16 };
+    for commutativity in [Commutativity::Commutative, Commutativity::NonCommutative] {
+        let err = world
+            .try_all_reduce_with(
+                &src,
+                &mut dst[..len],
+                |a, b| (*a).max(*b),
+                commutativity,
+                &mut scratch,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ShmemError::Size {
+                operation: "all_reduce_with",
+                pe: 0,
+                expected: 128,
+                actual: 120,
+            },
+            "{commutativity:?}"
+        );
+    }
+
+    // the non-commutative result is broadcast from staging, even on one PE
+    let mut none = CollectiveScratch::new(scope);
+    let err = world
+        .try_all_reduce_with(
+            &src,
+            &mut dst,
+            |a, _| *a,
+            Commutativity::NonCommutative,
+            &mut none,
+        )
+        .unwrap_err();
+    assert!(matches!(err, ShmemError::Size { actual: 0, .. }), "{err}");
+
+    world
+        .try_all_reduce_with(
+            &src,
+            &mut dst,
+            |a, b| (*a).max(*b),
+            Commutativity::Commutative,
+            &mut scratch,
+        )
+        .unwrap();
+    let last = scope.num_pes() - 1;
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(last, i)));
+}
@@ -0,0 +1,98 @@
+use openshmem_benchmark::{
+    osm_error::ShmemError, osm_scope::OsmScope, osm_slice::OsmSlice, osm_vec::ShVec,
+};
+use ref_cast::RefCast;
+
+use crate::{next_pe, pattern, prev_pe};
+
+tests![
+    new_and_push,
+    with_capacity_and_resize_with,
+    try_with_capacity,
+    try_resize_with,
+    deref_to_slice,
+];
+
+fn new_and_push(scope: &OsmScope) {
+    let mut vec = ShVec::new(scope);
+    assert_eq!(vec.len(), 0);
+
+    // growing reallocates collectively, so every PE pushes the same number
+    for i in 0..100 {
+        vec.push(pattern(scope.my_pe(), i));
+    }
+    assert_eq!(vec.len(), 100);
+    assert!(
+        vec.iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(scope.my_pe(), i))
+    );
+
+    // still symmetric after the reallocations
+    scope.barrier_all();
+    let mut remote = [0u64; 100];
+    OsmSlice::ref_cast_mut(&mut remote[..]).get_from(&vec, next_pe(scope));
+    assert!(
+        remote
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == pattern(next_pe(scope), i))
+    );
+}
+
+fn with_capacity_and_resize_with(scope: &OsmScope) {
+    let mut vec = ShVec::with_capacity(8, scope);
+    assert_eq!(vec.len(), 0);
+
+    vec.resize_with(8, || 7u32);
+    assert_eq!(vec.len(), 8);
+    assert!(vec.iter().all(|&v| v == 7));
+
+    vec.resize_with(32, || 9u32);
+    assert_eq!(vec[..8].to_vec(), [7; 8]);
+    assert!(vec[8..].iter().all(|&v| v == 9));
+
+    vec.resize_with(4, || 0u32);
+    assert_eq!(vec.len(), 4);
+}
+
+fn try_with_capacity(scope: &OsmScope) {
+    let vec = ShVec::<u64>::try_with_capacity(16, scope).unwrap();
+    assert_eq!(vec.len(), 0);
+
+    // rejected before reaching the allocator, so no PE allocates
+    let err = ShVec::<u64>::try_with_capacity(usize::MAX / 4, scope).unwrap_err();
+    assert!(
+        matches!(err, ShmemError::Alloc { align: 8, pe, .. } if pe == scope.my_pe()),
+        "{err}"
+    );
+}
+
+fn try_resize_with(scope: &OsmScope) {
+    let mut vec = ShVec::with_capacity(4, scope);
+    vec.try_resize_with(64, || 3u64).unwrap();
+    assert_eq!(vec.len(), 64);
+    assert!(vec.iter().all(|&v| v == 3));
+
+    let err = vec.try_resize_with(usize::MAX / 4, || 0).unwrap_err();
+    assert!(matches!(err, ShmemError::Alloc { .. }), "{err}");
+    assert_eq!(vec.len(), 64);
+}
+
+fn deref_to_slice(scope: &OsmScope) {
+    let mut src = ShVec::with_capacity(16, scope);
+    src.resize_with(16, || 0u64);
+    for (i, value) in src.iter_mut().enumerate() {
+        *value = pattern(scope.my_pe(), i);
+    }
+    let mut dst = ShVec::with_capacity(16, scope);
+    dst.resize_with(16, || 0u64);
+    scope.barrier_all();
+
+    let slice: &OsmSlice<u64> = &src;
+    slice.put_to(&mut dst, next_pe(scope));
+    scope.barrier_all();
+
+    let prev = prev_pe(scope);
+    assert!(dst.iter().enumerate().all(|(i, &v)| v == pattern(prev, i)));
+}
@@ -0,0 +1,203 @@
+use openshmem_benchmark::{
+    osm_box::OsmBox,
+    osm_error::ShmemError,
+    osm_scope::OsmScope,
+    osm_team::OsmTeam,
+    osm_wrapper::{OsmWrapper, ShmemCmp},
+};
+use ref_cast::RefCast;
+
+use crate::{next_pe, pattern, prev_pe, sym_vec};
+
+tests![
+    deref_eq_and_display,
+    put_to,
+    put_to_nbi,
+    get_from,
+    get_from_nbi,
+    get_value,
+    try_put_to,
+    try_get_value,
+    fetch_add,
+    compare_and_swap,
+    atomic_fetch_and_set,
+    wait_until,
+];
+
+fn deref_eq_and_display(scope: &OsmScope) {
+    let mut a = OsmBox::new(5i64, scope);
+    let b = OsmBox::new(7i64, scope);
+    assert_ne!(*a, *b);
+
+    **a = 7;
+    assert_eq!(*a, *b);
+    assert_eq!(a.to_string(), "7");
+}
+
+fn put_to(scope: &OsmScope) {
+    let src = OsmBox::new(pattern(scope.my_pe(), 0), scope);
+    let mut dst = OsmBox::new(0u64, scope);
+    scope.barrier_all();
+
+    src.put_to(&mut dst, next_pe(scope));
+    scope.barrier_all();
+
+    assert_eq!(**dst, pattern(prev_pe(scope), 0));
+}
+
+fn put_to_nbi(scope: &OsmScope) {
+    let src = OsmBox::new([pattern(scope.my_pe(), 0); 32], scope);
+    let mut dst = OsmBox::new([0u64; 32], scope);
+    let mut flag = OsmBox::new(0i64, scope);
+    scope.barrier_all();
+
+    let handle = src.put_to_nbi(&mut dst, next_pe(scope));
+    handle.wait(scope);
+    flag.atomic_set(1, next_pe(scope));
+
+    flag.wait_until(ShmemCmp::Eq, 1);
+    assert_eq!(**dst, [pattern(prev_pe(scope), 0); 32]);
+}
+
+fn get_from(scope: &OsmScope) {
+    let remote = OsmBox::new(pattern(scope.my_pe(), 0), scope);
+    scope.barrier_all();
+
+    let mut local = 0u64;
+    let size = std::mem::size_of::<u64>();
+    OsmWrapper::ref_cast_mut(&mut local).get_from(&remote, size, next_pe(scope));
+    assert_eq!(local, pattern(next_pe(scope), 0));
+    // a get must not write to the remote side
+    assert_eq!(**remote, pattern(scope.my_pe(), 0));
+}
+
+fn get_from_nbi(scope: &OsmScope) {
+    let remote = OsmBox::new([pattern(scope.my_pe(), 0); 32], scope);
+    scope.barrier_all();
+
+    let mut local = [0u64; 32];
+    let size = std::mem::size_of_val(&local);
+    let handle = OsmWrapper::ref_cast_mut(&mut local).get_from_nbi(&remote, size, next_pe(scope));
+    scope.quiet();
+    assert!(handle.is_complete());
+    assert_eq!(local, [pattern(next_pe(scope), 0); 32]);
+    assert_eq!(**remote, [pattern(scope.my_pe(), 0); 32]);
+}
+
+fn get_value(scope: &OsmScope) {
+    let boxed = OsmBox::new(pattern(scope.my_pe(), 0), scope);
+    scope.barrier_all();
+
+    assert_eq!(boxed.get_value(next_pe(scope)), pattern(next_pe(scope), 0));
+    assert_eq!(boxed.get_value(scope.my_pe()), pattern(scope.my_pe(), 0));
+}
+
+fn try_put_to(scope: &OsmScope) {
+    let src = OsmBox::new(pattern(scope.my_pe(), 0), scope);
+    let mut dst = OsmBox::new(0u64, scope);
+    scope.barrier_all();
+
+    src.try_put_to(&mut dst, next_pe(scope)).unwrap();
+    let err = src.try_put_to(&mut dst, scope.num_pes()).unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+    let err = src.try_put_to(&mut dst, -1).unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+    scope.barrier_all();
+
+    assert_eq!(**dst, pattern(prev_pe(scope), 0));
+}
+
+fn try_get_value(scope: &OsmScope) {
+    let boxed = OsmBox::new(pattern(scope.my_pe(), 0), scope);
+    scope.barrier_all();
+
+    assert_eq!(
+        boxed.try_get_value(next_pe(scope)),
+        Ok(pattern(next_pe(scope), 0))
+    );
+    let err = boxed.try_get_value(scope.num_pes()).unwrap_err();
+    assert!(matches!(err, ShmemError::InvalidPe { .. }), "{err}");
+}
+
+fn fetch_add(scope: &OsmScope) {
+    let mut counter = OsmBox::new(0i64, scope);
+    scope.barrier_all();
+
+    let fetched = counter.fetch_add(1, 0);
+    scope.barrier_all();
+
+    // every PE saw a different count
+    let src = sym_vec(scope, [fetched]);
+    let mut all = sym_vec(scope, vec![0i64; scope.num_pes() as usize]);
+    OsmTeam::world().fcollect(&src, &mut all);
+    all.sort();
+    assert_eq!(
+        all.to_vec(),
+        (0..scope.num_pes() as i64).collect::<Vec<_>>()
+    );
+    if scope.my_pe() == 0 {
+        assert_eq!(**counter, scope.num_pes() as i64);
+    }
+}
+
+fn compare_and_swap(scope: &OsmScope) {
+    let mut owner = OsmBox::new(-1i64, scope);
+    let mut winners = OsmBox::new(0i64, scope);
+    scope.barrier_all();
+
+    let me = scope.my_pe() as i64;
+    if owner.compare_and_swap(-1, me, 0) == -1 {
+        winners.fetch_add(1, 0);
+    }
+    // the losers see the winner's value
+    assert_ne!(owner.compare_and_swap(-1, me, 0), -1);
+    scope.barrier_all();
+
+    if scope.my_pe() == 0 {
+        assert_eq!(**winners, 1);
+        assert!((0..scope.num_pes() as i64).contains(&**owner));
+    }
+}
+
+fn atomic_fetch_and_set(scope: &OsmScope) {
+    let mut value = OsmBox::new(0i64, scope);
+    scope.barrier_all();
+
+    value.atomic_set(pattern(scope.my_pe(), 1) as i64, next_pe(scope));
+    scope.barrier_all();
+
+    assert_eq!(
+        value.atomic_fetch(scope.my_pe()),
+        pattern(prev_pe(scope), 1) as i64
+    );
+    assert_eq!(
+        value.atomic_fetch(next_pe(scope)),
+        pattern(scope.my_pe(), 1) as i64
+    );
+}
+
+fn wait_until(scope: &OsmScope) {
+    let mut flag = OsmBox::new(0i64, scope);
+    scope.barrier_all();
+
+    // a token passed around the ring, so every wait is released by a put
+    // from the previous PE
+    if scope.my_pe() != 0 {
+        flag.wait_until(ShmemCmp::Eq, 10);
+    }
+    flag.atomic_set(10, next_pe(scope));
+    if scope.my_pe() == 0 {
+        flag.wait_until(ShmemCmp::Eq, 10);
+    }
+
+    for (cmp, value) in [
+        (ShmemCmp::Eq, 10),
+        (ShmemCmp::Ne, 0),
+        (ShmemCmp::Gt, 9),
+        (ShmemCmp::Ge, 10),
+        (ShmemCmp::Lt, 11),
+        (ShmemCmp::Le, 10),
+    ] {
+        flag.wait_until(cmp, value);
+    }
+}
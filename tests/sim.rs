@@ -0,0 +1,194 @@
+//! Tests of the simulator's checkers and fault injection on programs the
+//! conformance suite must not contain, such as racy ones.
+//!
+//! ```text
+//! cargo test --no-default-features --features sim --test sim
+//! ```
+
+#![cfg(feature = "sim")]
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use openshmem_benchmark::{
+    osm_box::OsmBox,
+    osm_scope::OsmScope,
+    osm_sim::{self, FaultConfig, Race, SimConfig},
+    osm_vec::ShVec,
+    osm_wrapper::ShmemCmp,
+};
+
+/// Run `f` on `num_pes` race-checked PEs and return the races found.
+fn races(num_pes: usize, f: impl Fn(&OsmScope) + Sync) -> Vec<Race> {
+    let config = SimConfig {
+        num_pes,
+        check_races: true,
+        ..Default::default()
+    };
+    osm_sim::run(config, |scope| f(&scope)).races
+}
+
+fn buffer(scope: &OsmScope, value: u64) -> ShVec<'_, u64> {
+    let mut vec = ShVec::with_capacity(8, scope);
+    vec.resize_with(8, || value);
+    vec
+}
+
+#[test]
+fn nbi_put_then_get_without_quiet() {
+    let races = races(2, |scope| {
+        let src = buffer(scope, 1);
+        let mut dst = buffer(scope, 0);
+        let mut local = buffer(scope, 0);
+        scope.barrier_all();
+        if scope.my_pe() == 0 {
+            src.put_to_nbi(&mut dst, 1);
+            local.get_from(&dst, 1);
+        }
+        scope.barrier_all();
+    });
+
+    assert_eq!(races.len(), 1, "{races:#?}");
+    let race = &races[0];
+    assert_eq!(race.target_pe, 1);
+    assert_eq!(race.bytes.len(), 64);
+    assert_eq!(
+        (race.first.pe, race.first.operation, race.first.kind),
+        (0, "shmem_putmem_nbi", "write")
+    );
+    assert!(!race.first.completed);
+    assert_eq!(
+        (race.second.pe, race.second.operation, race.second.kind),
+        (0, "shmem_getmem", "read")
+    );
+}
+
+#[test]
+fn unsynchronized_puts_to_the_same_bytes() {
+    let races = races(3, |scope| {
+        let src = buffer(scope, scope.my_pe() as u64);
+        let mut dst = buffer(scope, 0);
+        scope.barrier_all();
+        if scope.my_pe() != 2 {
+            src.put_to(&mut dst, 2);
+        }
+        scope.barrier_all();
+    });
+
+    assert_eq!(races.len(), 1, "{races:#?}");
+    let race = &races[0];
+    assert_eq!(race.target_pe, 2);
+    assert_eq!(race.bytes.len(), 64);
+    let mut pes = [race.first.pe, race.second.pe];
+    pes.sort();
+    assert_eq!(pes, [0, 1]);
+    for access in [&race.first, &race.second] {
+        assert_eq!((access.operation, access.kind), ("shmem_putmem", "write"));
+    }
+}
+
+#[test]
+fn fenced_accesses_do_not_race() {
+    let races = races(3, |scope| {
+        let src = buffer(scope, scope.my_pe() as u64);
+        let mut dst = buffer(scope, 0);
+        let mut local = buffer(scope, 0);
+        let mut flag = OsmBox::new(0i64, scope);
+        scope.barrier_all();
+        match scope.my_pe() {
+            // the fence completes the put before the get and the flag
+            0 => {
+                src.put_to_nbi(&mut dst, 2);
+                scope.fence();
+                local.get_from(&dst, 2);
+                flag.atomic_set(1, 1);
+            }
+            1 => {
+                flag.wait_until(ShmemCmp::Eq, 1);
+                src.put_to(&mut dst, 2);
+            }
+            _ => {}
+        }
+        scope.barrier_all();
+    });
+
+    assert!(races.is_empty(), "{races:#?}");
+}
+
+/// Whether PE 1 reads stale data after PE 0 sends it with non-blocking puts,
+/// completed with a quiet if `quiet`, and both PEs `sync_all`.
+fn stale_after_sync_all(seed: u64, quiet: bool) -> bool {
+    let stale = AtomicBool::new(false);
+    let config = SimConfig {
+        num_pes: 2,
+        faults: FaultConfig {
+            seed,
+            delay_nbi: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    osm_sim::run(config, |scope| {
+        let src = buffer(&scope, 1);
+        let mut dst = buffer(&scope, 0);
+        scope.barrier_all();
+        if scope.my_pe() == 0 {
+            for i in 0..8 {
+                src[i..i + 1].put_to_nbi(&mut dst[i..i + 1], 1);
+            }
+            if quiet {
+                scope.quiet();
+            }
+        }
+        // synchronizes, but completes nothing
+        scope.sync_all();
+        if scope.my_pe() == 1 && dst.iter().any(|&v| v != 1) {
+            stale.store(true, Ordering::Relaxed);
+        }
+        scope.sync_all();
+    });
+    stale.into_inner()
+}
+
+#[test]
+fn delayed_nbi_exposes_missing_quiet() {
+    assert!((0..16).any(|seed| stale_after_sync_all(seed, false)));
+    assert!((0..16).all(|seed| !stale_after_sync_all(seed, true)));
+}
+
+#[test]
+fn stalled_pe_delays_barrier_exit() {
+    const BARRIERS: u32 = 10;
+    let max_stall = Duration::from_millis(20);
+    let waited = |stall_pes: Vec<i32>| {
+        let config = SimConfig {
+            num_pes: 2,
+            faults: FaultConfig {
+                stall_pes,
+                stall_probability: 1.0,
+                max_stall,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let waited = std::sync::Mutex::new(Duration::ZERO);
+        osm_sim::run(config, |scope| {
+            scope.barrier_all();
+            let start = Instant::now();
+            for _ in 0..BARRIERS {
+                scope.barrier_all();
+            }
+            if scope.my_pe() == 0 {
+                *waited.lock().unwrap() = start.elapsed();
+            }
+        });
+        waited.into_inner().unwrap()
+    };
+
+    // PE 0 never stalls itself, it waits for PE 1 at every barrier
+    let stalled = waited(vec![1]);
+    assert!(stalled >= max_stall, "{stalled:?}");
+    assert!(waited(Vec::new()) < stalled);
+}